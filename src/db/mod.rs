@@ -1,10 +1,11 @@
 //! Database module for the Playlist UI.
 //!
-//! Provides persistent storage for tag trees using the Sled embedded
-//! database. Tag trees (genre and creator hierarchies) are cached here
-//! to avoid rebuilding on every launch.
+//! Provides persistent storage using the Sled embedded database. Tag trees
+//! (genre and creator hierarchies) are cached here to avoid rebuilding on
+//! every launch; user settings such as export path rewrite rules are kept
+//! alongside them.
 //!
 //! Sub-modules:
-//!     sled_store — Sled-backed key-value store for tag tree persistence
+//!     sled_store — Sled-backed key-value store for tag trees and settings
 
 pub mod sled_store;
@@ -1,9 +1,10 @@
 //! Sled-backed persistent store for the Playlist UI.
 //!
 //! Wraps a Sled embedded database to provide save/load operations for
-//! genre and creator tag trees. Trees are serialised with bincode. The
-//! database is not incrementally updated — it must be deleted and rebuilt
-//! when file metadata changes.
+//! genre and creator tag trees and for user settings such as path rewrite
+//! rules. Values are serialised with bincode. The tag trees are not
+//! incrementally updated — they must be deleted and rebuilt when file
+//! metadata changes.
 //!
 //! Public API:
 //!     SledStore              — database handle
@@ -13,12 +14,56 @@
 //!     clear_genre_tree       — remove the genre tree from the DB
 //!     save_creator_tag_tree  — persist a creator hierarchy
 //!     load_creator_tag_tree  — load a cached creator hierarchy
+//!     save_path_rewrite_rules — persist the export path rewrite rules
+//!     load_path_rewrite_rules — load the export path rewrite rules
+//!     save_sync_template     — persist the device sync filename template
+//!     load_sync_template     — load the device sync filename template
+//!     save_table_export_columns — persist the columns chosen for table export
+//!     load_table_export_columns — load the columns chosen for table export
+//!     save_player_profiles   — persist the external player profiles
+//!     load_player_profiles   — load the external player profiles
+//!     save_selected_player   — persist the name of the chosen player profile
+//!     load_selected_player   — load the name of the chosen player profile
+//!     save_mpd_settings      — persist the MPD server settings
+//!     load_mpd_settings      — load the MPD server settings
+//!     save_playlists         — persist the right panel's named playlists
+//!     load_playlists         — load the right panel's named playlists
+//!     LoadError              — why stored playlists could not be loaded
+//!     save_active_playlist   — persist the index of the shown playlist
+//!     load_active_playlist   — load the index of the shown playlist
+//!     save_playlist_columns  — persist the playlist table's column layout
+//!     load_playlist_columns  — load the playlist table's column layout
 
-use crate::gui::TagTreeNode;
+use crate::fs::playlist_export::PathRewriteRule;
+use crate::fs::table_export::TableColumn;
+use crate::gui::{ColumnLayout, SavedPlaylist, TagTreeNode};
+use crate::player::external::PlayerProfile;
+use crate::player::mpd::MpdSettings;
 use bincode;
 use bincode::{config::standard, decode_from_slice, encode_to_vec};
 use sled::{Db, IVec};
 
+/// Key of the saved playlists. Its version changes whenever the encoding of
+/// `SavedPlaylist` (or of a type inside it, such as `SortColumn`) changes, so
+/// a value written by another version is never decoded as the wrong shape.
+pub(crate) const PLAYLISTS_KEY: &str = "playlists_v1";
+
+/// Why a stored value could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    Read(sled::Error),
+    Decode(bincode::error::DecodeError),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Read(e) => write!(f, "read failed: {e}"),
+            LoadError::Decode(e) => write!(f, "unreadable data: {e}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SledStore {
     db: Db,
@@ -30,21 +75,34 @@ impl SledStore {
         Ok(Self { db })
     }
 
+    /// Encodes `value` with bincode and stores it under `key`.
+    fn save<T: bincode::Encode + ?Sized>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<(), sled::Error> {
+        let data = encode_to_vec(value, standard()).unwrap();
+        self.db.insert(key, data)?;
+        Ok(())
+    }
+
+    /// Loads the value stored under `key`; `None` when there is none or it
+    /// does not decode.
+    fn load<T: bincode::Decode<()>>(&self, key: &str) -> Option<T> {
+        self.db.get(key).ok().flatten().and_then(|ivec: IVec| {
+            decode_from_slice(&ivec, standard()).ok().map(|(val, _len)| val)
+        })
+    }
+
     pub fn save_genre_tag_tree(
         &self,
         roots: &[TagTreeNode],
     ) -> Result<(), sled::Error> {
-        let config = standard();
-        let data = encode_to_vec(roots, config).unwrap();
-        self.db.insert("tag_tree", data)?;
-        Ok(())
+        self.save("tag_tree", roots)
     }
 
     pub fn load_genre_tag_tree(&self) -> Option<Vec<TagTreeNode>> {
-        let config = standard();
-        self.db.get("tag_tree").ok().flatten().and_then(|ivec: IVec| {
-            decode_from_slice(&ivec, config).ok().map(|(val, _len)| val)
-        })
+        self.load("tag_tree")
     }
 
     #[allow(dead_code)]
@@ -57,17 +115,125 @@ impl SledStore {
         &self,
         roots: &[TagTreeNode],
     ) -> Result<(), sled::Error> {
-        let config = standard();
-        let data = encode_to_vec(roots, config).unwrap();
-        self.db.insert("creator_tag_tree", data)?;
-        Ok(())
+        self.save("creator_tag_tree", roots)
     }
 
     pub fn load_creator_tag_tree(&self) -> Option<Vec<TagTreeNode>> {
-        let config = standard();
-        self.db.get("creator_tag_tree").ok().flatten().and_then(|ivec: IVec| {
-            decode_from_slice(&ivec, config).ok().map(|(val, _len)| val)
-        })
+        self.load("creator_tag_tree")
+    }
+
+    pub fn save_path_rewrite_rules(
+        &self,
+        rules: &[PathRewriteRule],
+    ) -> Result<(), sled::Error> {
+        self.save("path_rewrite_rules", rules)
+    }
+
+    pub fn load_path_rewrite_rules(&self) -> Option<Vec<PathRewriteRule>> {
+        self.load("path_rewrite_rules")
+    }
+
+    pub fn save_sync_template(
+        &self,
+        template: &str,
+    ) -> Result<(), sled::Error> {
+        self.save("sync_template", template)
+    }
+
+    pub fn load_sync_template(&self) -> Option<String> {
+        self.load("sync_template")
+    }
+
+    pub fn save_table_export_columns(
+        &self,
+        columns: &[TableColumn],
+    ) -> Result<(), sled::Error> {
+        self.save("table_export_columns", columns)
+    }
+
+    pub fn load_table_export_columns(&self) -> Option<Vec<TableColumn>> {
+        self.load("table_export_columns")
+    }
+
+    pub fn save_player_profiles(
+        &self,
+        profiles: &[PlayerProfile],
+    ) -> Result<(), sled::Error> {
+        self.save("player_profiles", profiles)
+    }
+
+    pub fn load_player_profiles(&self) -> Option<Vec<PlayerProfile>> {
+        self.load("player_profiles")
+    }
+
+    pub fn save_selected_player(&self, name: &str) -> Result<(), sled::Error> {
+        self.save("selected_player", name)
+    }
+
+    pub fn load_selected_player(&self) -> Option<String> {
+        self.load("selected_player")
+    }
+
+    pub fn save_mpd_settings(
+        &self,
+        settings: &MpdSettings,
+    ) -> Result<(), sled::Error> {
+        self.save("mpd_settings", settings)
+    }
+
+    pub fn load_mpd_settings(&self) -> Option<MpdSettings> {
+        self.load("mpd_settings")
+    }
+
+    pub fn save_playlist_columns(
+        &self,
+        layout: &[ColumnLayout],
+    ) -> Result<(), sled::Error> {
+        self.save("playlist_columns", layout)
+    }
+
+    pub fn load_playlist_columns(&self) -> Option<Vec<ColumnLayout>> {
+        self.load("playlist_columns")
+    }
+
+    pub fn save_playlists(
+        &self,
+        playlists: &[SavedPlaylist],
+    ) -> Result<(), sled::Error> {
+        self.save(PLAYLISTS_KEY, playlists)
+    }
+
+    /// The underlying database, for tests that inspect or damage stored
+    /// values.
+    #[cfg(test)]
+    pub(crate) fn raw(&self) -> &Db {
+        &self.db
+    }
+
+    /// Loads the saved playlists: `Ok(None)` when none were saved, and an
+    /// error, rather than nothing, when they cannot be read, so the caller
+    /// does not overwrite them.
+    pub fn load_playlists(
+        &self,
+    ) -> Result<Option<Vec<SavedPlaylist>>, LoadError> {
+        let Some(ivec) = self.db.get(PLAYLISTS_KEY).map_err(LoadError::Read)?
+        else {
+            return Ok(None);
+        };
+        decode_from_slice(&ivec, standard())
+            .map(|(val, _len)| Some(val))
+            .map_err(LoadError::Decode)
+    }
+
+    pub fn save_active_playlist(
+        &self,
+        index: usize,
+    ) -> Result<(), sled::Error> {
+        self.save("active_playlist", &index)
+    }
+
+    pub fn load_active_playlist(&self) -> Option<usize> {
+        self.load("active_playlist")
     }
 }
 
@@ -103,4 +269,139 @@ mod tests {
             println!("First genre label: {}", first_genre.label);
         }
     }
+
+    /// Asserts that `load` finds nothing in a fresh store, and `value` once
+    /// `save` stored it.
+    fn assert_round_trip<T: PartialEq + std::fmt::Debug>(
+        value: T,
+        save: impl Fn(&SledStore, &T) -> Result<(), sled::Error>,
+        load: impl Fn(&SledStore) -> Option<T>,
+    ) {
+        let temp_dir = TempDir::new().unwrap();
+        let sled_path = temp_dir.path().join("sled_test_db");
+        let sled_store = SledStore::new(sled_path.to_str().unwrap()).unwrap();
+        assert!(load(&sled_store).is_none());
+        save(&sled_store, &value).unwrap();
+        assert_eq!(load(&sled_store), Some(value));
+    }
+
+    #[test]
+    fn test_save_and_load_settings() {
+        use crate::gui::PlaylistColumn;
+        assert_round_trip(
+            vec![PathRewriteRule {
+                name: "Media box".to_string(),
+                source_prefix: "/mnt/nas/music".to_string(),
+                target_prefix: "/storage/music".to_string(),
+                windows_separators: false,
+            }],
+            |store, rules| store.save_path_rewrite_rules(rules),
+            SledStore::load_path_rewrite_rules,
+        );
+        assert_round_trip(
+            "{index:02} {title}.{ext}".to_string(),
+            |store, template| store.save_sync_template(template),
+            SledStore::load_sync_template,
+        );
+        assert_round_trip(
+            vec![TableColumn::Title, TableColumn::DurationMs],
+            |store, columns| store.save_table_export_columns(columns),
+            SledStore::load_table_export_columns,
+        );
+        assert_round_trip(
+            crate::player::external::default_profiles(),
+            |store, profiles| store.save_player_profiles(profiles),
+            SledStore::load_player_profiles,
+        );
+        assert_round_trip(
+            "mpv".to_string(),
+            |store, name| store.save_selected_player(name),
+            SledStore::load_selected_player,
+        );
+        assert_round_trip(
+            MpdSettings {
+                host: "listening-room".to_string(),
+                password: "secret".to_string(),
+                music_directory: "/mnt/nas/music".to_string(),
+                ..Default::default()
+            },
+            SledStore::save_mpd_settings,
+            SledStore::load_mpd_settings,
+        );
+        assert_round_trip(
+            vec![
+                ColumnLayout {
+                    column: PlaylistColumn::Title,
+                    visible: true,
+                    width: 3,
+                },
+                ColumnLayout {
+                    column: PlaylistColumn::Directory,
+                    visible: false,
+                    width: 1,
+                },
+            ],
+            |store, layout| store.save_playlist_columns(layout),
+            SledStore::load_playlist_columns,
+        );
+        assert_round_trip(
+            2,
+            |store, index| store.save_active_playlist(*index),
+            SledStore::load_active_playlist,
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_playlists() {
+        use crate::gui::{SortColumn, SortKey, SortOrder};
+        let temp_dir = TempDir::new().unwrap();
+        let sled_path = temp_dir.path().join("sled_test_db");
+        let sled_store = SledStore::new(sled_path.to_str().unwrap()).unwrap();
+        assert!(matches!(sled_store.load_playlists(), Ok(None)));
+
+        let playlists = vec![
+            SavedPlaylist {
+                name: "Party".to_string(),
+                paths: vec!["/music/b.mp3".into(), "/music/a.mp3".into()],
+                sort_column: SortColumn::Album,
+                sort_order: SortOrder::Desc,
+                then_by: vec![
+                    SortKey {
+                        column: SortColumn::DiscNumber,
+                        order: SortOrder::Asc,
+                    },
+                    SortKey {
+                        column: SortColumn::TrackNumber,
+                        order: SortOrder::Desc,
+                    },
+                ],
+                shuffled: true,
+                title: "Friday Set".to_string(),
+                creator: "DJ".to_string(),
+                annotation: String::new(),
+                missing_files: vec!["/music/gone.mp3".into()],
+            },
+            SavedPlaylist {
+                name: "Commute".to_string(),
+                paths: vec![],
+                sort_column: SortColumn::Directory,
+                sort_order: SortOrder::Asc,
+                then_by: vec![],
+                shuffled: false,
+                title: String::new(),
+                creator: String::new(),
+                annotation: String::new(),
+                missing_files: vec![],
+            },
+        ];
+        sled_store.save_playlists(&playlists).unwrap();
+        assert_eq!(sled_store.load_playlists().unwrap(), Some(playlists));
+
+        // Data that does not decode is an error, not an empty store
+        sled_store.db.insert(PLAYLISTS_KEY, vec![0xff; 3]).unwrap();
+        assert!(matches!(
+            sled_store.load_playlists(),
+            Err(LoadError::Decode(_))
+        ));
+    }
 }
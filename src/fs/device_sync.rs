@@ -0,0 +1,660 @@
+//! Copying playlist tracks to a destination folder (device sync).
+//!
+//! Copies every track of a playlist into one folder, typically on an SD card
+//! or USB stick for a portable player, naming each copy from a filename
+//! template such as `{index:03} - {creator} - {title}.{ext}`. Names are
+//! sanitized for FAT32 and exFAT: characters those filesystems reject are
+//! replaced, trailing dots and spaces are trimmed, reserved device names are
+//! escaped, and names are kept within 255 UTF-16 units.
+//!
+//! A copy whose name is already taken by a different file gets a ` (2)`,
+//! ` (3)`, … suffix, and a destination file with identical contents is
+//! skipped, so syncing the same playlist again only copies what changed. An
+//! M3U8 playlist with relative entries is written beside the copies. Copies
+//! are written to a temporary file and renamed into place, so cancelling
+//! never leaves a truncated track behind.
+//!
+//! Public API:
+//!     DEFAULT_SYNC_TEMPLATE — the filename template used until one is set
+//!     SYNC_PLAYLIST_NAME    — name of the playlist written beside the copies
+//!     SyncSummary           — what a sync copied, skipped, and failed on
+//!     SyncProgress          — progress and result of a background sync
+//!     render_template       — build a track's file name from a template
+//!     sanitize_file_name    — make a file name safe for FAT32 and exFAT
+//!     sync_to_folder        — copy tracks and write the playlist
+//!     sync_to_folder_stream — run a sync, streaming its progress
+
+use crate::fs::playlist_export::{
+    ExportOptions, PlaylistFormat, PlaylistInfo, export_playlist,
+};
+use crate::gui::RightPanelFile;
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, Stream, StreamExt};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The filename template used until the user sets one.
+pub(crate) const DEFAULT_SYNC_TEMPLATE: &str =
+    "{index:03} - {creator} - {title}.{ext}";
+
+/// Name of the playlist written beside the copied tracks.
+pub(crate) const SYNC_PLAYLIST_NAME: &str = "playlist.m3u8";
+
+/// Longest file name FAT32 and exFAT accept, in UTF-16 code units.
+const MAX_NAME_UNITS: usize = 255;
+
+/// Size of the buffer used when copying and comparing files.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Device names Windows reserves on FAT volumes, with or without extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6",
+    "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6",
+    "LPT7", "LPT8", "LPT9",
+];
+
+/// What a sync did, reported once it ends.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct SyncSummary {
+    /// Tracks copied to the destination.
+    pub copied: usize,
+    /// Tracks skipped because an identical copy was already there.
+    pub skipped: usize,
+    /// Tracks (or the playlist) that could not be written, with the reason.
+    pub failed: Vec<(PathBuf, String)>,
+    /// The playlist written beside the copies, unless the sync was
+    /// cancelled or the playlist could not be written.
+    pub playlist: Option<PathBuf>,
+    /// The sync was cancelled before every track was processed.
+    pub cancelled: bool,
+}
+
+/// Progress of a background sync, as reported to the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SyncProgress {
+    /// `done` of `total` tracks have been processed.
+    Running { done: usize, total: usize },
+    /// The sync ended, completely or by cancellation.
+    Finished(SyncSummary),
+    /// The sync could not start; holds a message for the user.
+    Failed(String),
+}
+
+/// Builds the file name for the track at 1-based position `index` from
+/// `template`. Supported placeholders are `{index}`, `{track}`, `{creator}`,
+/// `{album}`, `{title}`, `{genre}`, `{file}` (the original name without its
+/// extension), and `{ext}`; numbers take a width such as `{index:03}`.
+/// Missing tags fall back to "Unknown …" (the title to `{file}`), and
+/// unknown placeholders are kept as written. The result is sanitized with
+/// `sanitize_file_name`.
+pub(crate) fn render_template(
+    template: &str,
+    index: usize,
+    file: &RightPanelFile,
+) -> String {
+    let stem = file
+        .path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let ext = file
+        .path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let text = |value: &Option<String>, fallback: &str| {
+        value
+            .as_deref()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or(fallback)
+            .to_string()
+    };
+    let number = |value: Option<usize>, spec: &str| {
+        let Some(value) = value else {
+            return String::new();
+        };
+        let width = spec.parse::<usize>().unwrap_or(0);
+        if spec.starts_with('0') {
+            format!("{value:0width$}")
+        } else {
+            format!("{value:width$}")
+        }
+    };
+
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            rest = &rest[open..];
+            break;
+        };
+        let placeholder = &after[..close];
+        if placeholder.contains('{') {
+            // An unterminated brace; the next one may open a placeholder
+            rendered.push('{');
+            rest = after;
+            continue;
+        }
+        let (name, spec) =
+            placeholder.split_once(':').unwrap_or((placeholder, ""));
+        let value = match name {
+            "index" => Some(number(Some(index), spec)),
+            "track" => Some(number(file.track_num.map(|n| n as usize), spec)),
+            "creator" => Some(text(&file.creator, "Unknown Artist")),
+            "album" => Some(text(&file.album, "Unknown Album")),
+            "title" => Some(text(&file.title, &stem)),
+            "genre" => Some(text(&file.genre, "Unknown Genre")),
+            "file" => Some(stem.clone()),
+            "ext" => Some(ext.clone()),
+            _ => None,
+        };
+        match value {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[open..open + close + 2]),
+        }
+        rest = &after[close + 1..];
+    }
+    rendered.push_str(rest);
+    sanitize_file_name(&rendered)
+}
+
+/// Makes `name` a valid FAT32 and exFAT file name: control characters and
+/// `" * / : < > ? \ |` become `_`, leading spaces and trailing dots and
+/// spaces are trimmed, reserved device names such as `CON` or `LPT1.mp3` get
+/// a leading `_`, and the name is shortened to 255 UTF-16 units, keeping
+/// its extension.
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '"' | '*' | '/' | ':' | '<' | '>' | '?' | '\\' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let trimmed = replaced.trim_start_matches(' ').trim_end_matches(['.', ' ']);
+    if trimmed.is_empty() {
+        return "_".to_string();
+    }
+
+    let (stem, ext) = split_extension(trimmed);
+    let reserved_stem = stem.split('.').next().unwrap_or_default();
+    let stem = if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(reserved_stem))
+    {
+        format!("_{stem}")
+    } else {
+        stem.to_string()
+    };
+    fit_name(&stem, "", ext)
+}
+
+/// Splits `name` into its stem and its extension (with the dot), treating
+/// only short suffixes as extensions so that long names containing a dot
+/// are not cut at it.
+fn split_extension(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 && name.len() - dot <= 16 => {
+            (&name[..dot], &name[dot..])
+        },
+        _ => (name, ""),
+    }
+}
+
+/// Joins `stem`, `suffix`, and `ext`, shortening `stem` so the whole name
+/// fits in `MAX_NAME_UNITS` UTF-16 units.
+fn fit_name(stem: &str, suffix: &str, ext: &str) -> String {
+    let reserved = suffix.encode_utf16().count() + ext.encode_utf16().count();
+    let budget = MAX_NAME_UNITS.saturating_sub(reserved);
+    if stem.encode_utf16().count() <= budget {
+        return format!("{stem}{suffix}{ext}");
+    }
+    let mut used = 0;
+    let mut short = String::new();
+    for c in stem.chars() {
+        used += c.len_utf16();
+        if used > budget {
+            break;
+        }
+        short.push(c);
+    }
+    // A cut may leave a trailing dot or space where the name ends
+    let short = short.trim_end_matches(['.', ' ']);
+    let short = if short.is_empty() { "_" } else { short };
+    format!("{short}{suffix}{ext}")
+}
+
+/// Returns `true` when the two files have the same size and contents.
+fn files_identical(a: &Path, b: &Path) -> std::io::Result<bool> {
+    if a.metadata()?.len() != b.metadata()?.len() {
+        return Ok(false);
+    }
+    let (mut a, mut b) = (File::open(a)?, File::open(b)?);
+    let (mut buf_a, mut buf_b) = (vec![0; CHUNK_SIZE], vec![0; CHUNK_SIZE]);
+    loop {
+        let read = a.read(&mut buf_a)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut buf_b[..read])?;
+        if buf_a[..read] != buf_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Copies `source` to `dest` through a temporary file in the destination
+/// folder, checking `cancel` between chunks. Returns `Ok(false)`, leaving
+/// `dest` untouched, when the copy was cancelled.
+fn copy_file(
+    source: &Path,
+    dest: &Path,
+    cancel: &AtomicBool,
+) -> std::io::Result<bool> {
+    let part = dest.with_file_name(".playlist_ui_sync.part");
+    let result = (|| {
+        let mut reader = File::open(source)?;
+        let mut writer = File::create(&part)?;
+        let mut buf = vec![0; CHUNK_SIZE];
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(false);
+            }
+            let read = reader.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buf[..read])?;
+        }
+        writer.sync_all()?;
+        drop(writer);
+        std::fs::rename(&part, dest)?;
+        Ok(true)
+    })();
+    if !matches!(result, Ok(true)) {
+        let _ = std::fs::remove_file(&part);
+    }
+    result
+}
+
+/// Copies `files` into `dest_dir`, naming each copy from `template` (see
+/// `render_template`), then writes `SYNC_PLAYLIST_NAME` beside the copies
+/// listing them in order with relative paths. Names already used in this
+/// sync (compared case-insensitively, as FAT does) or taken by a different
+/// file get a numbered suffix; identical files are skipped. `cancel` is
+/// checked between and during copies, and `on_progress` is called with the
+/// number of tracks processed so far. Only failing to create `dest_dir` is
+/// an error; per-track failures are collected in the summary.
+pub(crate) fn sync_to_folder(
+    files: &[RightPanelFile],
+    dest_dir: &Path,
+    template: &str,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(usize),
+) -> std::io::Result<SyncSummary> {
+    std::fs::create_dir_all(dest_dir)?;
+    let mut summary = SyncSummary::default();
+    let mut used_names: HashSet<String> = HashSet::new();
+    let mut entries: Vec<RightPanelFile> = Vec::new();
+
+    for (i, file) in files.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            summary.cancelled = true;
+            break;
+        }
+        if !file.path.is_file() {
+            summary.failed.push((file.path.clone(), "not found".to_string()));
+            on_progress(i + 1);
+            continue;
+        }
+
+        let name = render_template(template, i + 1, file);
+        let (stem, ext) = split_extension(&name);
+        let mut n = 1;
+        let outcome = loop {
+            let candidate = if n == 1 {
+                name.clone()
+            } else {
+                fit_name(stem, &format!(" ({n})"), ext)
+            };
+            n += 1;
+            if !used_names.insert(candidate.to_lowercase()) {
+                continue;
+            }
+            let dest = dest_dir.join(&candidate);
+            if !dest.exists() {
+                break copy_file(&file.path, &dest, cancel)
+                    .map(|copied| copied.then_some((dest, true)));
+            }
+            match files_identical(&file.path, &dest) {
+                Ok(true) => break Ok(Some((dest, false))),
+                Ok(false) => continue,
+                Err(e) => break Err(e),
+            }
+        };
+
+        match outcome {
+            Ok(Some((dest, copied))) => {
+                if copied {
+                    summary.copied += 1;
+                } else {
+                    summary.skipped += 1;
+                }
+                entries.push(RightPanelFile { path: dest, ..file.clone() });
+            },
+            Ok(None) => {
+                summary.cancelled = true;
+                break;
+            },
+            Err(e) => summary.failed.push((file.path.clone(), e.to_string())),
+        }
+        on_progress(i + 1);
+    }
+
+    if !summary.cancelled {
+        let playlist = dest_dir.join(SYNC_PLAYLIST_NAME);
+        let options =
+            ExportOptions { relative_paths: true, ..Default::default() };
+        match export_playlist(
+            PlaylistFormat::M3u,
+            &entries,
+            &playlist,
+            &options,
+            &PlaylistInfo::default(),
+            &mut |_| {},
+        ) {
+            Ok(()) => summary.playlist = Some(playlist),
+            Err(e) => summary.failed.push((playlist, e.to_string())),
+        }
+    }
+    Ok(summary)
+}
+
+/// Runs `sync_to_folder` as a stream for `Task::run`. Yields at most about a
+/// hundred `Running` updates followed by exactly one `Finished` or `Failed`.
+/// Setting `cancel` stops the sync after the current chunk.
+pub(crate) fn sync_to_folder_stream(
+    files: Vec<RightPanelFile>,
+    dest_dir: PathBuf,
+    template: String,
+    cancel: Arc<AtomicBool>,
+) -> impl Stream<Item = SyncProgress> {
+    sync_stream(files.len(), dest_dir, move |dest_dir, on_progress| {
+        sync_to_folder(&files, dest_dir, &template, &cancel, on_progress)
+    })
+}
+
+/// Runs a sync of `total` tracks into `dest_dir` as a stream of
+/// `SyncProgress`, throttling the progress updates `sync` reports. `sync`
+/// runs on its own thread, so each update reaches the stream while the copy
+/// is still running.
+fn sync_stream(
+    total: usize,
+    dest_dir: PathBuf,
+    sync: impl FnOnce(&Path, &mut dyn FnMut(usize)) -> std::io::Result<SyncSummary>
+    + Send
+    + 'static,
+) -> impl Stream<Item = SyncProgress> {
+    iced::stream::channel(16, move |mut sender| async move {
+        // Throttled to about a hundred updates, so unbounded is fine
+        let (progress, mut events) = mpsc::unbounded();
+        std::thread::spawn(move || {
+            let step = (total / 100).max(1);
+            let result = sync(&dest_dir, &mut |done| {
+                if done % step == 0 || done == total {
+                    let _ = progress
+                        .unbounded_send(SyncProgress::Running { done, total });
+                }
+            });
+            let outcome = match result {
+                Ok(summary) => SyncProgress::Finished(summary),
+                Err(e) => SyncProgress::Failed(format!(
+                    "Failed to copy tracks to {}: {e}",
+                    dest_dir.display()
+                )),
+            };
+            let _ = progress.unbounded_send(outcome);
+        });
+        while let Some(event) = events.next().await {
+            if sender.send(event).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(path: &Path) -> RightPanelFile {
+        RightPanelFile { path: path.to_path_buf(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_render_template_fills_placeholders() {
+        let file = RightPanelFile {
+            path: PathBuf::from("/music/01 orig.flac"),
+            creator: Some("AC/DC".to_string()),
+            title: Some("T.N.T.".to_string()),
+            track_num: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_template(DEFAULT_SYNC_TEMPLATE, 7, &file),
+            "007 - AC_DC - T.N.T..flac"
+        );
+        assert_eq!(
+            render_template("{track:02} {album} {file}.{ext}", 1, &file),
+            "04 Unknown Album 01 orig.flac"
+        );
+        // Missing titles fall back to the file name; unknown placeholders
+        // and unterminated braces are kept
+        let bare = track(Path::new("/music/song.mp3"));
+        assert_eq!(
+            render_template("{title} {nope} {index.{ext}", 2, &bare),
+            "song {nope} {index.mp3"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_file_name_for_fat() {
+        assert_eq!(
+            sanitize_file_name("a<b>c:d\"e|f?g*h\\i.mp3"),
+            "a_b_c_d_e_f_g_h_i.mp3"
+        );
+        assert_eq!(sanitize_file_name("tab\there.mp3"), "tab_here.mp3");
+        assert_eq!(sanitize_file_name("  name. . "), "name");
+        assert_eq!(sanitize_file_name("..."), "_");
+        assert_eq!(sanitize_file_name("CON.mp3"), "_CON.mp3");
+        assert_eq!(sanitize_file_name("lpt1"), "_lpt1");
+        assert_eq!(sanitize_file_name("Console.mp3"), "Console.mp3");
+
+        let long = format!("{}.flac", "日".repeat(300));
+        let sanitized = sanitize_file_name(&long);
+        assert!(sanitized.ends_with("日.flac"));
+        assert_eq!(sanitized.encode_utf16().count(), MAX_NAME_UNITS);
+    }
+
+    #[test]
+    fn test_sync_copies_handles_collisions_and_writes_playlist() {
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let a = src.path().join("a.mp3");
+        let b = src.path().join("b.mp3");
+        std::fs::write(&a, b"first").unwrap();
+        std::fs::write(&b, b"second").unwrap();
+        // A different file already holds the first track's name
+        std::fs::write(dest.path().join("Song.mp3"), b"other").unwrap();
+        let files = vec![
+            RightPanelFile { title: Some("Song".into()), ..track(&a) },
+            RightPanelFile { title: Some("song".into()), ..track(&b) },
+        ];
+
+        let cancel = AtomicBool::new(false);
+        let summary = sync_to_folder(
+            &files,
+            dest.path(),
+            "{title}.{ext}",
+            &cancel,
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!((summary.copied, summary.skipped), (2, 0));
+        assert!(summary.failed.is_empty());
+        let read = |name: &str| std::fs::read(dest.path().join(name)).unwrap();
+        assert_eq!(read("Song.mp3"), b"other");
+        assert_eq!(read("Song (2).mp3"), b"first");
+        assert_eq!(read("song (3).mp3"), b"second");
+        assert_eq!(
+            std::fs::read_to_string(summary.playlist.unwrap()).unwrap(),
+            "#EXTM3U\n#EXTINF:-1,Song\nSong (2).mp3\n#EXTINF:-1,song\nsong (3).mp3\n"
+        );
+
+        // Syncing again skips the identical copies
+        let summary = sync_to_folder(
+            &files,
+            dest.path(),
+            "{title}.{ext}",
+            &cancel,
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!((summary.copied, summary.skipped), (0, 2));
+    }
+
+    #[test]
+    fn test_sync_reports_missing_tracks_and_cancellation() {
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let a = src.path().join("a.mp3");
+        std::fs::write(&a, b"data").unwrap();
+        let files = vec![track(&src.path().join("gone.mp3")), track(&a)];
+
+        let cancel = AtomicBool::new(false);
+        let summary = sync_to_folder(
+            &files,
+            dest.path(),
+            "{file}.{ext}",
+            &cancel,
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(summary.copied, 1);
+        assert_eq!(summary.failed.len(), 1);
+        assert!(summary.failed[0].0.ends_with("gone.mp3"));
+
+        let cancel = AtomicBool::new(true);
+        let other = tempfile::tempdir().unwrap();
+        let summary = sync_to_folder(
+            &files,
+            other.path(),
+            "{file}.{ext}",
+            &cancel,
+            &mut |_| {},
+        )
+        .unwrap();
+        assert!(summary.cancelled);
+        assert_eq!(summary.playlist, None);
+        assert_eq!(std::fs::read_dir(other.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_sync_stream_reports_progress_then_summary() {
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let files: Vec<RightPanelFile> = (0..3)
+            .map(|i| {
+                let path = src.path().join(format!("{i}.mp3"));
+                std::fs::write(&path, [i as u8]).unwrap();
+                track(&path)
+            })
+            .collect();
+
+        let stream = sync_to_folder_stream(
+            files,
+            dest.path().to_path_buf(),
+            DEFAULT_SYNC_TEMPLATE.to_string(),
+            Arc::new(AtomicBool::new(false)),
+        );
+        let events: Vec<SyncProgress> =
+            iced::futures::executor::block_on(stream.collect());
+        assert_eq!(
+            events.first(),
+            Some(&SyncProgress::Running { done: 1, total: 3 })
+        );
+        match events.last() {
+            Some(SyncProgress::Finished(summary)) => {
+                assert_eq!(summary.copied, 3);
+            },
+            other => panic!("expected a summary, got {other:?}"),
+        }
+    }
+
+    /// Progress arrives while the copy is still running: the sync below
+    /// pauses after its first track until the test has seen the update.
+    #[test]
+    fn test_sync_stream_reports_progress_while_copying() {
+        use std::sync::mpsc::channel;
+        use std::time::Duration;
+        let src = tempfile::tempdir().unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let files: Vec<RightPanelFile> = (0..2)
+            .map(|i| {
+                let path = src.path().join(format!("{i}.mp3"));
+                std::fs::write(&path, format!("track {i}")).unwrap();
+                track(&path)
+            })
+            .collect();
+        let (seen, wait_for_seen) = channel::<()>();
+        let mut stream = Box::pin(sync_stream(
+            files.len(),
+            dest.path().to_path_buf(),
+            move |dest_dir, on_progress| {
+                let mut delivered = true;
+                let summary = sync_to_folder(
+                    &files,
+                    dest_dir,
+                    DEFAULT_SYNC_TEMPLATE,
+                    &AtomicBool::new(false),
+                    &mut |done| {
+                        on_progress(done);
+                        if done == 1 {
+                            delivered = wait_for_seen
+                                .recv_timeout(Duration::from_secs(5))
+                                .is_ok();
+                        }
+                    },
+                )?;
+                if !delivered {
+                    return Err(std::io::Error::other(
+                        "progress was not delivered",
+                    ));
+                }
+                Ok(summary)
+            },
+        ));
+
+        let first = iced::futures::executor::block_on(stream.next());
+        assert_eq!(first, Some(SyncProgress::Running { done: 1, total: 2 }));
+        seen.send(()).unwrap();
+        let rest: Vec<SyncProgress> =
+            iced::futures::executor::block_on(stream.collect());
+        assert_eq!(rest[0], SyncProgress::Running { done: 2, total: 2 });
+        match rest.last() {
+            Some(SyncProgress::Finished(summary)) => {
+                assert_eq!(summary.copied, 2);
+            },
+            other => panic!("expected a summary, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! `file://` URI encoding and decoding for playlist locations.
+//!
+//! Converts local paths to RFC 3986 `file:` URIs and back. Every byte of the
+//! path that is not an unreserved character, a sub-delimiter, `:`, `@`, or
+//! the `/` separator is percent-encoded, so paths containing spaces, `#`,
+//! `?`, `%`, or non-ASCII characters produce URIs that players accept. Path
+//! bytes are encoded as-is (UTF-8 on every platform we ship), and decoding
+//! reverses the encoding exactly.
+//!
+//! Public API:
+//!     path_to_file_uri    — encode an absolute local path as a `file://` URI
+//!     file_uri_to_path    — decode a `file:` URI back to a local path
+//!     encode_uri_path     — percent-encode a path for use in a URI
+//!     encode_uri_reference — percent-encode a relative path as a URI reference
+//!     has_uri_scheme      — whether a location starts with a URI scheme
+//!     decode_uri_path     — percent-decode a URI path component
+
+use std::path::{Path, PathBuf};
+
+/// Returns `true` for bytes that may appear unencoded in a URI path: RFC 3986
+/// `unreserved` and `sub-delims` characters, plus `:`, `@`, and `/`.
+fn is_path_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric()
+        || matches!(
+            b,
+            b'-' | b'.'
+                | b'_'
+                | b'~'
+                | b'!'
+                | b'$'
+                | b'&'
+                | b'\''
+                | b'('
+                | b')'
+                | b'*'
+                | b'+'
+                | b','
+                | b';'
+                | b'='
+                | b':'
+                | b'@'
+                | b'/'
+        )
+}
+
+/// Returns the bytes of a path with `/` as the separator. On Unix these are
+/// the raw `OsStr` bytes; elsewhere the path is taken as UTF-8 and `\`
+/// separators are converted.
+fn path_bytes(path: &Path) -> Vec<u8> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes().to_vec()
+    }
+    #[cfg(not(unix))]
+    {
+        path.to_string_lossy().replace('\\', "/").into_bytes()
+    }
+}
+
+/// Builds a path from decoded URI bytes, reversing `path_bytes`.
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        PathBuf::from(std::ffi::OsString::from_vec(bytes))
+    }
+    #[cfg(not(unix))]
+    {
+        PathBuf::from(String::from_utf8_lossy(&bytes).replace('/', "\\"))
+    }
+}
+
+/// Percent-encodes a path so it can be used as the path of a URI or as a
+/// relative URI reference. Separators are written as `/`.
+pub(crate) fn encode_uri_path(path: &Path) -> String {
+    let mut encoded = String::new();
+    for b in path_bytes(path) {
+        if is_path_char(b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Percent-encodes a relative path as a relative URI reference. A first
+/// segment containing `:` would read as a scheme, so such references start
+/// with `./` (RFC 3986 §4.2), e.g. `AC 1:00.mp3` → `./AC%201:00.mp3`.
+pub(crate) fn encode_uri_reference(path: &Path) -> String {
+    let encoded = encode_uri_path(path);
+    let first_segment = encoded.split('/').next().unwrap_or_default();
+    if first_segment.contains(':') { format!("./{encoded}") } else { encoded }
+}
+
+/// Whether `location` starts with a URI scheme (`ALPHA *(ALPHA / DIGIT /
+/// "+" / "-" / ".") ":"`) before its first `/`, as opposed to being a
+/// relative reference.
+pub(crate) fn has_uri_scheme(location: &str) -> bool {
+    let first_segment = location.split('/').next().unwrap_or_default();
+    let Some((scheme, _)) = first_segment.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Percent-decodes a URI path component into a local path. A `%` that is
+/// not followed by two hex digits is kept literally, so locations written
+/// by older, unencoded exports still resolve.
+pub(crate) fn decode_uri_path(encoded: &str) -> PathBuf {
+    fn hex_value(b: u8) -> Option<u8> {
+        (b as char).to_digit(16).map(|d| d as u8)
+    }
+
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hi) = bytes.get(i + 1).copied().and_then(hex_value)
+            && let Some(lo) = bytes.get(i + 2).copied().and_then(hex_value)
+        {
+            decoded.push(hi << 4 | lo);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    path_from_bytes(decoded)
+}
+
+/// Encodes an absolute local path as a `file://` URI with an empty
+/// authority, e.g. `/music/a b.mp3` → `file:///music/a%20b.mp3`. Windows
+/// drive paths get a leading `/` (`file:///C:/Music/...`).
+pub(crate) fn path_to_file_uri(path: &Path) -> String {
+    let encoded = encode_uri_path(path);
+    if encoded.starts_with('/') {
+        format!("file://{encoded}")
+    } else {
+        format!("file:///{encoded}")
+    }
+}
+
+/// Decodes a `file:` URI to a local path. Accepts an empty or `localhost`
+/// authority (`file:///p`, `file://localhost/p`) and the authority-less
+/// form (`file:/p`). Returns `None` for other schemes or remote hosts.
+///
+/// A query or fragment is dropped only when the path including it does not
+/// exist, since older, unencoded exports may hold a literal `?` or `#`.
+pub(crate) fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let uri = uri.trim();
+    let scheme = uri.get(..5)?;
+    if !scheme.eq_ignore_ascii_case("file:") {
+        return None;
+    }
+    let rest = &uri[5..];
+    let path = match rest.strip_prefix("//") {
+        Some(after) => {
+            let authority_end = after.find('/').unwrap_or(after.len());
+            let authority = &after[..authority_end];
+            if !authority.is_empty()
+                && !authority.eq_ignore_ascii_case("localhost")
+            {
+                return None;
+            }
+            &after[authority_end..]
+        },
+        None => rest,
+    };
+    #[cfg(windows)]
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => &path[1..],
+        _ => path,
+    };
+    let full = decode_uri_path(path);
+    if !path.contains(['?', '#']) || full.exists() {
+        return Some(full);
+    }
+    let without_suffix = path.split(['?', '#']).next().unwrap_or_default();
+    Some(decode_uri_path(without_suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_to_file_uri_encodes_reserved_and_non_ascii() {
+        assert_eq!(
+            path_to_file_uri(Path::new("/music/a.mp3")),
+            "file:///music/a.mp3"
+        );
+        assert_eq!(
+            path_to_file_uri(Path::new("/music/My Song #1?.mp3")),
+            "file:///music/My%20Song%20%231%3F.mp3"
+        );
+        assert_eq!(
+            path_to_file_uri(Path::new("/music/100% Café.flac")),
+            "file:///music/100%25%20Caf%C3%A9.flac"
+        );
+        // Sub-delimiters, `:` and `@` need no encoding in a path
+        assert_eq!(
+            path_to_file_uri(Path::new("/music/AC+DC (Live) @ 1:00&co.mp3")),
+            "file:///music/AC+DC%20(Live)%20@%201:00&co.mp3"
+        );
+    }
+
+    #[test]
+    fn test_file_uri_round_trips_awkward_filenames() {
+        for name in [
+            "plain.mp3",
+            "with space.mp3",
+            "hash#tag.mp3",
+            "question?.mp3",
+            "percent%20literal.mp3",
+            "100%.mp3",
+            "Café del Mar.flac",
+            "日本語の曲.ogg",
+            "emoji 🎵.opus",
+            "brackets [1] {2}.wav",
+            "quote'\"<&>.mp3",
+            "tab\tand\\backslash.mp3",
+        ] {
+            let path = PathBuf::from("/music/dir with space").join(name);
+            let uri = path_to_file_uri(&path);
+            assert!(
+                uri.bytes().all(|b| b.is_ascii_graphic()),
+                "URI not fully encoded: {uri}"
+            );
+            assert_eq!(file_uri_to_path(&uri), Some(path), "URI: {uri}");
+        }
+    }
+
+    #[test]
+    fn test_file_uri_to_path_accepts_authority_forms() {
+        let expected = Some(PathBuf::from("/music/a b.mp3"));
+        assert_eq!(file_uri_to_path("file:///music/a%20b.mp3"), expected);
+        assert_eq!(
+            file_uri_to_path("file://localhost/music/a%20b.mp3"),
+            expected
+        );
+        assert_eq!(file_uri_to_path("FILE:/music/a%20b.mp3"), expected);
+        assert_eq!(file_uri_to_path("file://nas/music/a.mp3"), None);
+        assert_eq!(file_uri_to_path("http://example.com/a.mp3"), None);
+        assert_eq!(file_uri_to_path("/music/a.mp3"), None);
+    }
+
+    #[test]
+    fn test_file_uri_to_path_keeps_legacy_hash_in_existing_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join("a#1.mp3");
+        std::fs::write(&legacy, b"").unwrap();
+        // Older exports wrote the path as-is
+        let uri = format!("file://{}", legacy.display());
+        assert_eq!(file_uri_to_path(&uri), Some(legacy));
+        // A fragment on a path that does not exist with it is dropped
+        let uri = format!("file://{}/b.mp3#t=10", dir.path().display());
+        assert_eq!(file_uri_to_path(&uri), Some(dir.path().join("b.mp3")));
+    }
+
+    #[test]
+    fn test_decode_uri_path_keeps_invalid_escapes() {
+        // Older exports wrote paths without encoding them
+        assert_eq!(
+            decode_uri_path("/music/100% Hits/%zz.mp3"),
+            PathBuf::from("/music/100% Hits/%zz.mp3")
+        );
+        assert_eq!(decode_uri_path("a%2"), PathBuf::from("a%2"));
+        assert_eq!(decode_uri_path("a%2fb"), PathBuf::from("a/b"));
+    }
+
+    #[test]
+    fn test_encode_uri_reference_guards_colon_in_first_segment() {
+        assert_eq!(
+            encode_uri_reference(Path::new("AC 1:00.mp3")),
+            "./AC%201:00.mp3"
+        );
+        assert_eq!(
+            encode_uri_reference(Path::new("../live/1:00.mp3")),
+            "../live/1:00.mp3"
+        );
+        assert!(!has_uri_scheme(&encode_uri_reference(Path::new("a:b/c"))));
+    }
+
+    #[test]
+    fn test_has_uri_scheme() {
+        assert!(has_uri_scheme("file:///music/a.mp3"));
+        assert!(has_uri_scheme("http://example.com/a.mp3"));
+        assert!(has_uri_scheme("svn+ssh://host/a"));
+        assert!(!has_uri_scheme("AC%201:00.mp3"));
+        assert!(!has_uri_scheme("./AC%201:00.mp3"));
+        assert!(!has_uri_scheme("../live/1:00.mp3"));
+        assert!(!has_uri_scheme("1a:b.mp3"));
+        assert!(!has_uri_scheme("a.mp3"));
+    }
+
+    #[test]
+    fn test_encode_uri_path_relative() {
+        assert_eq!(
+            encode_uri_path(Path::new("../Other Dir/b#2.mp3")),
+            "../Other%20Dir/b%232.mp3"
+        );
+    }
+}
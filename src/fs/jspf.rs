@@ -0,0 +1,294 @@
+//! JSPF (JSON XSPF) playlist export and import.
+//!
+//! JSPF is the JSON rendering of XSPF used by web tools and ListenBrainz.
+//! Exports write the same per-track fields as `export_xspf_playlist`
+//! (location, title, creator, album, duration, trackNum, identifier,
+//! annotation, and image), taken from the metadata cached in
+//! `RightPanelFile`. As in the JSPF specification, `location` and
+//! `identifier` are arrays; imports also accept a single string for them.
+//!
+//! Imports resolve each track's first location back to a local path, exactly
+//! as XSPF imports do. Tracks without a location (e.g. ListenBrainz entries
+//! that only carry MusicBrainz identifiers) are skipped.
+//!
+//! Public API:
+//!     export_jspf_playlist — write a playlist to a JSPF file
+//!     import_jspf_playlist — read tracks back from a JSPF file
+
+use crate::fs::playlist_export::ExportOptions;
+use crate::fs::playlist_import::{
+    ImportedPlaylist, ImportedTrack, read_playlist_text,
+};
+use crate::gui::RightPanelFile;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// The top-level JSPF object, which wraps the playlist.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JspfDocument {
+    playlist: JspfPlaylist,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JspfPlaylist {
+    #[serde(default)]
+    track: Vec<JspfTrack>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JspfTrack {
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "one_or_many"
+    )]
+    location: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    album: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<u64>,
+    #[serde(
+        default,
+        rename = "trackNum",
+        skip_serializing_if = "Option::is_none"
+    )]
+    track_num: Option<u32>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "one_or_many"
+    )]
+    identifier: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    annotation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+}
+
+/// Deserializes a JSPF array field that some writers emit as a single
+/// string (or `null`).
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(OneOrMany::One(value)) => vec![value],
+        Some(OneOrMany::Many(values)) => values,
+    })
+}
+
+/// Exports a playlist of the given files to a JSPF file at the specified
+/// output path, with the same fields `export_xspf_playlist` writes for each
+/// track. Locations are written as URIs according to `options` (see
+/// `EntryLocation::uri`). `on_progress` is called with the number of tracks
+/// written so far.
+pub(crate) fn export_jspf_playlist(
+    files: &[RightPanelFile],
+    output_path: &Path,
+    options: &ExportOptions,
+    on_progress: &mut dyn FnMut(usize),
+) -> std::io::Result<()> {
+    let mut document = JspfDocument::default();
+    for (i, file) in files.iter().enumerate() {
+        document.playlist.track.push(JspfTrack {
+            location: vec![
+                options.entry_location(&file.path, output_path).uri(),
+            ],
+            title: file.title.clone(),
+            creator: file.creator.clone(),
+            album: file.album.clone(),
+            duration: file.duration_ms,
+            track_num: file.track_num,
+            identifier: file.identifier.iter().cloned().collect(),
+            annotation: file.annotation.clone(),
+            image: file.image_uri.clone(),
+        });
+        on_progress(i + 1);
+    }
+
+    let json = serde_json::to_string_pretty(&document)?;
+    let mut file = File::create(output_path)?;
+    file.write_all(json.as_bytes())?;
+    file.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Imports the tracks of the JSPF playlist at `input_path`, resolving each
+/// track's first location to a local path (relative locations against the
+/// playlist's directory). The track's title, creator, album, and duration are
+/// kept as hints. Locations that are not local files, or whose files no
+/// longer exist, are returned in `missing`.
+pub(crate) fn import_jspf_playlist(
+    input_path: &Path,
+) -> std::io::Result<ImportedPlaylist> {
+    let text = read_playlist_text(input_path)?;
+    let document: JspfDocument = serde_json::from_str(&text).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid JSPF playlist: {e}"),
+        )
+    })?;
+
+    let base_dir = input_path.parent().unwrap_or(Path::new(""));
+    let mut imported = ImportedPlaylist::default();
+    for track in document.playlist.track {
+        // Players render only the first location of a track
+        let Some(location) = track.location.first() else {
+            continue;
+        };
+        imported.push_location(
+            base_dir,
+            location,
+            ImportedTrack {
+                creator: track.creator,
+                album: track.album,
+                title: track.title,
+                duration_ms: track.duration,
+                ..Default::default()
+            },
+        );
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_jspf_export_writes_xspf_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = vec![
+            RightPanelFile {
+                path: PathBuf::from("/music/a b.mp3"),
+                creator: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                title: Some("Title".to_string()),
+                genre: Some("Rock".to_string()),
+                duration_ms: Some(185_000),
+                track_num: Some(3),
+                identifier: Some("urn:isrc:XX0000000001".to_string()),
+                annotation: Some("Note".to_string()),
+                image_uri: Some("file:///music/cover.jpg".to_string()),
+                ..Default::default()
+            },
+            RightPanelFile {
+                path: PathBuf::from("/music/b.mp3"),
+                ..Default::default()
+            },
+        ];
+
+        let out_file = dir.path().join("playlist.jspf");
+        export_jspf_playlist(
+            &files,
+            &out_file,
+            &ExportOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out_file).unwrap())
+                .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "playlist": {
+                    "track": [
+                        {
+                            "location": ["file:///music/a%20b.mp3"],
+                            "title": "Title",
+                            "creator": "Artist",
+                            "album": "Album",
+                            "duration": 185000,
+                            "trackNum": 3,
+                            "identifier": ["urn:isrc:XX0000000001"],
+                            "annotation": "Note",
+                            "image": "file:///music/cover.jpg"
+                        },
+                        { "location": ["file:///music/b.mp3"] }
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_jspf_round_trips_export() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("lists")).unwrap();
+        let song = dir.path().join("rock band").join("a#1.mp3");
+        std::fs::create_dir_all(song.parent().unwrap()).unwrap();
+        std::fs::write(&song, b"").unwrap();
+        let files = vec![RightPanelFile {
+            path: song.clone(),
+            title: Some("Title".to_string()),
+            duration_ms: Some(1000),
+            ..Default::default()
+        }];
+
+        let out_file = dir.path().join("lists").join("playlist.jspf");
+        export_jspf_playlist(
+            &files,
+            &out_file,
+            &ExportOptions { relative_paths: true, ..Default::default() },
+            &mut |_| {},
+        )
+        .unwrap();
+        let json = std::fs::read_to_string(&out_file).unwrap();
+        assert!(json.contains("\"../rock%20band/a%231.mp3\""));
+
+        let imported = import_jspf_playlist(&out_file).unwrap();
+        assert_eq!(imported.found.len(), 1);
+        assert!(imported.found[0].path.ends_with("rock band/a#1.mp3"));
+        assert_eq!(imported.found[0].title.as_deref(), Some("Title"));
+        assert_eq!(imported.found[0].duration_ms, Some(1000));
+        assert!(imported.missing.is_empty());
+    }
+
+    #[test]
+    fn test_jspf_import_accepts_single_strings_and_skips_unlocated() {
+        let dir = tempfile::tempdir().unwrap();
+        let song = dir.path().join("a.mp3");
+        std::fs::write(&song, b"").unwrap();
+        let playlist = dir.path().join("listenbrainz.jspf");
+        std::fs::write(
+            &playlist,
+            r#"{"playlist": {"title": "Weekly", "track": [
+                {"location": "a.mp3", "identifier": "urn:x", "creator": "C"},
+                {"identifier": ["https://musicbrainz.org/recording/1"]},
+                {"location": ["http://example.com/stream"]},
+                {"location": null, "title": "No location"}
+            ]}}"#,
+        )
+        .unwrap();
+
+        let imported = import_jspf_playlist(&playlist).unwrap();
+        assert_eq!(imported.found.len(), 1);
+        assert_eq!(imported.found[0].path, song);
+        assert_eq!(imported.found[0].creator.as_deref(), Some("C"));
+        assert_eq!(
+            imported.missing,
+            vec![PathBuf::from("http://example.com/stream")]
+        );
+
+        std::fs::write(&playlist, "{\"tracks\": []}").unwrap();
+        let err = import_jspf_playlist(&playlist).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,294 @@
+//! Printable track listing export as HTML or Markdown.
+//!
+//! Renders the playlist in its displayed order as a running order for
+//! events: one row per track with its running index, cumulative start time,
+//! title, creator, album, and duration, followed by the same item count and
+//! total time the right panel shows under the playlist. The HTML variant is
+//! a standalone page with inline print styles; the Markdown variant is a
+//! heading and a pipe table.
+//!
+//! Tracks without a known duration add nothing to the start times of the
+//! tracks after them, exactly as they add nothing to the right panel's total.
+//!
+//! Public API:
+//!     ListingFormat         — HTML or Markdown
+//!     export_listing        — write the listing in the given format
+//!     export_listing_stream — run a listing export, streaming its progress
+
+use crate::fs::playlist_export::{ExportProgress, export_stream};
+use crate::gui::RightPanelFile;
+use crate::utils::{format_duration, format_totals};
+use iced::futures::Stream;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// File formats of the track listing export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ListingFormat {
+    Html,
+    Markdown,
+}
+
+impl ListingFormat {
+    /// Human-readable format name for buttons and status messages.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ListingFormat::Html => "HTML",
+            ListingFormat::Markdown => "Markdown",
+        }
+    }
+
+    /// The file extension used for the format.
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            ListingFormat::Html => "html",
+            ListingFormat::Markdown => "md",
+        }
+    }
+}
+
+/// One rendered row of the listing.
+struct ListingRow {
+    index: usize,
+    start: String,
+    title: String,
+    creator: String,
+    album: String,
+    duration: String,
+}
+
+/// Builds the listing rows, accumulating each track's start time from the
+/// durations of the tracks before it, and returns them with the totals line.
+fn listing_rows(files: &[RightPanelFile]) -> (Vec<ListingRow>, String) {
+    let mut start_ms = 0;
+    let rows = files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| {
+            let title = file.title.clone().unwrap_or_else(|| {
+                file.path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            let row = ListingRow {
+                index: i + 1,
+                start: format_duration(Some(start_ms)),
+                title,
+                creator: file.creator.clone().unwrap_or_default(),
+                album: file.album.clone().unwrap_or_default(),
+                duration: format_duration(file.duration_ms),
+            };
+            start_ms += file.duration_ms.unwrap_or(0);
+            row
+        })
+        .collect();
+    (rows, format_totals(files.len(), start_ms))
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escapes the characters that would break a Markdown table cell.
+fn markdown_cell(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Renders the listing as a standalone HTML page titled `title`.
+fn render_html(
+    title: &str,
+    rows: &[ListingRow],
+    totals: &str,
+    on_progress: &mut dyn FnMut(usize),
+) -> String {
+    let title = html_escape(title);
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ border-bottom: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }}
+td.num {{ text-align: right; font-variant-numeric: tabular-nums; }}
+tfoot td {{ font-weight: bold; border-bottom: none; }}
+@media print {{ body {{ margin: 0; }} tr {{ page-break-inside: avoid; }} }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<table>
+<thead>
+<tr><th>#</th><th>Start</th><th>Title</th><th>Creator</th><th>Album</th><th>Duration</th></tr>
+</thead>
+<tbody>
+"#
+    );
+    for row in rows {
+        html.push_str(&format!(
+            "<tr><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td></tr>\n",
+            row.index,
+            row.start,
+            html_escape(&row.title),
+            html_escape(&row.creator),
+            html_escape(&row.album),
+            row.duration,
+        ));
+        on_progress(row.index);
+    }
+    html.push_str(&format!(
+        "</tbody>\n<tfoot>\n<tr><td colspan=\"6\">{}</td></tr>\n</tfoot>\n</table>\n</body>\n</html>\n",
+        html_escape(totals)
+    ));
+    html
+}
+
+/// Renders the listing as a Markdown document headed `title`.
+fn render_markdown(
+    title: &str,
+    rows: &[ListingRow],
+    totals: &str,
+    on_progress: &mut dyn FnMut(usize),
+) -> String {
+    let mut md = format!(
+        "# {}\n\n| # | Start | Title | Creator | Album | Duration |\n\
+         |--:|--:|---|---|---|--:|\n",
+        title.replace(['\r', '\n'], " ")
+    );
+    for row in rows {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            row.index,
+            row.start,
+            markdown_cell(&row.title),
+            markdown_cell(&row.creator),
+            markdown_cell(&row.album),
+            row.duration,
+        ));
+        on_progress(row.index);
+    }
+    md.push_str(&format!("\n**Total:** {totals}\n"));
+    md
+}
+
+/// Writes a track listing of `files` to `output_path`, titled after the
+/// output file's name. `on_progress` is called with the number of tracks
+/// written so far.
+pub(crate) fn export_listing(
+    files: &[RightPanelFile],
+    format: ListingFormat,
+    output_path: &Path,
+    on_progress: &mut dyn FnMut(usize),
+) -> std::io::Result<()> {
+    let title = output_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let (rows, totals) = listing_rows(files);
+    let text = match format {
+        ListingFormat::Html => render_html(&title, &rows, &totals, on_progress),
+        ListingFormat::Markdown => {
+            render_markdown(&title, &rows, &totals, on_progress)
+        },
+    };
+
+    let mut file = File::create(output_path)?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// Runs `export_listing` as a stream for `Task::run`, reporting progress and
+/// the result as `ExportProgress`.
+pub(crate) fn export_listing_stream(
+    files: Vec<RightPanelFile>,
+    format: ListingFormat,
+    output_path: PathBuf,
+) -> impl Stream<Item = ExportProgress> {
+    export_stream(
+        format!("{} track listing", format.name()),
+        files.len(),
+        output_path,
+        move |output_path, on_progress| {
+            export_listing(&files, format, output_path, on_progress)
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_files() -> Vec<RightPanelFile> {
+        vec![
+            RightPanelFile {
+                path: PathBuf::from("/music/opener.mp3"),
+                creator: Some("Band & Co".to_string()),
+                album: Some("Live | 1999".to_string()),
+                title: Some("Opener <intro>".to_string()),
+                duration_ms: Some(185_000),
+                ..Default::default()
+            },
+            RightPanelFile {
+                path: PathBuf::from("/music/untagged.mp3"),
+                ..Default::default()
+            },
+            RightPanelFile {
+                path: PathBuf::from("/music/closer.mp3"),
+                title: Some("Closer".to_string()),
+                duration_ms: Some(3_600_000),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn export_to_string(format: ListingFormat) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("Friday Set.out");
+        export_listing(&sample_files(), format, &out, &mut |_| {}).unwrap();
+        std::fs::read_to_string(out).unwrap()
+    }
+
+    #[test]
+    fn test_markdown_listing_has_start_times_and_total() {
+        assert_eq!(
+            export_to_string(ListingFormat::Markdown),
+            "# Friday Set\n\n\
+             | # | Start | Title | Creator | Album | Duration |\n\
+             |--:|--:|---|---|---|--:|\n\
+             | 1 | 0:00 | Opener <intro> | Band & Co | Live \\| 1999 | 3:05 |\n\
+             | 2 | 3:05 | untagged |  |  |  |\n\
+             | 3 | 3:05 | Closer |  |  | 1:00:00 |\n\
+             \n**Total:** 3 Items, Time: 1:03:05\n"
+        );
+    }
+
+    #[test]
+    fn test_html_listing_escapes_and_includes_total() {
+        let html = export_to_string(ListingFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Friday Set</title>"));
+        assert!(html.contains(
+            "<tr><td class=\"num\">1</td><td class=\"num\">0:00</td>\
+             <td>Opener &lt;intro&gt;</td><td>Band &amp; Co</td>\
+             <td>Live | 1999</td><td class=\"num\">3:05</td></tr>"
+        ));
+        assert!(
+            html.contains(
+                "<td class=\"num\">3</td><td class=\"num\">3:05</td>"
+            )
+        );
+        assert!(html.contains(
+            "<tr><td colspan=\"6\">3 Items, Time: 1:03:05</td></tr>"
+        ));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+}
@@ -0,0 +1,324 @@
+//! Extended M3U (M3U8) playlist export and import.
+//!
+//! Writes a UTF-8 `#EXTM3U` playlist understood by most hardware and mobile
+//! players. Each track gets an `#EXTINF:<seconds>,<creator> - <title>` line
+//! built from the metadata already held in `RightPanelFile`, followed by the
+//! track's path.
+//!
+//! Imports accept both plain and extended M3U files with absolute or
+//! relative entries, keeping the `#EXTINF` duration and display text as
+//! metadata hints for the entry that follows.
+//!
+//! Public API:
+//!     export_m3u_playlist — write a playlist to an M3U8 file
+//!     import_m3u_playlist — read tracks back from an M3U/M3U8 file
+
+use crate::fs::playlist_export::ExportOptions;
+use crate::fs::playlist_import::{
+    ImportedPlaylist, ImportedTrack, read_playlist_text,
+};
+use crate::gui::RightPanelFile;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Builds the display text of an `#EXTINF` line (also used for PLS `TitleN`
+/// keys): `creator - title` when both are known, otherwise whichever is
+/// present, falling back to the file stem. Line breaks are replaced so that a
+/// tag value cannot split the entry.
+pub(crate) fn track_display_title(file: &RightPanelFile) -> String {
+    let creator = file.creator.as_deref().filter(|s| !s.is_empty());
+    let title = file.title.as_deref().filter(|s| !s.is_empty());
+    let display = match (creator, title) {
+        (Some(creator), Some(title)) => format!("{creator} - {title}"),
+        (None, Some(title)) => title.to_string(),
+        (Some(creator), None) => format!(
+            "{creator} - {}",
+            file.path.file_stem().unwrap_or_default().to_string_lossy()
+        ),
+        (None, None) => {
+            file.path.file_stem().unwrap_or_default().to_string_lossy().into()
+        },
+    };
+    display.replace(['\r', '\n'], " ")
+}
+
+/// Exports a playlist of the given files to an extended M3U file at the
+/// specified output path. Durations are written in whole seconds, or `-1`
+/// when unknown, as the format requires. Locations are plain paths, as most
+/// players expect, written absolute or relative according to `options`.
+/// `on_progress` is called with the number of tracks written so far.
+pub(crate) fn export_m3u_playlist(
+    files: &[RightPanelFile],
+    output_path: &Path,
+    options: &ExportOptions,
+    on_progress: &mut dyn FnMut(usize),
+) -> std::io::Result<()> {
+    let mut m3u = String::from("#EXTM3U\n");
+    for (i, file) in files.iter().enumerate() {
+        let seconds =
+            file.duration_ms.map(|ms| (ms / 1000) as i64).unwrap_or(-1);
+        m3u.push_str(&format!(
+            "#EXTINF:{seconds},{}\n",
+            track_display_title(file)
+        ));
+        let location = options.entry_location(&file.path, output_path);
+        m3u.push_str(&format!("{}\n", location.plain_path()));
+        on_progress(i + 1);
+    }
+
+    let mut file = File::create(output_path)?;
+    file.write_all(m3u.as_bytes())?;
+    Ok(())
+}
+
+/// Splits display text written by `track_display_title` back into creator
+/// and title at the first ` - `; text without one is taken as the title.
+pub(crate) fn split_display_title(
+    display: &str,
+) -> (Option<String>, Option<String>) {
+    let display = display.trim();
+    match display.split_once(" - ") {
+        Some((creator, title)) => {
+            (Some(creator.trim().to_string()), Some(title.trim().to_string()))
+        },
+        None if !display.is_empty() => (None, Some(display.to_string())),
+        None => (None, None),
+    }
+}
+
+/// Parses the part of an `#EXTINF:` line after the colon, e.g.
+/// `123,Artist - Title` or `-1 tvg-id="x",Title`. The duration is given in
+/// seconds (possibly fractional, negative when unknown); the display text is
+/// split into creator and title at the first ` - `.
+fn parse_extinf(info: &str) -> ImportedTrack {
+    let (duration, display) = info.split_once(',').unwrap_or((info, ""));
+    let duration_ms = duration
+        .split_whitespace()
+        .next()
+        .and_then(|secs| secs.parse::<f64>().ok())
+        .filter(|secs| *secs >= 0.0)
+        .map(|secs| (secs * 1000.0).round() as u64);
+    let (creator, title) = split_display_title(display);
+    ImportedTrack { creator, title, duration_ms, ..Default::default() }
+}
+
+/// Imports the tracks of the M3U or M3U8 playlist at `input_path`. Relative
+/// entries are resolved against the playlist's directory. Hints from an
+/// `#EXTINF` line apply to the next entry only; other `#` lines are ignored.
+pub(crate) fn import_m3u_playlist(
+    input_path: &Path,
+) -> std::io::Result<ImportedPlaylist> {
+    let text = read_playlist_text(input_path)?;
+    let base_dir = input_path.parent().unwrap_or(Path::new(""));
+
+    let mut imported = ImportedPlaylist::default();
+    let mut pending_hint = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            pending_hint = Some(parse_extinf(info));
+        } else if !line.starts_with('#') {
+            imported.push_entry(
+                base_dir,
+                line,
+                pending_hint.take().unwrap_or_default(),
+            );
+        }
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_m3u_playlist, import_m3u_playlist};
+    use crate::fs::playlist_export::ExportOptions;
+    use crate::fs::test_fixtures::right_panel_file;
+    use std::path::PathBuf;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_m3u_export_writes_extinf_lines_in_order() {
+        let files = vec![
+            right_panel_file(
+                "/music/b.mp3",
+                Some("Artist2"),
+                Some("Title2"),
+                Some(61_999),
+            ),
+            right_panel_file(
+                "/music/a.mp3",
+                Some("Artist1"),
+                Some("Title1"),
+                Some(1_000),
+            ),
+        ];
+        let out_file = NamedTempFile::new().unwrap();
+        export_m3u_playlist(
+            &files,
+            out_file.path(),
+            &ExportOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+
+        let m3u = std::fs::read_to_string(out_file.path()).unwrap();
+        assert_eq!(
+            m3u,
+            "#EXTM3U\n\
+             #EXTINF:61,Artist2 - Title2\n\
+             /music/b.mp3\n\
+             #EXTINF:1,Artist1 - Title1\n\
+             /music/a.mp3\n"
+        );
+    }
+
+    #[test]
+    fn test_m3u_export_falls_back_without_metadata() {
+        let files = vec![
+            right_panel_file(
+                "/music/only_title.mp3",
+                None,
+                Some("Title"),
+                None,
+            ),
+            right_panel_file(
+                "/music/only_creator.mp3",
+                Some("Artist"),
+                None,
+                None,
+            ),
+            right_panel_file("/music/bare.mp3", None, None, None),
+            right_panel_file(
+                "/music/multi.mp3",
+                Some("Art\nist"),
+                Some("Ti\r\ntle"),
+                None,
+            ),
+        ];
+        let out_file = NamedTempFile::new().unwrap();
+        export_m3u_playlist(
+            &files,
+            out_file.path(),
+            &ExportOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+
+        let m3u = std::fs::read_to_string(out_file.path()).unwrap();
+        let extinf: Vec<&str> =
+            m3u.lines().filter(|l| l.starts_with("#EXTINF")).collect();
+        assert_eq!(
+            extinf,
+            vec![
+                "#EXTINF:-1,Title",
+                "#EXTINF:-1,Artist - only_creator",
+                "#EXTINF:-1,bare",
+                "#EXTINF:-1,Art ist - Ti  tle",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_m3u_import_resolves_relative_and_absolute_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let relative = dir.path().join("sub").join("rel.mp3");
+        let absolute = dir.path().join("abs.flac");
+        std::fs::write(&relative, b"").unwrap();
+        std::fs::write(&absolute, b"").unwrap();
+        let m3u = format!(
+            "#EXTM3U\n\
+             #EXTINF:61,Artist - Title\n\
+             sub/rel.mp3\n\
+             \n\
+             # a comment\n\
+             {}\n\
+             #EXTINF:-1,Only Title\n\
+             missing.mp3\n\
+             http://example.com/stream\n",
+            absolute.display()
+        );
+        let playlist_path = dir.path().join("list.m3u8");
+        std::fs::write(&playlist_path, m3u).unwrap();
+
+        let imported = import_m3u_playlist(&playlist_path).unwrap();
+
+        assert_eq!(imported.found.len(), 2);
+        assert_eq!(imported.found[0].path, relative);
+        assert_eq!(imported.found[0].creator.as_deref(), Some("Artist"));
+        assert_eq!(imported.found[0].title.as_deref(), Some("Title"));
+        assert_eq!(imported.found[0].duration_ms, Some(61_000));
+        // The hint of the first entry must not leak onto the second
+        assert_eq!(imported.found[1].path, absolute);
+        assert_eq!(imported.found[1].title, None);
+        assert_eq!(
+            imported.missing,
+            vec![
+                dir.path().join("missing.mp3"),
+                PathBuf::from("http://example.com/stream")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_m3u_export_then_import_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let song = dir.path().join("song.mp3");
+        std::fs::write(&song, b"").unwrap();
+        let files = vec![right_panel_file(
+            song.to_str().unwrap(),
+            Some("Artist"),
+            Some("Title"),
+            Some(125_000),
+        )];
+        let playlist_path = dir.path().join("out.m3u8");
+        export_m3u_playlist(
+            &files,
+            &playlist_path,
+            &ExportOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+
+        let imported = import_m3u_playlist(&playlist_path).unwrap();
+
+        assert_eq!(imported.found.len(), 1);
+        assert_eq!(imported.found[0].path, song);
+        assert_eq!(imported.found[0].creator.as_deref(), Some("Artist"));
+        assert_eq!(imported.found[0].title.as_deref(), Some("Title"));
+        assert_eq!(imported.found[0].duration_ms, Some(125_000));
+        assert!(imported.missing.is_empty());
+    }
+
+    #[test]
+    fn test_m3u_relative_export_then_import_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("music").join("rock")).unwrap();
+        std::fs::create_dir_all(dir.path().join("lists")).unwrap();
+        let song = dir.path().join("music").join("rock").join("a.mp3");
+        std::fs::write(&song, b"").unwrap();
+        let files = vec![right_panel_file(
+            song.to_str().unwrap(),
+            None,
+            Some("Title"),
+            None,
+        )];
+        let playlist_path = dir.path().join("lists").join("out.m3u8");
+        let options =
+            ExportOptions { relative_paths: true, ..Default::default() };
+        export_m3u_playlist(&files, &playlist_path, &options, &mut |_| {})
+            .unwrap();
+
+        let m3u = std::fs::read_to_string(&playlist_path).unwrap();
+        assert_eq!(m3u, "#EXTM3U\n#EXTINF:-1,Title\n../music/rock/a.mp3\n");
+
+        let imported = import_m3u_playlist(&playlist_path).unwrap();
+        assert_eq!(imported.found.len(), 1);
+        assert!(imported.found[0].path.ends_with("music/rock/a.mp3"));
+        assert!(imported.found[0].path.is_file());
+    }
+}
@@ -1,20 +1,23 @@
 //! Media metadata extraction and tag-tree construction.
 //!
 //! Uses the `lofty` crate to read audio-file metadata (artist, album,
-//! title, genre, duration, track number, album art, etc.) and builds
-//! hierarchical tag trees for tag-based browsing:
+//! title, genre, duration, track and disc number, year, album art, etc.) and
+//! audio properties (bitrate, sample rate, channels, bit depth, codec), and
+//! builds hierarchical tag trees for tag-based browsing:
 //! - `build_genre_tag_tree`: genre → artist → album → track
 //! - `build_creator_tag_tree`: artist → album → track
 //!
 //! Public API:
 //!     MediaMetadata        — parsed metadata fields
 //!     extract_media_metadata  — read metadata from a file path
+//!     read_media_metadata     — the same, optionally without saving cover art
 //!     build_genre_tag_tree     — genre-based hierarchy
 //!     build_creator_tag_tree   — creator-based hierarchy
 
+use crate::fs::file_uri::path_to_file_uri;
 use crate::gui::TagTreeNode;
 use lofty::{
-    file::{AudioFile, TaggedFileExt},
+    file::{AudioFile, FileType, TaggedFileExt},
     prelude::ItemKey,
     read_from_path,
     tag::Accessor,
@@ -40,16 +43,59 @@ pub(crate) struct MediaMetadata {
     pub image_uri: Option<String>,
     pub identifier: Option<String>,
     pub annotation: Option<String>,
+    pub disc_num: Option<u32>,
+    pub year: Option<u32>,
+    pub album_artist: Option<String>,
+    pub bitrate_kbps: Option<u32>,
+    pub sample_rate_hz: Option<u32>,
+    pub channels: Option<u8>,
+    pub bit_depth: Option<u8>,
+    pub codec: Option<String>,
+    pub file_size: Option<u64>,
+}
+
+/// Display name of the codec of a file of type `file_type`.
+fn codec_name(file_type: FileType) -> String {
+    match file_type {
+        FileType::Aac => "AAC",
+        FileType::Aiff => "AIFF",
+        FileType::Ape => "APE",
+        FileType::Flac => "FLAC",
+        FileType::Mpeg => "MP3",
+        FileType::Mp4 => "MP4",
+        FileType::Mpc => "Musepack",
+        FileType::Opus => "Opus",
+        FileType::Vorbis => "Vorbis",
+        FileType::Speex => "Speex",
+        FileType::Wav => "WAV",
+        FileType::WavPack => "WavPack",
+        FileType::Custom(name) => name,
+        _ => return format!("{file_type:?}"),
+    }
+    .to_string()
 }
 
 /// Extracts media metadata from the given file path using the `lofty` crate,
-///     returning information such as artist, album, title, genre, track number,
-///     duration, album art URI, identifier, and annotation if available.
+///     returning information such as artist, album, title, genre, track and
+///     disc number, year, duration, audio properties, album art URI,
+///     identifier, and annotation if available. The file size is reported
+///     even for files `lofty` cannot read. The first embedded picture is
+///     saved beside the track as `<track>.cover.jpg`.
 pub(crate) fn extract_media_metadata(path: &Path) -> MediaMetadata {
+    read_media_metadata(path, true)
+}
+
+/// Like `extract_media_metadata`, but with `save_cover_art` unset only
+///     points at a `<track>.cover.jpg` saved earlier instead of writing one.
+pub(crate) fn read_media_metadata(
+    path: &Path,
+    save_cover_art: bool,
+) -> MediaMetadata {
+    let file_size = std::fs::metadata(path).ok().map(|m| m.len());
     if let Ok(tagged_file) = read_from_path(path) {
         let tag = tagged_file.primary_tag().or_else(|| tagged_file.first_tag());
-        let duration_ms =
-            Some(tagged_file.properties().duration().as_millis() as u64);
+        let properties = tagged_file.properties();
+        let duration_ms = Some(properties.duration().as_millis() as u64);
 
         let (track_num, annotation, identifier, image_uri) =
             if let Some(tag) = tag {
@@ -68,11 +114,12 @@ pub(crate) fn extract_media_metadata(path: &Path) -> MediaMetadata {
                 // Album art (save first picture if present)
                 let image_uri = tag.pictures().first().and_then(|pic| {
                     let img_path = path.with_extension("cover.jpg");
-                    if std::fs::write(&img_path, pic.data()).is_ok() {
-                        Some(format!("file://{}", img_path.display()))
+                    let saved = if save_cover_art {
+                        std::fs::write(&img_path, pic.data()).is_ok()
                     } else {
-                        None
-                    }
+                        img_path.is_file()
+                    };
+                    if saved { Some(path_to_file_uri(&img_path)) } else { None }
                 });
 
                 (track_num, annotation, identifier, image_uri)
@@ -90,9 +137,22 @@ pub(crate) fn extract_media_metadata(path: &Path) -> MediaMetadata {
             image_uri,
             identifier,
             annotation,
+            disc_num: tag.and_then(|t| t.disk()),
+            year: tag.and_then(|t| t.year()),
+            album_artist: tag.and_then(|t| {
+                t.get_string(&ItemKey::AlbumArtist).map(|s| s.to_string())
+            }),
+            bitrate_kbps: properties
+                .audio_bitrate()
+                .or_else(|| properties.overall_bitrate()),
+            sample_rate_hz: properties.sample_rate(),
+            channels: properties.channels(),
+            bit_depth: properties.bit_depth(),
+            codec: Some(codec_name(tagged_file.file_type())),
+            file_size,
         }
     } else {
-        MediaMetadata::default()
+        MediaMetadata { file_size, ..MediaMetadata::default() }
     }
 }
 
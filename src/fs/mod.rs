@@ -1,15 +1,38 @@
 //! Filesystem module for the Playlist UI.
 //!
 //! Handles directory scanning, media metadata extraction, tag-tree
-//! construction (genre and creator hierarchies), and XSPF playlist
-//! export.
+//! construction (genre and creator hierarchies), playlist export and import
+//! in the XSPF, JSPF, extended M3U, and PLS formats, tabular (CSV, TSV,
+//! JSON) and printable (HTML, Markdown) exports, and copying playlist tracks
+//! to a device folder.
 //!
 //! Sub-modules:
+//!     device_sync         — copy playlist tracks to a device folder
 //!     file_tree           — recursive directory scanning
+//!     file_uri            — `file://` URI encoding and decoding
+//!     jspf                — JSPF (JSON XSPF) playlist export and import
+//!     listing_export      — printable HTML and Markdown track listings
 //!     media_metadata      — audio file metadata and tag trees
+//!     m3u                 — extended M3U (M3U8) playlist export and import
 //!     media_metadata_async — async variants (experimental, not wired)
-//!     xspf                — XSPF playlist export
+//!     playlist_export     — options shared by every playlist exporter
+//!     playlist_import     — format dispatch and shared import types
+//!     pls                 — PLS playlist export and import
+//!     table_export        — CSV, TSV, and JSON export with chosen columns
+//!     test_fixtures       — entries shared by the format tests (tests only)
+//!     xspf                — XSPF playlist export and import
 
+pub mod device_sync;
 pub mod file_tree;
+pub mod file_uri;
+pub mod jspf;
+pub mod listing_export;
+pub mod m3u;
 pub mod media_metadata;
+pub mod playlist_export;
+pub mod playlist_import;
+pub mod pls;
+pub mod table_export;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
 pub mod xspf;
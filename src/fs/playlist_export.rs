@@ -0,0 +1,615 @@
+//! Format-independent playlist export options.
+//!
+//! Holds the options shared by every playlist exporter and decides how each
+//! track's location is written: as an absolute path, relative to the
+//! directory of the playlist file being written, or rewritten for another
+//! machine by a named `PathRewriteRule`. Relative locations are computed
+//! lexically with `..` traversal, so a playlist and its tracks can be moved
+//! together to another machine or mount point.
+//!
+//! Exporters build every entry from the metadata already cached in
+//! `RightPanelFile`, so no track is re-read from disk. The UI runs exports
+//! through `export_playlist_stream`, which writes the playlist off the UI
+//! thread and reports progress and the final result as `ExportProgress`.
+//!
+//! Playlist-level metadata (title, creator, annotation, date) and the right
+//! panel's sort state travel in `PlaylistInfo`; only XSPF writes them.
+//!
+//! Public API:
+//!     ExportOptions          — options applied by every exporter
+//!     PlaylistInfo           — playlist-level metadata and view state
+//!     PlaylistView           — sort keys and shuffle state
+//!     PathRewriteRule        — named source → target path prefix rewrite
+//!     EntryLocation          — how one track location is written
+//!     relative_path          — compute `target` relative to a base directory
+//!     PlaylistFormat         — the playlist file formats we can export
+//!     ExportProgress         — progress and result of a background export
+//!     export_playlist        — write a playlist in the given format
+//!     export_playlist_stream — run an export, streaming its progress
+//!     export_stream          — stream the progress of any track export
+
+use crate::fs::file_uri::{encode_uri_reference, path_to_file_uri};
+use crate::gui::{RightPanelFile, SortColumn, SortKey, SortOrder};
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// Options applied by every playlist exporter.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct ExportOptions {
+    /// Write each location relative to the playlist file's directory. Tracks
+    /// on a different root (e.g. another drive) are still written absolute.
+    pub relative_paths: bool,
+    /// Rewrite rule applied to tracks under its source prefix. Rewritten
+    /// locations point at another machine, so they are never made relative.
+    pub rewrite_rule: Option<PathRewriteRule>,
+}
+
+/// Playlist-level metadata, written by formats that support it (XSPF) and
+/// read back on import.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct PlaylistInfo {
+    pub title: Option<String>,
+    pub creator: Option<String>,
+    pub annotation: Option<String>,
+    /// When the playlist was written, as an XML Schema `dateTime`.
+    pub date: Option<String>,
+    /// How the right panel was ordered when the playlist was written.
+    pub view: Option<PlaylistView>,
+}
+
+/// The right panel's ordering: a sort column and order followed by the
+/// further sort keys, or the stored (shuffled or imported) track order when
+/// `shuffled` is set.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PlaylistView {
+    pub sort_column: SortColumn,
+    pub sort_order: SortOrder,
+    pub then_by: Vec<SortKey>,
+    pub shuffled: bool,
+}
+
+/// A named rule that replaces a path prefix on export, e.g. `/mnt/nas/music`
+/// on a desktop → `/storage/music` on a media box. With `windows_separators`
+/// set, plain-path formats (M3U8, PLS) write `\` instead of `/`.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    bincode::Encode,
+    bincode::Decode,
+)]
+pub(crate) struct PathRewriteRule {
+    pub name: String,
+    pub source_prefix: String,
+    pub target_prefix: String,
+    pub windows_separators: bool,
+}
+
+impl PathRewriteRule {
+    /// Returns `path` with the source prefix replaced by the target prefix,
+    /// using `/` separators, or `None` when `path` is not under the source
+    /// prefix. Prefixes match whole components, so `/music` does not match
+    /// `/musicals/a.mp3`.
+    pub(crate) fn rewrite(&self, path: &Path) -> Option<String> {
+        if self.source_prefix.is_empty() {
+            return None;
+        }
+        let rest = path.strip_prefix(&self.source_prefix).ok()?;
+        let target = self.target_prefix.replace('\\', "/");
+        let target = target.trim_end_matches('/');
+        let rest: Vec<String> = rest
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if rest.is_empty() {
+            Some(target.to_string())
+        } else if target.is_empty() && !self.target_prefix.is_empty() {
+            // A target of `/` keeps the root
+            Some(format!("/{}", rest.join("/")))
+        } else if target.is_empty() {
+            Some(rest.join("/"))
+        } else {
+            Some(format!("{target}/{}", rest.join("/")))
+        }
+    }
+}
+
+/// Where a track is written in an exported playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum EntryLocation {
+    Absolute(PathBuf),
+    Relative(PathBuf),
+    /// A location rewritten by a `PathRewriteRule`, with `/` separators.
+    Rewritten {
+        path: String,
+        windows_separators: bool,
+    },
+}
+
+impl EntryLocation {
+    /// The location as a plain path, as written by M3U8 and PLS exports.
+    pub(crate) fn plain_path(&self) -> String {
+        match self {
+            EntryLocation::Absolute(path) | EntryLocation::Relative(path) => {
+                path.display().to_string()
+            },
+            EntryLocation::Rewritten { path, windows_separators: true } => {
+                path.replace('/', "\\")
+            },
+            EntryLocation::Rewritten { path, .. } => path.clone(),
+        }
+    }
+
+    /// The location as a URI, as written by XSPF exports: a `file://` URI
+    /// for absolute and rewritten paths, a percent-encoded URI reference for
+    /// relative ones. URIs always use `/`, whatever the rule's separators.
+    pub(crate) fn uri(&self) -> String {
+        match self {
+            EntryLocation::Absolute(path) => path_to_file_uri(path),
+            EntryLocation::Relative(path) => encode_uri_reference(path),
+            EntryLocation::Rewritten { path, .. } => {
+                path_to_file_uri(Path::new(path))
+            },
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Returns the location to write for the track at `path` in a playlist
+    /// saved to `output_path`. A matching rewrite rule takes precedence over
+    /// relative paths.
+    pub(crate) fn entry_location(
+        &self,
+        path: &Path,
+        output_path: &Path,
+    ) -> EntryLocation {
+        if let Some(rule) = &self.rewrite_rule
+            && let Some(rewritten) = rule.rewrite(path)
+        {
+            return EntryLocation::Rewritten {
+                path: rewritten,
+                windows_separators: rule.windows_separators,
+            };
+        }
+        if self.relative_paths {
+            let base_dir = output_path.parent().unwrap_or(Path::new(""));
+            if let Some(relative) = relative_path(base_dir, path) {
+                return EntryLocation::Relative(relative);
+            }
+        }
+        EntryLocation::Absolute(path.to_path_buf())
+    }
+}
+
+/// Makes `path` absolute against the current directory and removes `.` and
+/// `..` components without touching the filesystem.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let absolute = std::path::absolute(path).ok()?;
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                normalized.pop();
+            },
+            other => normalized.push(other),
+        }
+    }
+    Some(normalized)
+}
+
+/// Computes `target` relative to `base_dir`, climbing out of `base_dir` with
+/// `..` as needed, e.g. `/music/lists` and `/music/rock/a.mp3` give
+/// `../rock/a.mp3`. Returns `None` when the two paths do not share a root
+/// (e.g. different Windows drives), where only an absolute path works.
+pub(crate) fn relative_path(base_dir: &Path, target: &Path) -> Option<PathBuf> {
+    let base = normalize(base_dir)?;
+    let target = normalize(target)?;
+    let base: Vec<Component> = base.components().collect();
+    let target: Vec<Component> = target.components().collect();
+
+    // Prefix and root components must match for a relative path to exist
+    let root_len = |c: &[Component]| {
+        c.iter()
+            .take_while(|c| {
+                matches!(c, Component::Prefix(_) | Component::RootDir)
+            })
+            .count()
+    };
+    let (base_root, target_root) = (root_len(&base), root_len(&target));
+    if base_root != target_root || base[..base_root] != target[..target_root] {
+        return None;
+    }
+
+    let common =
+        base.iter().zip(target.iter()).take_while(|(a, b)| a == b).count();
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &target[common..] {
+        relative.push(component);
+    }
+    Some(relative)
+}
+
+/// Playlist file formats the right panel can export.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    bincode::Encode,
+    bincode::Decode,
+)]
+pub(crate) enum PlaylistFormat {
+    Xspf,
+    Jspf,
+    M3u,
+    Pls,
+}
+
+impl PlaylistFormat {
+    /// Every format, in the order the UI offers them.
+    pub(crate) const ALL: [PlaylistFormat; 4] = [
+        PlaylistFormat::Xspf,
+        PlaylistFormat::Jspf,
+        PlaylistFormat::M3u,
+        PlaylistFormat::Pls,
+    ];
+
+    /// Human-readable format name for status messages.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            PlaylistFormat::Xspf => "XSPF",
+            PlaylistFormat::Jspf => "JSPF",
+            PlaylistFormat::M3u => "M3U8",
+            PlaylistFormat::Pls => "PLS",
+        }
+    }
+
+    /// The file extension used for the format.
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            PlaylistFormat::Xspf => "xspf",
+            PlaylistFormat::Jspf => "jspf",
+            PlaylistFormat::M3u => "m3u8",
+            PlaylistFormat::Pls => "pls",
+        }
+    }
+}
+
+/// Progress of a background playlist export, as reported to the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ExportProgress {
+    /// `done` of `total` tracks have been written.
+    Running { done: usize, total: usize },
+    /// The playlist was written to the given path.
+    Finished(PathBuf),
+    /// The export failed; holds a message for the user.
+    Failed(String),
+}
+
+/// Writes `files` to `output_path` in the given format. `info` is written
+/// only by formats with playlist-level metadata. `on_progress` is called with
+/// the number of tracks written so far.
+pub(crate) fn export_playlist(
+    format: PlaylistFormat,
+    files: &[RightPanelFile],
+    output_path: &Path,
+    options: &ExportOptions,
+    info: &PlaylistInfo,
+    on_progress: &mut dyn FnMut(usize),
+) -> std::io::Result<()> {
+    match format {
+        PlaylistFormat::Xspf => crate::fs::xspf::export_xspf_playlist(
+            files,
+            output_path,
+            options,
+            info,
+            on_progress,
+        ),
+        PlaylistFormat::Jspf => crate::fs::jspf::export_jspf_playlist(
+            files,
+            output_path,
+            options,
+            on_progress,
+        ),
+        PlaylistFormat::M3u => crate::fs::m3u::export_m3u_playlist(
+            files,
+            output_path,
+            options,
+            on_progress,
+        ),
+        PlaylistFormat::Pls => crate::fs::pls::export_pls_playlist(
+            files,
+            output_path,
+            options,
+            on_progress,
+        ),
+    }
+}
+
+/// Runs `export_playlist` as a stream for `Task::run`. Yields at most about
+/// a hundred `Running` updates, however long the playlist, followed by
+/// exactly one `Finished` or `Failed`.
+pub(crate) fn export_playlist_stream(
+    format: PlaylistFormat,
+    files: Vec<RightPanelFile>,
+    output_path: PathBuf,
+    options: ExportOptions,
+    info: PlaylistInfo,
+) -> impl Stream<Item = ExportProgress> {
+    export_stream(
+        format!("{} playlist", format.name()),
+        files.len(),
+        output_path,
+        move |output_path, on_progress| {
+            export_playlist(
+                format,
+                &files,
+                output_path,
+                &options,
+                &info,
+                on_progress,
+            )
+        },
+    )
+}
+
+/// Runs an export of `total` tracks to `output_path` as a stream of
+/// `ExportProgress`, throttling the progress updates `export` reports.
+/// `export` runs on its own thread, so each update reaches the stream while
+/// the export is still writing. `what` names the export in the failure
+/// message, e.g. "M3U8 playlist".
+pub(crate) fn export_stream(
+    what: String,
+    total: usize,
+    output_path: PathBuf,
+    export: impl FnOnce(&Path, &mut dyn FnMut(usize)) -> std::io::Result<()>
+    + Send
+    + 'static,
+) -> impl Stream<Item = ExportProgress> {
+    iced::stream::channel(16, move |mut sender| async move {
+        // Throttled to about a hundred updates, so unbounded is fine
+        let (progress, mut events) = mpsc::unbounded();
+        std::thread::spawn(move || {
+            let step = (total / 100).max(1);
+            let result = export(&output_path, &mut |done| {
+                if done % step == 0 || done == total {
+                    let _ = progress.unbounded_send(ExportProgress::Running {
+                        done,
+                        total,
+                    });
+                }
+            });
+            let outcome = match result {
+                Ok(()) => ExportProgress::Finished(output_path),
+                Err(e) => ExportProgress::Failed(format!(
+                    "Failed to export {what} {}: {e}",
+                    output_path.display()
+                )),
+            };
+            let _ = progress.unbounded_send(outcome);
+        });
+        while let Some(event) = events.next().await {
+            if sender.send(event).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_path_traverses_up_and_down() {
+        let cases = [
+            ("/music", "/music/a.mp3", "a.mp3"),
+            ("/music", "/music/rock/a.mp3", "rock/a.mp3"),
+            ("/music/lists", "/music/rock/a.mp3", "../rock/a.mp3"),
+            ("/media/usb/lists/2024", "/media/usb/a.mp3", "../../a.mp3"),
+            ("/home/me/lists", "/mnt/nas/a.mp3", "../../../mnt/nas/a.mp3"),
+            ("/music/./lists/../lists", "/music/rock/../a.mp3", "../a.mp3"),
+        ];
+        for (base, target, expected) in cases {
+            assert_eq!(
+                relative_path(Path::new(base), Path::new(target)),
+                Some(PathBuf::from(expected)),
+                "{target} relative to {base}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_entry_location_respects_option() {
+        let track = Path::new("/music/rock/a.mp3");
+        let output = Path::new("/music/lists/out.m3u8");
+
+        let absolute = ExportOptions::default().entry_location(track, output);
+        assert_eq!(absolute, EntryLocation::Absolute(track.to_path_buf()));
+
+        let options =
+            ExportOptions { relative_paths: true, ..Default::default() };
+        let relative = options.entry_location(track, output);
+        assert_eq!(
+            relative,
+            EntryLocation::Relative(PathBuf::from("../rock/a.mp3"))
+        );
+        assert_eq!(relative.plain_path(), "../rock/a.mp3");
+    }
+
+    fn nas_rule(windows_separators: bool) -> PathRewriteRule {
+        PathRewriteRule {
+            name: "Media box".to_string(),
+            source_prefix: "/mnt/nas/music".to_string(),
+            target_prefix: "/storage/music/".to_string(),
+            windows_separators,
+        }
+    }
+
+    #[test]
+    fn test_path_rewrite_rule_matches_whole_components() {
+        let rule = nas_rule(false);
+        assert_eq!(
+            rule.rewrite(Path::new("/mnt/nas/music/rock/a b.mp3")),
+            Some("/storage/music/rock/a b.mp3".to_string())
+        );
+        assert_eq!(rule.rewrite(Path::new("/mnt/nas/musicals/a.mp3")), None);
+        assert_eq!(rule.rewrite(Path::new("/home/me/a.mp3")), None);
+
+        let to_root = PathRewriteRule {
+            target_prefix: "/".to_string(),
+            ..nas_rule(false)
+        };
+        assert_eq!(
+            to_root.rewrite(Path::new("/mnt/nas/music/a.mp3")),
+            Some("/a.mp3".to_string())
+        );
+        let empty_source =
+            PathRewriteRule { source_prefix: String::new(), ..nas_rule(false) };
+        assert_eq!(
+            empty_source.rewrite(Path::new("/mnt/nas/music/a.mp3")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rewritten_location_formats() {
+        let rule = PathRewriteRule {
+            target_prefix: r"M:\Music".to_string(),
+            ..nas_rule(true)
+        };
+        let options =
+            ExportOptions { relative_paths: true, rewrite_rule: Some(rule) };
+        let output = Path::new("/mnt/nas/music/lists/out.m3u8");
+
+        let location =
+            options.entry_location(Path::new("/mnt/nas/music/a b.mp3"), output);
+        assert_eq!(location.plain_path(), r"M:\Music\a b.mp3");
+        assert_eq!(location.uri(), "file:///M:/Music/a%20b.mp3");
+
+        // Tracks outside the rule's prefix fall back to relative paths
+        let location =
+            options.entry_location(Path::new("/mnt/other/a.mp3"), output);
+        assert_eq!(location.plain_path(), "../../../other/a.mp3");
+    }
+
+    fn collect_export(
+        format: PlaylistFormat,
+        count: usize,
+        output_path: PathBuf,
+    ) -> Vec<ExportProgress> {
+        let files = (0..count)
+            .map(|i| RightPanelFile {
+                path: PathBuf::from(format!("/music/{i}.mp3")),
+                ..Default::default()
+            })
+            .collect();
+        let stream = export_playlist_stream(
+            format,
+            files,
+            output_path,
+            ExportOptions::default(),
+            PlaylistInfo::default(),
+        );
+        iced::futures::executor::block_on(stream.collect())
+    }
+
+    #[test]
+    fn test_export_stream_reports_progress_then_result() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.m3u8");
+        let events = collect_export(PlaylistFormat::M3u, 1000, output.clone());
+
+        let running: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                ExportProgress::Running { done, total } => {
+                    Some((*done, *total))
+                },
+                _ => None,
+            })
+            .collect();
+        assert!(!running.is_empty() && running.len() <= 101);
+        assert!(running.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(
+            events.last(),
+            Some(&ExportProgress::Finished(output.clone()))
+        );
+        let m3u = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(
+            m3u.lines().filter(|l| l.starts_with("/music/")).count(),
+            1000
+        );
+    }
+
+    /// Progress arrives while the export is still running: the export
+    /// below only finishes once the test has seen its first update.
+    #[test]
+    fn test_export_stream_reports_progress_while_running() {
+        use std::sync::mpsc::channel;
+        use std::time::Duration;
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.m3u8");
+        let (seen, wait_for_seen) = channel::<()>();
+        let mut stream = Box::pin(export_stream(
+            "test playlist".to_string(),
+            2,
+            output.clone(),
+            move |_, on_progress| {
+                on_progress(1);
+                wait_for_seen.recv_timeout(Duration::from_secs(5)).map_err(
+                    |_| std::io::Error::other("progress was not delivered"),
+                )?;
+                on_progress(2);
+                Ok(())
+            },
+        ));
+
+        let first = iced::futures::executor::block_on(stream.next());
+        assert_eq!(first, Some(ExportProgress::Running { done: 1, total: 2 }));
+        seen.send(()).unwrap();
+        let rest: Vec<_> = iced::futures::executor::block_on(stream.collect());
+        assert_eq!(
+            rest,
+            [
+                ExportProgress::Running { done: 2, total: 2 },
+                ExportProgress::Finished(output)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_stream_reports_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("missing dir").join("out.pls");
+        let events = collect_export(PlaylistFormat::Pls, 3, output);
+
+        match events.last() {
+            Some(ExportProgress::Failed(message)) => {
+                assert!(message.contains("PLS"), "{message}");
+            },
+            other => panic!("expected a failure, got {other:?}"),
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_relative_path_different_drive_is_none() {
+        assert_eq!(
+            relative_path(Path::new(r"C:\lists"), Path::new(r"D:\a.mp3")),
+            None
+        );
+    }
+}
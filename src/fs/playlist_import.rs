@@ -0,0 +1,187 @@
+//! Format-independent playlist import.
+//!
+//! Dispatches a playlist file to the XSPF, JSPF, M3U/M3U8, or PLS parser
+//! based on its extension and collects the result into an `ImportedPlaylist`.
+//! Each parser resolves its entries against the playlist file's directory
+//! (so relative entries work) and keeps any title, creator, album, or
+//! duration hints the playlist carries, to be used where a file's own tags
+//! are empty. XSPF imports also return the playlist-level metadata and the
+//! right panel's sort state written by `export_xspf_playlist`.
+//!
+//! Public API:
+//!     ImportedTrack     — a resolved entry plus the playlist's metadata hints
+//!     ImportedPlaylist  — found tracks and missing entries of an import
+//!     import_playlist   — read any supported playlist file
+
+use crate::fs::file_uri::{decode_uri_path, file_uri_to_path, has_uri_scheme};
+use crate::fs::playlist_export::PlaylistInfo;
+use std::path::{Path, PathBuf};
+
+/// A playlist entry that exists on disk, together with the metadata hints
+/// the playlist itself recorded for it (e.g. `#EXTINF` or `TitleN` lines).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct ImportedTrack {
+    pub path: PathBuf,
+    pub creator: Option<String>,
+    pub album: Option<String>,
+    pub title: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+/// Tracks read from a playlist file, in playlist order. Entries whose files
+/// do not exist on disk, or that are not local files at all, are kept
+/// separately in `missing` so they can be reported to the user. `info` holds
+/// the playlist-level metadata, for formats that carry any.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct ImportedPlaylist {
+    pub found: Vec<ImportedTrack>,
+    pub missing: Vec<PathBuf>,
+    pub info: PlaylistInfo,
+}
+
+impl ImportedPlaylist {
+    /// Resolves a raw playlist entry against `base_dir` and records it as
+    /// found or missing. The hints in `track` are kept for found entries;
+    /// its `path` is replaced by the resolved path.
+    pub(crate) fn push_entry(
+        &mut self,
+        base_dir: &Path,
+        entry: &str,
+        track: ImportedTrack,
+    ) {
+        match resolve_entry(base_dir, entry) {
+            Some(path) if path.is_file() => {
+                self.found.push(ImportedTrack { path, ..track })
+            },
+            Some(path) => self.missing.push(path),
+            None => self.missing.push(PathBuf::from(entry.trim())),
+        }
+    }
+
+    /// Like `push_entry`, for an XSPF or JSPF `location`: a URI or a
+    /// percent-encoded relative URI reference, which is decoded first.
+    pub(crate) fn push_location(
+        &mut self,
+        base_dir: &Path,
+        location: &str,
+        track: ImportedTrack,
+    ) {
+        let location = location.trim();
+        if has_uri_scheme(location) {
+            self.push_entry(base_dir, location, track);
+        } else {
+            let decoded = decode_uri_path(location);
+            self.push_entry(base_dir, &decoded.to_string_lossy(), track);
+        }
+    }
+}
+
+/// Resolves a playlist entry to a local path. `file:` URIs are
+/// percent-decoded and absolute paths are used as they are; relative paths
+/// are joined onto `base_dir`. Returns `None` for other URIs (e.g. `http://`
+/// streams).
+pub(crate) fn resolve_entry(base_dir: &Path, entry: &str) -> Option<PathBuf> {
+    let entry = entry.trim();
+    if let Some(path) = file_uri_to_path(entry) {
+        return Some(path);
+    }
+    if entry.contains("://") {
+        return None;
+    }
+    let path = Path::new(entry);
+    if path.is_absolute() {
+        Some(path.to_path_buf())
+    } else {
+        Some(base_dir.join(path))
+    }
+}
+
+/// Reads a text playlist, accepting UTF-8 (with or without a byte-order
+/// mark) and falling back to Latin-1 for legacy files that are not valid
+/// UTF-8.
+pub(crate) fn read_playlist_text(input_path: &Path) -> std::io::Result<String> {
+    let bytes = std::fs::read(input_path)?;
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => e.into_bytes().iter().map(|&b| b as char).collect(),
+    };
+    Ok(text.strip_prefix('\u{feff}').map(str::to_string).unwrap_or(text))
+}
+
+/// Imports the playlist at `input_path`, choosing the parser from the file
+/// extension: `.xspf`, `.jspf`, `.m3u`/`.m3u8`, or `.pls`.
+pub(crate) fn import_playlist(
+    input_path: &Path,
+) -> std::io::Result<ImportedPlaylist> {
+    let ext = input_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "xspf" => crate::fs::xspf::import_xspf_playlist(input_path),
+        "jspf" => crate::fs::jspf::import_jspf_playlist(input_path),
+        "m3u" | "m3u8" => crate::fs::m3u::import_m3u_playlist(input_path),
+        "pls" => crate::fs::pls::import_pls_playlist(input_path),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unsupported playlist format: {}", input_path.display()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_entry_relative_absolute_and_uri() {
+        let base = Path::new("/playlists");
+        assert_eq!(
+            resolve_entry(base, "music/a.mp3"),
+            Some(PathBuf::from("/playlists/music/a.mp3"))
+        );
+        assert_eq!(
+            resolve_entry(base, "../music/a.mp3"),
+            Some(PathBuf::from("/playlists/../music/a.mp3"))
+        );
+        assert_eq!(
+            resolve_entry(base, "/music/a.mp3"),
+            Some(PathBuf::from("/music/a.mp3"))
+        );
+        assert_eq!(
+            resolve_entry(base, "file:///music/a.mp3"),
+            Some(PathBuf::from("/music/a.mp3"))
+        );
+        assert_eq!(
+            resolve_entry(base, "file:///music/a%20b%23.mp3"),
+            Some(PathBuf::from("/music/a b#.mp3"))
+        );
+        // Plain M3U/PLS entries are not URIs and are never decoded
+        assert_eq!(
+            resolve_entry(base, "/music/100%20.mp3"),
+            Some(PathBuf::from("/music/100%20.mp3"))
+        );
+        assert_eq!(resolve_entry(base, "http://example.com/stream"), None);
+    }
+
+    #[test]
+    fn test_read_playlist_text_strips_bom_and_decodes_latin1() {
+        let dir = tempfile::tempdir().unwrap();
+        let utf8 = dir.path().join("utf8.m3u8");
+        std::fs::write(&utf8, "\u{feff}Café.mp3\n").unwrap();
+        assert_eq!(read_playlist_text(&utf8).unwrap(), "Café.mp3\n");
+
+        let latin1 = dir.path().join("latin1.m3u");
+        std::fs::write(&latin1, b"Caf\xe9.mp3\n").unwrap();
+        assert_eq!(read_playlist_text(&latin1).unwrap(), "Café.mp3\n");
+    }
+
+    #[test]
+    fn test_import_playlist_rejects_unknown_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("playlist.txt");
+        std::fs::write(&path, "a.mp3\n").unwrap();
+        assert!(import_playlist(&path).is_err());
+    }
+}
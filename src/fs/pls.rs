@@ -0,0 +1,265 @@
+//! PLS playlist export and import.
+//!
+//! Handles the INI-style PLS format (`[playlist]`, `FileN`, `TitleN`,
+//! `LengthN`, `NumberOfEntries`) used by internet-radio players. Exports
+//! number the entries from 1 in the order given, building `TitleN` from the
+//! metadata already held in `RightPanelFile`.
+//!
+//! Imports order entries by their index `N`; relative entries are resolved
+//! against the playlist file's directory, and `TitleN`/`LengthN` are kept as
+//! metadata hints, `TitleN` split into creator and title like an M3U
+//! `#EXTINF` line.
+//!
+//! Public API:
+//!     export_pls_playlist — write a playlist to a PLS file
+//!     import_pls_playlist — read tracks back from a PLS file
+
+use crate::fs::m3u::{split_display_title, track_display_title};
+use crate::fs::playlist_export::ExportOptions;
+use crate::fs::playlist_import::{
+    ImportedPlaylist, ImportedTrack, read_playlist_text,
+};
+use crate::gui::RightPanelFile;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Exports a playlist of the given files to a PLS file at the specified
+/// output path. Lengths are written in whole seconds, or `-1` when unknown,
+/// and the file ends with `NumberOfEntries` and `Version=2`. Locations are
+/// written absolute or relative according to `options`. `on_progress` is
+/// called with the number of tracks written so far.
+pub(crate) fn export_pls_playlist(
+    files: &[RightPanelFile],
+    output_path: &Path,
+    options: &ExportOptions,
+    on_progress: &mut dyn FnMut(usize),
+) -> std::io::Result<()> {
+    let mut pls = String::from("[playlist]\n");
+    for (i, file) in files.iter().enumerate() {
+        let n = i + 1;
+        let seconds =
+            file.duration_ms.map(|ms| (ms / 1000) as i64).unwrap_or(-1);
+        let location = options.entry_location(&file.path, output_path);
+        pls.push_str(&format!("File{n}={}\n", location.plain_path()));
+        pls.push_str(&format!("Title{n}={}\n", track_display_title(file)));
+        pls.push_str(&format!("Length{n}={seconds}\n"));
+        on_progress(n);
+    }
+    pls.push_str(&format!("NumberOfEntries={}\n", files.len()));
+    pls.push_str("Version=2\n");
+
+    let mut file = File::create(output_path)?;
+    file.write_all(pls.as_bytes())?;
+    Ok(())
+}
+
+/// Fields of one numbered PLS entry, collected across the file since the
+/// `FileN`, `TitleN`, and `LengthN` keys need not be adjacent.
+#[derive(Default)]
+struct PlsEntry {
+    file: Option<String>,
+    title: Option<String>,
+    length_secs: Option<i64>,
+}
+
+/// Imports the tracks of the PLS playlist at `input_path`. Keys are matched
+/// case-insensitively; `NumberOfEntries`, `Version`, and unknown keys are
+/// ignored, and entries without a `FileN` key are skipped.
+pub(crate) fn import_pls_playlist(
+    input_path: &Path,
+) -> std::io::Result<ImportedPlaylist> {
+    let text = read_playlist_text(input_path)?;
+    let base_dir = input_path.parent().unwrap_or(Path::new(""));
+
+    let mut entries: BTreeMap<u32, PlsEntry> = BTreeMap::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('[') || line.starts_with(';') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim().to_string();
+        let (field, index) = if let Some(n) = key.strip_prefix("file") {
+            ("file", n)
+        } else if let Some(n) = key.strip_prefix("title") {
+            ("title", n)
+        } else if let Some(n) = key.strip_prefix("length") {
+            ("length", n)
+        } else {
+            continue;
+        };
+        let Ok(index) = index.parse::<u32>() else {
+            continue;
+        };
+        let entry = entries.entry(index).or_default();
+        match field {
+            "file" => entry.file = Some(value),
+            "title" => entry.title = Some(value).filter(|t| !t.is_empty()),
+            _ => entry.length_secs = value.parse().ok(),
+        }
+    }
+
+    let mut imported = ImportedPlaylist::default();
+    for entry in entries.into_values() {
+        let Some(file) = entry.file else {
+            continue;
+        };
+        let (creator, title) =
+            entry.title.as_deref().map(split_display_title).unwrap_or_default();
+        imported.push_entry(
+            base_dir,
+            &file,
+            ImportedTrack {
+                creator,
+                title,
+                duration_ms: entry
+                    .length_secs
+                    .filter(|secs| *secs >= 0)
+                    .map(|secs| secs as u64 * 1000),
+                ..Default::default()
+            },
+        );
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_pls_playlist, import_pls_playlist};
+    use crate::fs::playlist_export::ExportOptions;
+    use crate::fs::test_fixtures::right_panel_file;
+    use std::path::PathBuf;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_pls_export_writes_numbered_entries_in_order() {
+        let files = vec![
+            right_panel_file(
+                "/music/b.mp3",
+                Some("Artist2"),
+                Some("Title2"),
+                Some(61_999),
+            ),
+            right_panel_file("/music/a.mp3", None, None, None),
+        ];
+        let out_file = NamedTempFile::new().unwrap();
+        export_pls_playlist(
+            &files,
+            out_file.path(),
+            &ExportOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+
+        let pls = std::fs::read_to_string(out_file.path()).unwrap();
+        assert_eq!(
+            pls,
+            "[playlist]\n\
+             File1=/music/b.mp3\n\
+             Title1=Artist2 - Title2\n\
+             Length1=61\n\
+             File2=/music/a.mp3\n\
+             Title2=a\n\
+             Length2=-1\n\
+             NumberOfEntries=2\n\
+             Version=2\n"
+        );
+    }
+
+    #[test]
+    fn test_pls_export_then_import_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.mp3");
+        let second = dir.path().join("second.mp3");
+        std::fs::write(&first, b"").unwrap();
+        std::fs::write(&second, b"").unwrap();
+        let files = vec![
+            right_panel_file(
+                second.to_str().unwrap(),
+                Some("Artist"),
+                Some("Second"),
+                Some(90_000),
+            ),
+            right_panel_file(first.to_str().unwrap(), None, None, None),
+        ];
+        let playlist_path = dir.path().join("out.pls");
+        export_pls_playlist(
+            &files,
+            &playlist_path,
+            &ExportOptions::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+
+        let imported = import_pls_playlist(&playlist_path).unwrap();
+
+        let found: Vec<_> =
+            imported.found.iter().map(|t| t.path.clone()).collect();
+        assert_eq!(found, vec![second, first]);
+        // The creator written into TitleN is split off again
+        assert_eq!(imported.found[0].creator.as_deref(), Some("Artist"));
+        assert_eq!(imported.found[0].title.as_deref(), Some("Second"));
+        assert_eq!(imported.found[0].duration_ms, Some(90_000));
+        assert_eq!(imported.found[1].duration_ms, None);
+        assert!(imported.missing.is_empty());
+    }
+
+    #[test]
+    fn test_pls_import_orders_by_index_and_keeps_hints() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.mp3");
+        let second = dir.path().join("second.ogg");
+        std::fs::write(&first, b"").unwrap();
+        std::fs::write(&second, b"").unwrap();
+        let pls = format!(
+            "[playlist]\n\
+             File2={}\n\
+             Title2=Second\n\
+             Length2=-1\n\
+             file1=first.mp3\n\
+             title1=First\n\
+             length1=90\n\
+             File3=gone.mp3\n\
+             File4=http://radio.example.com:8000/stream\n\
+             NumberOfEntries=4\n\
+             Version=2\n",
+            second.display()
+        );
+        let playlist_path = dir.path().join("list.pls");
+        std::fs::write(&playlist_path, pls).unwrap();
+
+        let imported = import_pls_playlist(&playlist_path).unwrap();
+
+        assert_eq!(imported.found.len(), 2);
+        assert_eq!(imported.found[0].path, first);
+        assert_eq!(imported.found[0].title.as_deref(), Some("First"));
+        assert_eq!(imported.found[0].duration_ms, Some(90_000));
+        assert_eq!(imported.found[1].path, second);
+        assert_eq!(imported.found[1].title.as_deref(), Some("Second"));
+        assert_eq!(imported.found[1].duration_ms, None);
+        assert_eq!(
+            imported.missing,
+            vec![
+                dir.path().join("gone.mp3"),
+                PathBuf::from("http://radio.example.com:8000/stream")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pls_import_skips_entries_without_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let playlist_path = dir.path().join("list.pls");
+        std::fs::write(&playlist_path, "[playlist]\nTitle1=Orphan\n").unwrap();
+
+        let imported = import_pls_playlist(&playlist_path).unwrap();
+
+        assert!(imported.found.is_empty());
+        assert!(imported.missing.is_empty());
+    }
+}
@@ -0,0 +1,24 @@
+//! Fixtures shared by the playlist format tests.
+//!
+//! Public API:
+//!     right_panel_file — a playlist entry with a creator, title and duration
+
+use crate::gui::RightPanelFile;
+use std::path::PathBuf;
+
+/// A playlist entry for `path` with only the fields the plain-text formats
+/// write: creator, title, and duration.
+pub(crate) fn right_panel_file(
+    path: &str,
+    creator: Option<&str>,
+    title: Option<&str>,
+    duration_ms: Option<u64>,
+) -> RightPanelFile {
+    RightPanelFile {
+        path: PathBuf::from(path),
+        creator: creator.map(str::to_string),
+        title: title.map(str::to_string),
+        duration_ms,
+        ..Default::default()
+    }
+}
@@ -1,17 +1,88 @@
-//! XSPF (XML Shareable Playlist Format) playlist export.
+//! XSPF (XML Shareable Playlist Format) playlist export and import.
 //!
 //! Exports a list of audio files with their metadata to the XSPF format,
 //! which can be opened by media players such as VLC. Each track entry
 //! includes location, title, creator, album, duration, genre, identifier,
-//! annotation, track number, and album art URI where available.
+//! annotation, track number, and album art URI where available, all taken
+//! from the metadata cached in `RightPanelFile` rather than re-read from disk.
+//! The playlist itself gets a title, creator, annotation, and date from
+//! `PlaylistInfo`, and an `<extension application="playlist_ui">` block
+//! recording the right panel's sort column and order, a `<thenBy>` element
+//! per further sort key, and the shuffle state.
+//!
+//! Imports parse an XSPF file with the `xspf` crate and resolve each track's
+//! location back to a local path, separating entries whose files still exist
+//! from those that no longer do. Locations are percent-encoded URIs in both
+//! directions (see `file_uri`). The playlist metadata and the `playlist_ui`
+//! extension are read back into `ImportedPlaylist::info`; other
+//! applications' extensions are ignored.
 //!
 //! Public API:
 //!     export_xspf_playlist — write a playlist to an XSPF file
+//!     import_xspf_playlist — read tracks back from an XSPF file
 
-use crate::fs::media_metadata::extract_media_metadata;
-use crate::gui::RightPanelFile;
+use crate::fs::playlist_export::{ExportOptions, PlaylistInfo, PlaylistView};
+use crate::fs::playlist_import::{ImportedPlaylist, ImportedTrack};
+use crate::gui::{RightPanelFile, SortColumn, SortKey, SortOrder};
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
+use xml::reader::XmlEvent;
+
+/// The `application` of the extension block holding the right panel's view.
+const EXTENSION_APPLICATION: &str = "playlist_ui";
+
+/// Sort columns and their names in the `playlist_ui` extension.
+const SORT_COLUMN_NAMES: [(SortColumn, &str); 18] = [
+    (SortColumn::Directory, "directory"),
+    (SortColumn::File, "file"),
+    (SortColumn::Creator, "creator"),
+    (SortColumn::Album, "album"),
+    (SortColumn::Title, "title"),
+    (SortColumn::Genre, "genre"),
+    (SortColumn::Duration, "duration"),
+    (SortColumn::Manual, "manual"),
+    (SortColumn::TrackNumber, "track_number"),
+    (SortColumn::DiscNumber, "disc_number"),
+    (SortColumn::Year, "year"),
+    (SortColumn::AlbumArtist, "album_artist"),
+    (SortColumn::Bitrate, "bitrate"),
+    (SortColumn::SampleRate, "sample_rate"),
+    (SortColumn::Channels, "channels"),
+    (SortColumn::BitDepth, "bit_depth"),
+    (SortColumn::Codec, "codec"),
+    (SortColumn::FileSize, "file_size"),
+];
+
+/// The name of `column` in the `playlist_ui` extension.
+fn sort_column_name(column: SortColumn) -> &'static str {
+    SORT_COLUMN_NAMES
+        .iter()
+        .find(|(c, _)| *c == column)
+        .map_or("directory", |(_, name)| name)
+}
+
+/// The sort column named `name` in the `playlist_ui` extension, if known.
+fn parse_sort_column(name: &str) -> Option<SortColumn> {
+    SORT_COLUMN_NAMES.iter().find(|(_, n)| *n == name).map(|(c, _)| *c)
+}
+
+/// The name of `order` in the `playlist_ui` extension.
+fn sort_order_name(order: SortOrder) -> &'static str {
+    match order {
+        SortOrder::Asc => "asc",
+        SortOrder::Desc => "desc",
+    }
+}
+
+/// The sort order named `name` in the `playlist_ui` extension, if known.
+fn parse_sort_order(name: &str) -> Option<SortOrder> {
+    match name {
+        "asc" => Some(SortOrder::Asc),
+        "desc" => Some(SortOrder::Desc),
+        _ => None,
+    }
+}
 
 // Simple XML escape for special characters
 fn xml_escape(s: &str) -> String {
@@ -24,10 +95,16 @@ fn xml_escape(s: &str) -> String {
 
 /// Exports a playlist of the given files to an XSPF (XML Shareable Playlist
 /// Format) file at the specified output path, including metadata such as title,
-/// artist, album, duration, genre, and more for each track.
+/// artist, album, duration, genre, and more for each track. Locations are
+/// written as URIs according to `options` (see `EntryLocation::uri`). The
+/// playlist-level fields of `info` are written where set. `on_progress` is
+/// called with the number of tracks written so far.
 pub(crate) fn export_xspf_playlist(
     files: &[RightPanelFile],
     output_path: &std::path::Path,
+    options: &ExportOptions,
+    info: &PlaylistInfo,
+    on_progress: &mut dyn FnMut(usize),
 ) -> std::io::Result<()> {
     fn push_line(xml: &mut String, indent: usize, line: &str) {
         for _ in 0..indent {
@@ -44,85 +121,120 @@ pub(crate) fn export_xspf_playlist(
         0,
         r#"<playlist version="1" xmlns="http://xspf.org/ns/0/">"#,
     );
+    // XSPF requires this order of the playlist-level elements
+    for (element, value) in [
+        ("title", &info.title),
+        ("creator", &info.creator),
+        ("annotation", &info.annotation),
+        ("date", &info.date),
+    ] {
+        if let Some(value) = value {
+            push_line(
+                &mut xml,
+                1,
+                &format!("<{element}>{}</{element}>", xml_escape(value)),
+            );
+        }
+    }
+    if let Some(view) = &info.view {
+        let column = sort_column_name(view.sort_column);
+        let order = sort_order_name(view.sort_order);
+        push_line(
+            &mut xml,
+            1,
+            &format!(r#"<extension application="{EXTENSION_APPLICATION}">"#),
+        );
+        push_line(&mut xml, 2, &format!("<sortColumn>{column}</sortColumn>"));
+        push_line(&mut xml, 2, &format!("<sortOrder>{order}</sortOrder>"));
+        for key in &view.then_by {
+            push_line(
+                &mut xml,
+                2,
+                &format!(
+                    r#"<thenBy column="{}" order="{}"/>"#,
+                    sort_column_name(key.column),
+                    sort_order_name(key.order)
+                ),
+            );
+        }
+        push_line(
+            &mut xml,
+            2,
+            &format!("<shuffled>{}</shuffled>", view.shuffled),
+        );
+        push_line(&mut xml, 1, "</extension>");
+    }
     push_line(&mut xml, 1, "<trackList>");
 
-    for file in files {
-        let meta = extract_media_metadata(&file.path);
+    for (i, file) in files.iter().enumerate() {
+        let location = options.entry_location(&file.path, output_path).uri();
         push_line(&mut xml, 2, "<track>");
         push_line(
             &mut xml,
             3,
-            &format!(
-                "<location>file://{}</location>",
-                xml_escape(&file.path.display().to_string())
-            ),
+            &format!("<location>{}</location>", xml_escape(&location)),
         );
-        if let Some(title) = meta.title {
+        if let Some(title) = &file.title {
             push_line(
                 &mut xml,
                 3,
-                &format!("<title>{}</title>", xml_escape(&title)),
+                &format!("<title>{}</title>", xml_escape(title)),
             );
         }
-        if let Some(creator) = meta.creator {
+        if let Some(creator) = &file.creator {
             push_line(
                 &mut xml,
                 3,
-                &format!("<creator>{}</creator>", xml_escape(&creator)),
+                &format!("<creator>{}</creator>", xml_escape(creator)),
             );
         }
-        if let Some(album) = meta.album {
+        if let Some(album) = &file.album {
             push_line(
                 &mut xml,
                 3,
-                &format!("<album>{}</album>", xml_escape(&album)),
+                &format!("<album>{}</album>", xml_escape(album)),
             );
         }
-        if let Some(duration) = meta.duration_ms {
+        if let Some(duration) = file.duration_ms {
             push_line(&mut xml, 3, &format!("<duration>{duration}</duration>"));
         }
-        if let Some(genre) = meta.genre {
+        if let Some(genre) = &file.genre {
             push_line(
                 &mut xml,
                 3,
-                &format!("<genre>{}</genre>", xml_escape(&genre)),
+                &format!("<genre>{}</genre>", xml_escape(genre)),
             );
         }
-        if let Some(identifier) = meta.identifier {
+        if let Some(identifier) = &file.identifier {
             push_line(
                 &mut xml,
                 3,
-                &format!(
-                    "<identifier>{}</identifier>",
-                    xml_escape(&identifier)
-                ),
+                &format!("<identifier>{}</identifier>", xml_escape(identifier)),
             );
         }
-        if let Some(annotation) = meta.annotation {
+        if let Some(annotation) = &file.annotation {
             push_line(
                 &mut xml,
                 3,
-                &format!(
-                    "<annotation>{}</annotation>",
-                    xml_escape(&annotation)
-                ),
+                &format!("<annotation>{}</annotation>", xml_escape(annotation)),
             );
         }
-        if let Some(track_num) = meta.track_num {
+        if let Some(track_num) = file.track_num {
             push_line(
                 &mut xml,
                 3,
                 &format!("<trackNum>{track_num}</trackNum>"),
             );
         }
-        if let Some(image_uri) = meta.image_uri {
+        if let Some(image_uri) = &file.image_uri {
             push_line(
                 &mut xml,
                 3,
-                &format!("<image>{}</image>", xml_escape(&image_uri)),
+                &format!("<image>{}</image>", xml_escape(image_uri)),
             );
         }
         push_line(&mut xml, 2, "</track>");
+        on_progress(i + 1);
     }
 
     push_line(&mut xml, 1, "</trackList>");
@@ -133,9 +245,118 @@ pub(crate) fn export_xspf_playlist(
     Ok(())
 }
 
+/// Reads the right panel's view back from the events of a `playlist_ui`
+/// extension block. Returns `None` unless both the sort column and the sort
+/// order are present and known; `<thenBy>` keys with an unknown column or
+/// order are skipped.
+fn parse_view_extension(content: &[XmlEvent]) -> Option<PlaylistView> {
+    let (mut column, mut order, mut shuffled) = (None, None, false);
+    let mut then_by = Vec::new();
+    let mut element = None;
+    for event in content {
+        match event {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                element = Some(name.local_name.as_str());
+                if name.local_name == "thenBy" {
+                    let attribute = |key: &str| {
+                        attributes
+                            .iter()
+                            .find(|a| a.name.local_name == key)
+                            .map(|a| a.value.as_str())
+                    };
+                    let key_column =
+                        attribute("column").and_then(parse_sort_column);
+                    let key_order =
+                        attribute("order").and_then(parse_sort_order);
+                    if let (Some(column), Some(order)) = (key_column, key_order)
+                    {
+                        then_by.push(SortKey { column, order });
+                    }
+                }
+            },
+            XmlEvent::Characters(text) => {
+                let text = text.trim();
+                match element {
+                    Some("sortColumn") => column = parse_sort_column(text),
+                    Some("sortOrder") => order = parse_sort_order(text),
+                    Some("shuffled") => shuffled = text == "true",
+                    _ => {},
+                }
+            },
+            XmlEvent::EndElement { .. } => element = None,
+            _ => {},
+        }
+    }
+    Some(PlaylistView {
+        sort_column: column?,
+        sort_order: order?,
+        then_by,
+        shuffled,
+    })
+}
+
+/// Imports the tracks of the XSPF playlist at `input_path`, resolving each
+/// track's first `<location>` to a local path (relative locations against the
+/// playlist's directory). The track's title, creator, album, and duration are
+/// kept as hints. Locations that are not local files, or whose files no
+/// longer exist, are returned in `missing`. The playlist's title, creator,
+/// annotation, date, and `playlist_ui` view are returned in `info`.
+pub(crate) fn import_xspf_playlist(
+    input_path: &Path,
+) -> std::io::Result<ImportedPlaylist> {
+    let playlist =
+        xspf::Playlist::read_file(input_path).map_err(|e| match e {
+            xspf::parse::ParseError::IoError(io_err) => io_err,
+            other => std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid XSPF playlist: {other:?}"),
+            ),
+        })?;
+
+    let non_empty = |s: Option<String>| s.filter(|s| !s.is_empty());
+    let mut imported = ImportedPlaylist {
+        info: PlaylistInfo {
+            title: non_empty(playlist.title),
+            creator: non_empty(playlist.creator),
+            annotation: non_empty(playlist.annotation),
+            date: non_empty(playlist.date),
+            view: playlist
+                .extension
+                .iter()
+                .filter(|ext| ext.application == EXTENSION_APPLICATION)
+                .find_map(|ext| parse_view_extension(&ext.content)),
+        },
+        ..Default::default()
+    };
+    let base_dir = input_path.parent().unwrap_or(Path::new(""));
+    for track in playlist.track_list {
+        // Players render only the first location of a track
+        let Some(location) = track.location.first() else {
+            continue;
+        };
+        imported.push_location(
+            base_dir,
+            location,
+            ImportedTrack {
+                creator: track.creator,
+                album: track.album,
+                title: track.title,
+                duration_ms: track.duration,
+                ..Default::default()
+            },
+        );
+    }
+    Ok(imported)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::gui::{FileTreeApp, RightPanelFile, SortColumn, SortOrder};
+    use crate::fs::playlist_export::{
+        ExportOptions, PlaylistInfo, PlaylistView,
+    };
+    use crate::gui::{
+        FileTreeApp, RightPanelFile, SortColumn, SortKey, SortOrder,
+    };
     use std::path::PathBuf;
     use tempfile::NamedTempFile;
 
@@ -149,6 +370,7 @@ mod tests {
             title: Some("Title1".to_string()),
             genre: Some("Genre1".to_string()),
             duration_ms: Some(1),
+            ..Default::default()
         };
         let file2 = RightPanelFile {
             path: PathBuf::from("/music/b.mp3"),
@@ -157,21 +379,28 @@ mod tests {
             title: Some("Title2".to_string()),
             genre: Some("Genre2".to_string()),
             duration_ms: Some(1),
+            ..Default::default()
         };
 
         let persist_path = NamedTempFile::new().unwrap().path().to_path_buf();
         let mut app = FileTreeApp::new(vec![], &[], persist_path, None);
-        app.right_panel_files = vec![file2.clone(), file1.clone()]; // Intentionally reversed
-        app.right_panel_sort_column = SortColumn::File;
-        app.right_panel_sort_order = SortOrder::Asc;
+        app.playlist_mut().files = vec![file2.clone(), file1.clone()]; // Intentionally reversed
+        app.playlist_mut().sort_column = SortColumn::File;
+        app.playlist_mut().sort_order = SortOrder::Asc;
 
         let sorted = app.sorted_right_panel_files();
         assert_eq!(sorted[0].path, file1.path);
         assert_eq!(sorted[1].path, file2.path);
 
         let out_file = NamedTempFile::new().unwrap();
-        crate::fs::xspf::export_xspf_playlist(&sorted, out_file.path())
-            .unwrap();
+        crate::fs::xspf::export_xspf_playlist(
+            &sorted,
+            out_file.path(),
+            &ExportOptions::default(),
+            &PlaylistInfo::default(),
+            &mut |_| {},
+        )
+        .unwrap();
 
         let xml = std::fs::read_to_string(out_file.path()).unwrap();
         let locations: Vec<_> = xml
@@ -189,4 +418,322 @@ mod tests {
             format!("file://{}", file2.path.to_string_lossy())
         );
     }
+
+    #[test]
+    fn test_xspf_import_round_trips_export_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path_b = dir.path().join("b.mp3");
+        let path_a = dir.path().join("a.mp3");
+        std::fs::write(&path_b, b"").unwrap();
+        std::fs::write(&path_a, b"").unwrap();
+        let files: Vec<RightPanelFile> = [&path_b, &path_a]
+            .iter()
+            .map(|p| RightPanelFile {
+                path: p.to_path_buf(),
+                ..Default::default()
+            })
+            .collect();
+
+        let out_file = dir.path().join("playlist.xspf");
+        crate::fs::xspf::export_xspf_playlist(
+            &files,
+            &out_file,
+            &ExportOptions::default(),
+            &PlaylistInfo::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&out_file).unwrap();
+
+        let found: Vec<_> =
+            imported.found.into_iter().map(|t| t.path).collect();
+        assert_eq!(found, vec![path_b, path_a]);
+        assert!(imported.missing.is_empty());
+    }
+
+    #[test]
+    fn test_xspf_round_trips_awkward_filenames() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> =
+            ["a b#1?.mp3", "100% Café.mp3", "日本語 & <co>.mp3"]
+                .iter()
+                .map(|name| dir.path().join(name))
+                .collect();
+        for path in &paths {
+            std::fs::write(path, b"").unwrap();
+        }
+        let files: Vec<RightPanelFile> = paths
+            .iter()
+            .map(|p| RightPanelFile { path: p.clone(), ..Default::default() })
+            .collect();
+
+        let out_file = dir.path().join("playlist.xspf");
+        crate::fs::xspf::export_xspf_playlist(
+            &files,
+            &out_file,
+            &ExportOptions::default(),
+            &PlaylistInfo::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        let xml = std::fs::read_to_string(&out_file).unwrap();
+        assert!(xml.contains("a%20b%231%3F.mp3</location>"));
+        assert!(xml.contains("100%25%20Caf%C3%A9.mp3</location>"));
+        assert!(xml.contains("&amp;%20%3Cco%3E.mp3</location>"));
+
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&out_file).unwrap();
+        let found: Vec<_> =
+            imported.found.into_iter().map(|t| t.path).collect();
+        assert_eq!(found, paths);
+        assert!(imported.missing.is_empty());
+    }
+
+    #[test]
+    fn test_xspf_relative_export_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("lists")).unwrap();
+        std::fs::create_dir_all(dir.path().join("rock band")).unwrap();
+        let song = dir.path().join("rock band").join("a#1.mp3");
+        std::fs::write(&song, b"").unwrap();
+        let files =
+            vec![RightPanelFile { path: song.clone(), ..Default::default() }];
+
+        let out_file = dir.path().join("lists").join("playlist.xspf");
+        crate::fs::xspf::export_xspf_playlist(
+            &files,
+            &out_file,
+            &ExportOptions { relative_paths: true, ..Default::default() },
+            &PlaylistInfo::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        let xml = std::fs::read_to_string(&out_file).unwrap();
+        assert!(xml.contains("<location>../rock%20band/a%231.mp3</location>"));
+
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&out_file).unwrap();
+        assert_eq!(imported.found.len(), 1);
+        assert!(imported.found[0].path.ends_with("rock band/a#1.mp3"));
+        assert!(imported.missing.is_empty());
+    }
+
+    #[test]
+    fn test_xspf_relative_export_round_trips_colon_in_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let song = dir.path().join("AC 1:00.mp3");
+        std::fs::write(&song, b"").unwrap();
+        let files =
+            vec![RightPanelFile { path: song.clone(), ..Default::default() }];
+
+        let out_file = dir.path().join("playlist.xspf");
+        crate::fs::xspf::export_xspf_playlist(
+            &files,
+            &out_file,
+            &ExportOptions { relative_paths: true, ..Default::default() },
+            &PlaylistInfo::default(),
+            &mut |_| {},
+        )
+        .unwrap();
+        let xml = std::fs::read_to_string(&out_file).unwrap();
+        assert!(xml.contains("<location>./AC%201:00.mp3</location>"));
+
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&out_file).unwrap();
+        let found: Vec<_> =
+            imported.found.into_iter().map(|t| t.path).collect();
+        assert_eq!(found, vec![song]);
+        assert!(imported.missing.is_empty());
+    }
+
+    #[test]
+    fn test_xspf_import_reports_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("here.mp3");
+        std::fs::write(&existing, b"").unwrap();
+        let gone = dir.path().join("gone.mp3");
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+    <trackList>
+        <track><location>file://{}</location></track>
+        <track><location>file://{}</location></track>
+        <track><location>http://example.com/stream</location></track>
+    </trackList>
+</playlist>"#,
+            existing.display(),
+            gone.display()
+        );
+        let playlist_path = dir.path().join("playlist.xspf");
+        std::fs::write(&playlist_path, xml).unwrap();
+
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&playlist_path).unwrap();
+
+        let found: Vec<_> =
+            imported.found.into_iter().map(|t| t.path).collect();
+        assert_eq!(found, vec![existing]);
+        assert_eq!(
+            imported.missing,
+            vec![gone, PathBuf::from("http://example.com/stream")]
+        );
+    }
+
+    #[test]
+    fn test_xspf_import_keeps_track_hints_and_relative_locations() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("music")).unwrap();
+        let song = dir.path().join("music").join("song.mp3");
+        std::fs::write(&song, b"").unwrap();
+        let xml = r#"<playlist version="1" xmlns="http://xspf.org/ns/0/">
+    <trackList>
+        <track>
+            <location>music/s%6Fng.mp3</location>
+            <title>Song</title>
+            <creator>Artist</creator>
+            <album>Album</album>
+            <duration>61000</duration>
+        </track>
+    </trackList>
+</playlist>"#;
+        let playlist_path = dir.path().join("playlist.xspf");
+        std::fs::write(&playlist_path, xml).unwrap();
+
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&playlist_path).unwrap();
+
+        assert_eq!(imported.found.len(), 1);
+        let track = &imported.found[0];
+        assert_eq!(track.path, song);
+        assert_eq!(track.title.as_deref(), Some("Song"));
+        assert_eq!(track.creator.as_deref(), Some("Artist"));
+        assert_eq!(track.album.as_deref(), Some("Album"));
+        assert_eq!(track.duration_ms, Some(61000));
+    }
+
+    #[test]
+    fn test_xspf_playlist_info_and_view_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let song = dir.path().join("a.mp3");
+        std::fs::write(&song, b"").unwrap();
+        let info = PlaylistInfo {
+            title: Some("Rock & Roll".to_string()),
+            creator: Some("DJ".to_string()),
+            annotation: None,
+            date: Some("2025-06-01T20:00:00+02:00".to_string()),
+            view: Some(PlaylistView {
+                sort_column: SortColumn::Duration,
+                sort_order: SortOrder::Desc,
+                then_by: vec![],
+                shuffled: true,
+            }),
+        };
+
+        let out_file = dir.path().join("playlist.xspf");
+        crate::fs::xspf::export_xspf_playlist(
+            &[RightPanelFile { path: song, ..Default::default() }],
+            &out_file,
+            &ExportOptions::default(),
+            &info,
+            &mut |_| {},
+        )
+        .unwrap();
+        let xml = std::fs::read_to_string(&out_file).unwrap();
+        assert!(xml.contains(
+            "    <title>Rock &amp; Roll</title>\n\
+             \x20   <creator>DJ</creator>\n\
+             \x20   <date>2025-06-01T20:00:00+02:00</date>\n\
+             \x20   <extension application=\"playlist_ui\">\n\
+             \x20       <sortColumn>duration</sortColumn>\n\
+             \x20       <sortOrder>desc</sortOrder>\n\
+             \x20       <shuffled>true</shuffled>\n\
+             \x20   </extension>\n\
+             \x20   <trackList>"
+        ));
+
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&out_file).unwrap();
+        assert_eq!(imported.info, info);
+        assert_eq!(imported.found.len(), 1);
+    }
+
+    #[test]
+    fn test_xspf_view_round_trips_further_sort_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = PlaylistInfo {
+            view: Some(PlaylistView {
+                sort_column: SortColumn::Album,
+                sort_order: SortOrder::Asc,
+                then_by: vec![
+                    SortKey {
+                        column: SortColumn::DiscNumber,
+                        order: SortOrder::Asc,
+                    },
+                    SortKey {
+                        column: SortColumn::TrackNumber,
+                        order: SortOrder::Desc,
+                    },
+                ],
+                shuffled: false,
+            }),
+            ..Default::default()
+        };
+
+        let out_file = dir.path().join("playlist.xspf");
+        crate::fs::xspf::export_xspf_playlist(
+            &[],
+            &out_file,
+            &ExportOptions::default(),
+            &info,
+            &mut |_| {},
+        )
+        .unwrap();
+        let xml = std::fs::read_to_string(&out_file).unwrap();
+        assert!(xml.contains(
+            "        <sortColumn>album</sortColumn>\n\
+             \x20       <sortOrder>asc</sortOrder>\n\
+             \x20       <thenBy column=\"disc_number\" order=\"asc\"/>\n\
+             \x20       <thenBy column=\"track_number\" order=\"desc\"/>\n\
+             \x20       <shuffled>false</shuffled>\n"
+        ));
+
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&out_file).unwrap();
+        assert_eq!(imported.info, info);
+    }
+
+    #[test]
+    fn test_xspf_import_ignores_other_and_incomplete_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let xml = r#"<playlist version="1" xmlns="http://xspf.org/ns/0/">
+    <title></title>
+    <extension application="http://example.com/other">
+        <sortColumn>title</sortColumn>
+        <sortOrder>asc</sortOrder>
+    </extension>
+    <extension application="playlist_ui">
+        <sortColumn>rating</sortColumn>
+        <sortOrder>asc</sortOrder>
+    </extension>
+    <trackList/>
+</playlist>"#;
+        let playlist_path = dir.path().join("playlist.xspf");
+        std::fs::write(&playlist_path, xml).unwrap();
+
+        let imported =
+            crate::fs::xspf::import_xspf_playlist(&playlist_path).unwrap();
+        assert_eq!(imported.info, PlaylistInfo::default());
+    }
+
+    #[test]
+    fn test_xspf_import_invalid_file_is_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let playlist_path = dir.path().join("broken.xspf");
+        std::fs::write(&playlist_path, "<playlist><trackList>").unwrap();
+        assert!(crate::fs::xspf::import_xspf_playlist(&playlist_path).is_err());
+
+        let absent = dir.path().join("absent.xspf");
+        assert!(crate::fs::xspf::import_xspf_playlist(&absent).is_err());
+    }
 }
@@ -0,0 +1,203 @@
+//! Column layout of the right-panel playlist table.
+//!
+//! The user chooses which columns the playlist table shows, in which order,
+//! and how wide each one is. Widths are relative: a column of width 2 takes
+//! twice the space of a column of width 1. The layout is persisted in Sled
+//! and shared by all playlists. The directory, file, tag and duration
+//! columns are visible by default; the track, disc, year, album artist and
+//! audio-property columns start hidden.
+//!
+//! Public API:
+//!     PlaylistColumn        — a column of the playlist table
+//!     ColumnLayout          — a column's visibility and width, in table order
+//!     MIN_COLUMN_WIDTH      — narrowest relative column width
+//!     MAX_COLUMN_WIDTH      — widest relative column width
+//!     default_column_layout — the original columns visible, equally wide
+//!     normalize_column_layout — repairs a stored layout
+
+use crate::gui::SortColumn;
+
+/// Narrowest relative width of a playlist column.
+pub(crate) const MIN_COLUMN_WIDTH: u16 = 1;
+
+/// Widest relative width of a playlist column.
+pub(crate) const MAX_COLUMN_WIDTH: u16 = 8;
+
+/// A column of the right-panel playlist table.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, bincode::Encode, bincode::Decode,
+)]
+pub enum PlaylistColumn {
+    Directory,
+    File,
+    Creator,
+    Album,
+    Title,
+    Genre,
+    Duration,
+    TrackNumber,
+    DiscNumber,
+    Year,
+    AlbumArtist,
+    Bitrate,
+    SampleRate,
+    Channels,
+    BitDepth,
+    Codec,
+    FileSize,
+}
+
+impl PlaylistColumn {
+    /// Every column, in the default table order.
+    pub(crate) const ALL: [PlaylistColumn; 17] = [
+        PlaylistColumn::Directory,
+        PlaylistColumn::File,
+        PlaylistColumn::Creator,
+        PlaylistColumn::Album,
+        PlaylistColumn::Title,
+        PlaylistColumn::Genre,
+        PlaylistColumn::Duration,
+        PlaylistColumn::TrackNumber,
+        PlaylistColumn::DiscNumber,
+        PlaylistColumn::Year,
+        PlaylistColumn::AlbumArtist,
+        PlaylistColumn::Bitrate,
+        PlaylistColumn::SampleRate,
+        PlaylistColumn::Channels,
+        PlaylistColumn::BitDepth,
+        PlaylistColumn::Codec,
+        PlaylistColumn::FileSize,
+    ];
+
+    /// Number of leading columns of `ALL` shown by default.
+    const DEFAULT_VISIBLE: usize = 7;
+
+    /// The column's header text.
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            PlaylistColumn::Directory => "Directory",
+            PlaylistColumn::File => "File",
+            PlaylistColumn::Creator => "Musician",
+            PlaylistColumn::Album => "Album",
+            PlaylistColumn::Title => "Title",
+            PlaylistColumn::Genre => "Genre",
+            PlaylistColumn::Duration => "Duration",
+            PlaylistColumn::TrackNumber => "Track",
+            PlaylistColumn::DiscNumber => "Disc",
+            PlaylistColumn::Year => "Year",
+            PlaylistColumn::AlbumArtist => "Album Artist",
+            PlaylistColumn::Bitrate => "Bitrate",
+            PlaylistColumn::SampleRate => "Sample Rate",
+            PlaylistColumn::Channels => "Channels",
+            PlaylistColumn::BitDepth => "Bit Depth",
+            PlaylistColumn::Codec => "Codec",
+            PlaylistColumn::FileSize => "Size",
+        }
+    }
+
+    /// The sort key of clicking the column's header.
+    pub(crate) fn sort_column(self) -> SortColumn {
+        match self {
+            PlaylistColumn::Directory => SortColumn::Directory,
+            PlaylistColumn::File => SortColumn::File,
+            PlaylistColumn::Creator => SortColumn::Creator,
+            PlaylistColumn::Album => SortColumn::Album,
+            PlaylistColumn::Title => SortColumn::Title,
+            PlaylistColumn::Genre => SortColumn::Genre,
+            PlaylistColumn::Duration => SortColumn::Duration,
+            PlaylistColumn::TrackNumber => SortColumn::TrackNumber,
+            PlaylistColumn::DiscNumber => SortColumn::DiscNumber,
+            PlaylistColumn::Year => SortColumn::Year,
+            PlaylistColumn::AlbumArtist => SortColumn::AlbumArtist,
+            PlaylistColumn::Bitrate => SortColumn::Bitrate,
+            PlaylistColumn::SampleRate => SortColumn::SampleRate,
+            PlaylistColumn::Channels => SortColumn::Channels,
+            PlaylistColumn::BitDepth => SortColumn::BitDepth,
+            PlaylistColumn::Codec => SortColumn::Codec,
+            PlaylistColumn::FileSize => SortColumn::FileSize,
+        }
+    }
+}
+
+/// One column of the playlist table layout: whether it is shown and its
+/// relative width. A layout lists every column once, in table order.
+#[derive(Debug, Clone, PartialEq, bincode::Encode, bincode::Decode)]
+pub struct ColumnLayout {
+    pub column: PlaylistColumn,
+    pub visible: bool,
+    pub width: u16,
+}
+
+/// Every column equally wide, in the default order, with the directory,
+/// file, tag and duration columns visible and the rest hidden.
+pub(crate) fn default_column_layout() -> Vec<ColumnLayout> {
+    PlaylistColumn::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(index, column)| ColumnLayout {
+            column,
+            visible: index < PlaylistColumn::DEFAULT_VISIBLE,
+            width: MIN_COLUMN_WIDTH,
+        })
+        .collect()
+}
+
+/// Repairs a stored layout: drops repeated columns, clamps widths, appends
+/// columns it does not list (added in a later version) as hidden, and shows
+/// the first column if none is visible.
+pub(crate) fn normalize_column_layout(
+    layout: Vec<ColumnLayout>,
+) -> Vec<ColumnLayout> {
+    let mut normalized: Vec<ColumnLayout> = Vec::new();
+    for mut entry in layout {
+        if normalized.iter().all(|e| e.column != entry.column) {
+            entry.width = entry.width.clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+            normalized.push(entry);
+        }
+    }
+    for column in PlaylistColumn::ALL {
+        if normalized.iter().all(|e| e.column != column) {
+            normalized.push(ColumnLayout {
+                column,
+                visible: false,
+                width: MIN_COLUMN_WIDTH,
+            });
+        }
+    }
+    if !normalized.iter().any(|e| e.visible) {
+        normalized[0].visible = true;
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_column_layout() {
+        let layout = vec![
+            ColumnLayout {
+                column: PlaylistColumn::Album,
+                visible: false,
+                width: 20,
+            },
+            ColumnLayout {
+                column: PlaylistColumn::Album,
+                visible: true,
+                width: 1,
+            },
+        ];
+        let normalized = normalize_column_layout(layout);
+        assert_eq!(normalized.len(), PlaylistColumn::ALL.len());
+        assert_eq!(normalized[0].column, PlaylistColumn::Album);
+        assert_eq!(normalized[0].width, MAX_COLUMN_WIDTH);
+        // Nothing was visible, so the first column is shown
+        assert!(normalized[0].visible);
+        assert!(normalized[1..].iter().all(|e| !e.visible));
+        assert_eq!(
+            normalize_column_layout(default_column_layout()),
+            default_column_layout()
+        );
+    }
+}
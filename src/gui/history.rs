@@ -0,0 +1,135 @@
+//! Undo/redo history for right-panel playlist edits.
+//!
+//! Every playlist keeps its own `History`. Before an edit that changes the
+//! playlist's tracks, their order, its sorting, or its details, `update`
+//! records the playlist's previous `PlaylistState`; undoing swaps the
+//! current state for the recorded one and keeps the current one for redo. A
+//! new edit clears the redo stack. Only the most recent `MAX_HISTORY` edits
+//! are kept, since each entry holds a full copy of the playlist's tracks.
+//!
+//! Public API:
+//!     MAX_HISTORY   — number of edits that can be undone
+//!     PlaylistState — the undoable part of a playlist
+//!     History       — undo and redo stacks of one playlist
+
+use crate::gui::{RightPanelFile, SortColumn, SortKey, SortOrder};
+use std::path::PathBuf;
+
+/// How many edits of one playlist can be undone.
+pub(crate) const MAX_HISTORY: usize = 50;
+
+/// The part of a playlist an edit can change: its tracks in order, how
+/// they are sorted, its details, and the entries an import did not find.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PlaylistState {
+    pub files: Vec<RightPanelFile>,
+    pub sort_column: SortColumn,
+    pub sort_order: SortOrder,
+    pub then_by: Vec<SortKey>,
+    pub shuffled: bool,
+    pub title: String,
+    pub creator: String,
+    pub annotation: String,
+    pub missing_files: Vec<PathBuf>,
+}
+
+/// Undo and redo stacks of one playlist, most recent last.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct History {
+    undo: Vec<PlaylistState>,
+    redo: Vec<PlaylistState>,
+}
+
+impl History {
+    /// Records the state before an edit, dropping the oldest entry beyond
+    /// `MAX_HISTORY` and everything that could be redone.
+    pub(crate) fn record(&mut self, before: PlaylistState) {
+        self.undo.push(before);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Returns the state before the latest edit, keeping `current` so the
+    /// undo can be redone. `None` when there is nothing to undo.
+    pub(crate) fn undo(
+        &mut self,
+        current: PlaylistState,
+    ) -> Option<PlaylistState> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Returns the state the latest undo went back from, keeping `current`
+    /// so it can be undone again. `None` when there is nothing to redo.
+    pub(crate) fn redo(
+        &mut self,
+        current: PlaylistState,
+    ) -> Option<PlaylistState> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(names: &[&str]) -> PlaylistState {
+        PlaylistState {
+            files: names
+                .iter()
+                .map(|name| RightPanelFile {
+                    path: name.into(),
+                    ..Default::default()
+                })
+                .collect(),
+            sort_column: SortColumn::Directory,
+            sort_order: SortOrder::Asc,
+            then_by: Vec::new(),
+            shuffled: false,
+            title: String::new(),
+            creator: String::new(),
+            annotation: String::new(),
+            missing_files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_history_undo_redo_and_bound() {
+        let mut history = History::default();
+        assert_eq!(history.undo(state(&[])), None);
+
+        history.record(state(&[]));
+        history.record(state(&["a"]));
+        assert_eq!(history.undo(state(&["a", "b"])), Some(state(&["a"])));
+        assert_eq!(history.undo(state(&["a"])), Some(state(&[])));
+        assert!(!history.can_undo());
+        assert_eq!(history.redo(state(&[])), Some(state(&["a"])));
+        assert!(history.can_redo());
+
+        // A new edit discards what could be redone
+        history.record(state(&["a"]));
+        assert!(!history.can_redo());
+
+        for i in 0..MAX_HISTORY + 10 {
+            history.record(state(&[&i.to_string()]));
+        }
+        let mut undone = 0;
+        while history.undo(state(&[])).is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_HISTORY);
+    }
+}
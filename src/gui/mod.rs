@@ -10,25 +10,44 @@
 //!     Message            — all user-action messages
 //!     TagTreeNode        — genre/creator/album/track hierarchy node
 //!     RightPanelFile     — a file entry in the playlist
+//!     RightPanelDrag     — a playlist row being dragged to a new place
+//!     RowMove            — up/down/top/bottom move of a playlist row
+//!     Playlist           — a named playlist shown as a right-panel tab
+//!     SavedPlaylist      — a playlist as persisted across restarts
 //!     LeftPanelSelectMode — directory / genre / creator selection mode
 //!     LeftPanelSortMode  — alphanumeric, modified-date, or file-count sort
 //!     SortColumn         — column key for right-panel sorting
+//!     PlaylistColumn     — a column of the right-panel playlist table
+//!     ColumnLayout       — visibility and width of a playlist column
 //!     SortOrder          — ascending or descending
+//!     SortKey            — a further sort column of a playlist and its order
 //!     TextSearchMode     — search mode for text filtering
+//!     subscription       — keyboard shortcuts and row-drag release
 //!     update             — message handler (pure state transition)
+//!     read_restored_files_task — read restored tracks' metadata in the
+//!                                background
 //!     view               — layout composer
 
+mod columns;
+mod history;
 mod left_panel;
+mod notifications;
 mod render_node;
 mod right_panel;
 mod state;
+mod status_bar;
+mod subscription;
 mod tantivy_search;
 mod update;
 mod view;
 
+pub use columns::{ColumnLayout, PlaylistColumn};
 pub use state::{
-    FileTreeApp, LeftPanelSelectMode, LeftPanelSortMode, Message,
-    RightPanelFile, SortColumn, SortOrder, TagTreeNode, TextSearchMode,
+    FileTreeApp, ImportedRightPanelPlaylist, LeftPanelSelectMode,
+    LeftPanelSortMode, Message, Playlist, RightPanelDrag, RightPanelFile,
+    RowMove, SavedPlaylist, SortColumn, SortKey, SortOrder, TagTreeNode,
+    TextSearchMode,
 };
-pub use update::update;
+pub use subscription::subscription;
+pub use update::{read_restored_files_task, update};
 pub use view::view;
@@ -0,0 +1,147 @@
+//! User-facing notifications for the Playlist UI.
+//!
+//! Side-effecting `update` arms (file dialogs, exports, imports, device
+//! sync, launching players, persisting settings) report their outcome here
+//! instead of discarding it. Each notification has a level, a message, and
+//! the local time it was raised; it is also written to the log at the
+//! matching level. The queue keeps the most recent `MAX_NOTIFICATIONS`
+//! entries, and the view shows them as a status bar under the panels.
+//!
+//! Public API:
+//!     NotificationLevel — info / warning / error
+//!     Notification      — one timestamped message
+//!     Notifications     — bounded queue of notifications, newest last
+
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+
+/// How many notifications are kept before the oldest are dropped.
+pub(crate) const MAX_NOTIFICATIONS: usize = 50;
+
+/// Severity of a notification, which decides its colour in the status bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NotificationLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A message raised by a side effect, with the time it was raised.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Notification {
+    /// Unique within the queue, used to dismiss the notification.
+    pub id: u64,
+    pub level: NotificationLevel,
+    pub message: String,
+    pub timestamp: DateTime<Local>,
+}
+
+/// Bounded queue of notifications, oldest first.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Notifications {
+    entries: VecDeque<Notification>,
+    next_id: u64,
+}
+
+impl Notifications {
+    /// Adds a notification, logging it and dropping the oldest entry once
+    /// more than `MAX_NOTIFICATIONS` are queued.
+    pub(crate) fn push(
+        &mut self,
+        level: NotificationLevel,
+        message: impl Into<String>,
+    ) {
+        let message = message.into();
+        match level {
+            NotificationLevel::Info => log::info!("{message}"),
+            NotificationLevel::Warning => log::warn!("{message}"),
+            NotificationLevel::Error => log::error!("{message}"),
+        }
+        self.entries.push_back(Notification {
+            id: self.next_id,
+            level,
+            message,
+            timestamp: Local::now(),
+        });
+        self.next_id += 1;
+        while self.entries.len() > MAX_NOTIFICATIONS {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Adds an informational notification.
+    pub(crate) fn info(&mut self, message: impl Into<String>) {
+        self.push(NotificationLevel::Info, message);
+    }
+
+    /// Adds a warning notification.
+    pub(crate) fn warning(&mut self, message: impl Into<String>) {
+        self.push(NotificationLevel::Warning, message);
+    }
+
+    /// Adds an error notification.
+    pub(crate) fn error(&mut self, message: impl Into<String>) {
+        self.push(NotificationLevel::Error, message);
+    }
+
+    /// Removes the notification with the given id, if it is still queued.
+    pub(crate) fn dismiss(&mut self, id: u64) {
+        self.entries.retain(|n| n.id != id);
+    }
+
+    /// Removes every notification.
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The most recent notification.
+    pub(crate) fn latest(&self) -> Option<&Notification> {
+        self.entries.back()
+    }
+
+    /// All queued notifications, oldest first.
+    pub(crate) fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &Notification> {
+        self.entries.iter()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notifications_are_bounded_and_dismissable() {
+        let mut notifications = Notifications::default();
+        for i in 0..MAX_NOTIFICATIONS + 5 {
+            notifications.info(format!("message {i}"));
+        }
+        notifications.error("failed");
+
+        assert_eq!(notifications.len(), MAX_NOTIFICATIONS);
+        assert_eq!(
+            notifications.iter().next().map(|n| n.message.as_str()),
+            Some("message 6")
+        );
+        let latest = notifications.latest().unwrap().clone();
+        assert_eq!(latest.level, NotificationLevel::Error);
+        assert_eq!(latest.message, "failed");
+
+        notifications.dismiss(latest.id);
+        assert_eq!(
+            notifications.latest().map(|n| n.message.as_str()),
+            Some(format!("message {}", MAX_NOTIFICATIONS + 4).as_str())
+        );
+        notifications.clear();
+        assert!(notifications.is_empty());
+    }
+}
@@ -1,46 +1,69 @@
 //! Right-panel UI construction for the Playlist UI.
 //!
-//! Builds the right sidebar containing the playlist table: a menu row
-//! (Shuffle, Export, Play, Clear), a sortable header row, and alternating
-//! file rows with context menus. Automatically shows/hides metadata columns
-//! (creator, album, title, genre, duration) based on which fields are
-//! populated in the current playlist.
+//! Builds the right sidebar containing the playlist table: a tab strip for
+//! switching, creating, renaming, duplicating, and deleting named playlists,
+//! a menu row (Undo, Redo, Shuffle, Manual order, Open, Export
+//! XSPF/JSPF/M3U8/PLS, relative-paths toggle, Play, Clear), bulk actions for
+//! the selected rows (shown while rows are selected), a collapsible
+//! "Columns" menu for choosing, ordering, and sizing the table's columns, a
+//! collapsible "Playlist Details" menu for the playlist's title, creator,
+//! and annotation, a collapsible "Player" menu of external player profiles,
+//! a collapsible "MPD" menu for sending the playlist to an MPD server, a
+//! collapsible editor for export path rewrite rules, a collapsible "Table
+//! Export" menu (CSV/TSV/JSON with chosen columns, printable HTML/Markdown
+//! listings), a collapsible "Copy to Folder" menu for syncing tracks to a
+//! device, the status of the latest playlist export, a notice listing
+//! playlist entries that could not be found on import, a notice with a
+//! "Remove Missing" button for tracks not found on restore (their rows are
+//! dimmed), a sortable header row, and alternating file rows with context
+//! menus and drag handles for reordering them by hand. Clicking a row
+//! selects it; shift-click selects a range and ctrl-click toggles a row.
 //!
 //! Public API:
 //!     create_right_panel — assemble the full right-panel Element
 
+use crate::fs::device_sync::SyncProgress;
+use crate::fs::listing_export::ListingFormat;
+use crate::fs::playlist_export::{ExportProgress, PlaylistFormat};
+use crate::fs::table_export::{TableColumn, TableFormat};
+use crate::gui::columns::{MAX_COLUMN_WIDTH, MIN_COLUMN_WIDTH};
 use crate::gui::view::{ItemListStyle, MenuStyle};
-use crate::gui::{FileTreeApp, Message, RightPanelFile, SortColumn, SortOrder};
-use crate::utils::format_duration;
+use crate::gui::{
+    FileTreeApp, Message, PlaylistColumn, RightPanelFile, RowMove, SortColumn,
+    SortOrder,
+};
+use crate::player::mpd::MpdAction;
+use crate::utils::{format_filtered_totals, format_totals};
 use iced::{
     Element, Length,
     widget::{Scrollable, Space},
 };
 
-#[derive(Default)]
-struct AudioColumnToggles {
-    show_creator: bool,
-    show_album: bool,
-    show_title: bool,
-    show_genre: bool,
-    show_duration: bool,
-}
+/// Width of the drag-handle column at the start of each playlist row.
+const DRAG_HANDLE_WIDTH: f32 = 20.0;
 
 /// Creates a widget displaying the total number of items and the sum of
-/// durations for all files shown in the right panel.
+/// durations for all files shown in the right panel. While a filter hides
+/// rows, the totals of the shown rows are given next to the playlist's.
 fn create_totals_display(
     displayed_files: &[RightPanelFile],
+    all_files: &[RightPanelFile],
     menu_style: MenuStyle,
 ) -> Element<'static, Message> {
-    let total_duration_ms: u64 =
-        displayed_files.iter().filter_map(|f| f.duration_ms).sum();
-    let row_count = displayed_files.len();
-    let total_duration_str = format!(
-        " {} Item{}, Time: {}",
-        row_count,
-        if row_count == 1 { "" } else { "s" },
-        format_duration(Some(total_duration_ms)),
-    );
+    let duration_ms = |files: &[RightPanelFile]| -> u64 {
+        files.iter().filter_map(|f| f.duration_ms).sum()
+    };
+    let totals = if displayed_files.len() == all_files.len() {
+        format_totals(all_files.len(), duration_ms(all_files))
+    } else {
+        format_filtered_totals(
+            displayed_files.len(),
+            duration_ms(displayed_files),
+            all_files.len(),
+            duration_ms(all_files),
+        )
+    };
+    let total_duration_str = format!(" {totals}");
     iced::widget::text(total_duration_str)
         .size(menu_style.text_size)
         .style(move |_theme| iced::widget::text::Style {
@@ -49,13 +72,42 @@ fn create_totals_display(
         .into()
 }
 
-/// Creates the right panel's menu row with "Shuffle", "Export to XSPF", and
-/// "Play in VLC" buttons, applying the specified text size, spacing, and color
-/// styling to each button.
+/// Creates the right panel's menu row with "Undo", "Redo", "Shuffle", a
+/// manual-order toggle, "Open Playlist", "Export to XSPF", "Export to JSPF",
+/// "Export to M3U8", "Export to PLS", a relative-paths toggle for exports, and
+/// "Play" buttons, applying the specified text size, spacing, and color
+/// styling to each button. Undo and Redo are disabled when the playlist has
+/// nothing to undo or redo.
 fn create_right_panel_menu_row(
     menu_style: MenuStyle,
+    manual_order: bool,
+    relative_paths: bool,
+    can_undo: bool,
+    can_redo: bool,
     extra_widget: Option<Element<'static, Message>>,
 ) -> Element<'static, Message> {
+    let undo_button = iced::widget::button(
+        iced::widget::text("Undo")
+            .width(Length::Shrink)
+            .size(menu_style.text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            }),
+    )
+    .on_press_maybe(can_undo.then_some(Message::UndoRightPanel))
+    .width(Length::Shrink);
+
+    let redo_button = iced::widget::button(
+        iced::widget::text("Redo")
+            .width(Length::Shrink)
+            .size(menu_style.text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            }),
+    )
+    .on_press_maybe(can_redo.then_some(Message::RedoRightPanel))
+    .width(Length::Shrink);
+
     let shuffle_button = iced::widget::button(
         iced::widget::text("Shuffle")
             .width(Length::Shrink)
@@ -67,6 +119,33 @@ fn create_right_panel_menu_row(
     .on_press(Message::ShuffleRightPanel)
     .width(Length::Shrink);
 
+    // Leaving manual order is done by clicking a column header
+    let manual_button = iced::widget::button(
+        iced::widget::text(if manual_order {
+            "[x] Manual order"
+        } else {
+            "[ ] Manual order"
+        })
+        .width(Length::Shrink)
+        .size(menu_style.text_size)
+        .style(move |_theme| iced::widget::text::Style {
+            color: Some(menu_style.text_color.into()),
+        }),
+    )
+    .on_press_maybe((!manual_order).then_some(Message::SortRightPanelManually))
+    .width(Length::Shrink);
+
+    let open_button = iced::widget::button(
+        iced::widget::text("Open Playlist")
+            .width(Length::Shrink)
+            .size(menu_style.text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            }),
+    )
+    .on_press(Message::ImportRightPanelPlaylist)
+    .width(Length::Shrink);
+
     let export_button = iced::widget::button(
         iced::widget::text("Export to XSPF")
             .width(Length::Shrink)
@@ -78,6 +157,54 @@ fn create_right_panel_menu_row(
     .on_press(Message::ExportRightPanelAsXspf)
     .width(Length::Shrink);
 
+    let export_jspf_button = iced::widget::button(
+        iced::widget::text("Export to JSPF")
+            .width(Length::Shrink)
+            .size(menu_style.text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            }),
+    )
+    .on_press(Message::ExportRightPanelAsJspf)
+    .width(Length::Shrink);
+
+    let export_m3u_button = iced::widget::button(
+        iced::widget::text("Export to M3U8")
+            .width(Length::Shrink)
+            .size(menu_style.text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            }),
+    )
+    .on_press(Message::ExportRightPanelAsM3u)
+    .width(Length::Shrink);
+
+    let export_pls_button = iced::widget::button(
+        iced::widget::text("Export to PLS")
+            .width(Length::Shrink)
+            .size(menu_style.text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            }),
+    )
+    .on_press(Message::ExportRightPanelAsPls)
+    .width(Length::Shrink);
+
+    let relative_paths_button = iced::widget::button(
+        iced::widget::text(if relative_paths {
+            "[x] Relative paths"
+        } else {
+            "[ ] Relative paths"
+        })
+        .width(Length::Shrink)
+        .size(menu_style.text_size)
+        .style(move |_theme| iced::widget::text::Style {
+            color: Some(menu_style.text_color.into()),
+        }),
+    )
+    .on_press(Message::ToggleExportRelativePaths)
+    .width(Length::Shrink);
+
     let play_button = iced::widget::button(
         iced::widget::text("Play")
             .width(Length::Shrink)
@@ -86,7 +213,7 @@ fn create_right_panel_menu_row(
                 color: Some(menu_style.text_color.into()),
             }),
     )
-    .on_press(Message::ExportAndPlayRightPanelAsXspf)
+    .on_press(Message::PlayRightPanel)
     .width(Length::Shrink);
 
     let clear_button = iced::widget::button(
@@ -101,8 +228,16 @@ fn create_right_panel_menu_row(
     .width(Length::Shrink);
 
     let mut row = iced::widget::Row::new()
+        .push(undo_button)
+        .push(redo_button)
         .push(shuffle_button)
+        .push(manual_button)
+        .push(open_button)
         .push(export_button)
+        .push(export_jspf_button)
+        .push(export_m3u_button)
+        .push(export_pls_button)
+        .push(relative_paths_button)
         .push(play_button)
         .push(Space::with_width(Length::Fixed((menu_style.spacing * 2) as f32)))
         .push(clear_button)
@@ -115,177 +250,915 @@ fn create_right_panel_menu_row(
     row.into()
 }
 
-/// Builds the header row for the right panel table, including sortable column
-/// buttons for directory, file, and optionally creator, album, title, and
-/// genre. Column spacing and text size are configurable via parameters.
-fn create_right_panel_header_row(
+/// Creates the playlist tab strip: one button per named playlist, the
+/// active one highlighted, followed by a name input and buttons to rename,
+/// create, duplicate, and delete playlists.
+fn create_playlist_tabs(
     app: &FileTreeApp,
-    audio_column_toggles: AudioColumnToggles,
-    column_spacing: u16,
-    header_text_size: u16,
-    header_text_color: [f32; 4],
-) -> iced::widget::Row<'static, Message> {
-    // Sorting arrows
-    let dir_arrow = if app.right_panel_sort_column == SortColumn::Directory {
-        match app.right_panel_sort_order {
-            SortOrder::Desc => " ↑",
-            SortOrder::Asc => " ↓",
-        }
-    } else {
-        ""
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let mut tabs = iced::widget::Row::new().spacing(menu_style.spacing);
+    for (index, playlist) in app.playlists.iter().enumerate() {
+        let style = if index == app.active_playlist {
+            iced::widget::button::primary
+        } else {
+            iced::widget::button::secondary
+        };
+        tabs = tabs.push(
+            iced::widget::button(
+                iced::widget::text(format!(
+                    "{} ({})",
+                    playlist.name,
+                    playlist.files.len()
+                ))
+                .size(menu_style.text_size),
+            )
+            .style(style)
+            .on_press(Message::SelectPlaylist(index)),
+        );
+    }
+
+    let button = |label: &'static str, message: Message| {
+        iced::widget::button(iced::widget::text(label).size(row_text_size))
+            .on_press(message)
     };
-    let file_arrow = if app.right_panel_sort_column == SortColumn::File {
-        match app.right_panel_sort_order {
-            SortOrder::Desc => " ↑",
-            SortOrder::Asc => " ↓",
-        }
-    } else {
-        ""
+    let controls = iced::widget::Row::new()
+        .push(
+            iced::widget::text_input("Playlist name", &app.playlist_name_draft)
+                .on_input(Message::PlaylistNameDraftChanged)
+                .on_submit(Message::RenamePlaylist)
+                .size(row_text_size)
+                .width(Length::Fill),
+        )
+        .push(button("Rename", Message::RenamePlaylist))
+        .push(button("New", Message::NewPlaylist))
+        .push(button("Duplicate", Message::DuplicatePlaylist))
+        .push(button("Delete", Message::DeletePlaylist))
+        .spacing(menu_style.spacing);
+
+    iced::widget::column![
+        Scrollable::new(tabs).direction(
+            iced::widget::scrollable::Direction::Horizontal(
+                iced::widget::scrollable::Scrollbar::new(),
+            )
+        ),
+        controls
+    ]
+    .spacing(menu_style.spacing)
+    .into()
+}
+
+/// Creates the bulk-action row for the selected tracks, if any: the number
+/// selected, buttons to remove them, move them to the top or bottom, keep
+/// only them, or clear the selection, and one button per other playlist to
+/// copy them there.
+fn create_selection_menu(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Option<Element<'static, Message>> {
+    if app.right_panel_selection.is_empty() {
+        return None;
+    }
+    let button = |label: String, message: Message| {
+        iced::widget::button(iced::widget::text(label).size(row_text_size))
+            .on_press(message)
     };
-    let creator_arrow = if audio_column_toggles.show_creator
-        && app.right_panel_sort_column == SortColumn::Creator
-    {
-        match app.right_panel_sort_order {
-            SortOrder::Desc => " ↑",
-            SortOrder::Asc => " ↓",
+    let actions = iced::widget::Row::new()
+        .push(
+            iced::widget::text(format!(
+                "{} selected",
+                app.right_panel_selection.len()
+            ))
+            .size(row_text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            }),
+        )
+        .push(button("Remove".into(), Message::RemoveSelectedFromRightPanel))
+        .push(button(
+            "Move to Top".into(),
+            Message::MoveSelectedRightPanelFiles(RowMove::ToTop),
+        ))
+        .push(button(
+            "Move to Bottom".into(),
+            Message::MoveSelectedRightPanelFiles(RowMove::ToBottom),
+        ))
+        .push(button(
+            "Keep Only Selected".into(),
+            Message::KeepOnlySelectedInRightPanel,
+        ))
+        .push(button("Select None".into(), Message::ClearRightPanelSelection))
+        .spacing(menu_style.spacing);
+
+    let mut copy_targets = iced::widget::Row::new().spacing(menu_style.spacing);
+    for (index, playlist) in app.playlists.iter().enumerate() {
+        if index != app.active_playlist {
+            copy_targets = copy_targets.push(button(
+                format!("Copy to {}", playlist.name),
+                Message::CopySelectedToPlaylist(index),
+            ));
         }
-    } else {
-        ""
+    }
+
+    Some(
+        iced::widget::column![
+            actions,
+            Scrollable::new(copy_targets).direction(
+                iced::widget::scrollable::Direction::Horizontal(
+                    iced::widget::scrollable::Scrollbar::new(),
+                )
+            )
+        ]
+        .spacing(menu_style.spacing)
+        .into(),
+    )
+}
+
+/// Creates the row filtering the playlist: a text input narrowing the rows
+/// shown to those matching it in any metadata field, a clear button (✕)
+/// while it holds text, and "Select All", which selects every shown row.
+fn create_right_panel_filter_row(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    has_rows: bool,
+) -> Element<'static, Message> {
+    let filter_input =
+        iced::widget::text_input("Filter playlist...", &app.right_panel_filter)
+            .on_input(Message::RightPanelFilterChanged)
+            .size(menu_style.text_size);
+    let clear_button = (!app.right_panel_filter.is_empty()).then(|| {
+        iced::widget::button(iced::widget::text("✕").size(menu_style.text_size))
+            .on_press(Message::RightPanelFilterCleared)
+    });
+    let select_all_button = iced::widget::button(
+        iced::widget::text("Select All").size(menu_style.text_size),
+    )
+    .on_press_maybe(has_rows.then_some(Message::SelectAllRightPanelFiles));
+    iced::widget::Row::new()
+        .push(filter_input)
+        .push_maybe(clear_button)
+        .push(select_all_button)
+        .spacing(menu_style.spacing)
+        .into()
+}
+
+/// Creates the collapsible "Columns" menu: a header button that toggles it,
+/// then one line per playlist column in table order, with a visibility
+/// toggle, buttons that move it left or right, and buttons that narrow or
+/// widen it, followed by a button restoring the default layout.
+fn create_columns_menu(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let header = iced::widget::button(
+        iced::widget::text(if app.columns_menu_expanded {
+            "▼ Columns"
+        } else {
+            "▶ Columns"
+        })
+        .size(menu_style.text_size)
+        .style(move |_theme| iced::widget::text::Style {
+            color: Some(menu_style.text_color.into()),
+        }),
+    )
+    .on_press(Message::ToggleColumnsMenu);
+
+    if !app.columns_menu_expanded {
+        return iced::widget::column![header].into();
+    }
+
+    let button = |label: &'static str, message: Option<Message>| {
+        iced::widget::button(iced::widget::text(label).size(row_text_size))
+            .on_press_maybe(message)
     };
-    let album_arrow = if audio_column_toggles.show_album
-        && app.right_panel_sort_column == SortColumn::Album
-    {
-        match app.right_panel_sort_order {
-            SortOrder::Desc => " ↑",
-            SortOrder::Asc => " ↓",
-        }
-    } else {
-        ""
+    let last = app.playlist_columns.len() - 1;
+    let mut col = iced::widget::Column::new().push(header);
+    for (index, entry) in app.playlist_columns.iter().enumerate() {
+        let column = entry.column;
+        let mark = if entry.visible { "[x]" } else { "[ ]" };
+        col = col.push(
+            iced::widget::Row::new()
+                .push(
+                    iced::widget::button(
+                        iced::widget::text(format!(
+                            "{mark} {}",
+                            column.label()
+                        ))
+                        .size(row_text_size),
+                    )
+                    .on_press(Message::TogglePlaylistColumn(column))
+                    .width(Length::Fixed(160.0)),
+                )
+                .push(button(
+                    "◀",
+                    (index > 0)
+                        .then_some(Message::MovePlaylistColumnLeft(column)),
+                ))
+                .push(button(
+                    "▶",
+                    (index < last)
+                        .then_some(Message::MovePlaylistColumnRight(column)),
+                ))
+                .push(button(
+                    "−",
+                    (entry.width > MIN_COLUMN_WIDTH)
+                        .then_some(Message::NarrowPlaylistColumn(column)),
+                ))
+                .push(
+                    iced::widget::text(format!("Width {}", entry.width))
+                        .size(row_text_size),
+                )
+                .push(button(
+                    "+",
+                    (entry.width < MAX_COLUMN_WIDTH)
+                        .then_some(Message::WidenPlaylistColumn(column)),
+                ))
+                .spacing(menu_style.spacing)
+                .align_y(iced::Alignment::Center),
+        );
+    }
+    col.push(button("Reset Columns", Some(Message::ResetPlaylistColumns)))
+        .spacing(menu_style.spacing / 2)
+        .into()
+}
+
+/// Creates the collapsible "Playlist Details" menu: a header button that
+/// toggles it, naming the playlist once it has a title, and inputs for the
+/// title, creator, and annotation written by XSPF exports. Enter or closing
+/// the menu saves what was typed.
+fn create_playlist_info_menu(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let arrow = if app.playlist_info_menu_expanded { "▼" } else { "▶" };
+    let header_label = match app.playlist().title.trim() {
+        "" => format!("{arrow} Playlist Details"),
+        title => format!("{arrow} Playlist Details: {title}"),
     };
-    let title_arrow = if audio_column_toggles.show_title
-        && app.right_panel_sort_column == SortColumn::Title
-    {
-        match app.right_panel_sort_order {
-            SortOrder::Desc => " ↑",
-            SortOrder::Asc => " ↓",
-        }
-    } else {
-        ""
+    let header = iced::widget::button(
+        iced::widget::text(header_label).size(menu_style.text_size).style(
+            move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            },
+        ),
+    )
+    .on_press(Message::TogglePlaylistInfoMenu);
+
+    if !app.playlist_info_menu_expanded {
+        return iced::widget::column![header].into();
+    }
+
+    let fields = iced::widget::Row::new()
+        .push(
+            iced::widget::text_input("Title", &app.playlist_title_draft)
+                .on_input(Message::PlaylistTitleChanged)
+                .on_submit(Message::SavePlaylistDetails)
+                .size(row_text_size)
+                .width(Length::FillPortion(2)),
+        )
+        .push(
+            iced::widget::text_input("Creator", &app.playlist_creator_draft)
+                .on_input(Message::PlaylistCreatorChanged)
+                .on_submit(Message::SavePlaylistDetails)
+                .size(row_text_size)
+                .width(Length::FillPortion(1)),
+        )
+        .spacing(menu_style.spacing);
+    let annotation =
+        iced::widget::text_input("Annotation", &app.playlist_annotation_draft)
+            .on_input(Message::PlaylistAnnotationChanged)
+            .on_submit(Message::SavePlaylistDetails)
+            .size(row_text_size)
+            .width(Length::Fill);
+
+    iced::widget::column![header, fields, annotation].into()
+}
+
+/// Creates the collapsible player menu: a header button that toggles it,
+/// naming the player "Play" starts, one row per profile (select, remove),
+/// and inputs for adding a profile or replacing one with the same name.
+fn create_player_menu(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let arrow = if app.player_menu_expanded { "▼" } else { "▶" };
+    let header_label = match app.selected_player_profile() {
+        Some(profile) => format!("{arrow} Player: {}", profile.name),
+        None => format!("{arrow} Player: None"),
     };
-    let genre_arrow = if audio_column_toggles.show_genre
-        && app.right_panel_sort_column == SortColumn::Genre
-    {
-        match app.right_panel_sort_order {
-            SortOrder::Desc => " ↑",
-            SortOrder::Asc => " ↓",
-        }
-    } else {
-        ""
+    let header = iced::widget::button(
+        iced::widget::text(header_label).size(menu_style.text_size).style(
+            move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            },
+        ),
+    )
+    .on_press(Message::TogglePlayerMenu);
+
+    if !app.player_menu_expanded {
+        return iced::widget::column![header].into();
+    }
+
+    let mut menu = iced::widget::Column::new();
+    for profile in &app.player_profiles {
+        let mark =
+            if profile.name == app.selected_player { "(x)" } else { "( )" };
+        let label = format!(
+            "{mark} {}: {} {}  [{}]",
+            profile.name,
+            profile.executable,
+            profile.args,
+            profile.format.name()
+        );
+        menu = menu.push(
+            iced::widget::Row::new()
+                .push(
+                    iced::widget::button(
+                        iced::widget::text(label).size(row_text_size),
+                    )
+                    .on_press(
+                        Message::SelectPlayerProfile(profile.name.clone()),
+                    ),
+                )
+                .push(
+                    iced::widget::button(
+                        iced::widget::text("X").size(row_text_size),
+                    )
+                    .on_press(
+                        Message::RemovePlayerProfile(profile.name.clone()),
+                    ),
+                )
+                .spacing(menu_style.spacing),
+        );
+    }
+
+    let draft = &app.player_draft;
+    let mut editor = iced::widget::Row::new()
+        .push(
+            iced::widget::text_input("Name", &draft.name)
+                .on_input(Message::PlayerNameChanged)
+                .size(row_text_size)
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            iced::widget::text_input("Executable", &draft.executable)
+                .on_input(Message::PlayerExecutableChanged)
+                .size(row_text_size)
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            iced::widget::text_input(
+                "Arguments ({playlist}, {files})",
+                &draft.args,
+            )
+            .on_input(Message::PlayerArgsChanged)
+            .size(row_text_size)
+            .width(Length::FillPortion(2)),
+        )
+        .spacing(menu_style.spacing);
+    for format in PlaylistFormat::ALL {
+        let mark = if draft.format == format { "(x)" } else { "( )" };
+        editor = editor.push(
+            iced::widget::button(
+                iced::widget::text(format!("{mark} {}", format.name()))
+                    .size(row_text_size),
+            )
+            .on_press(Message::SelectPlayerFormat(format)),
+        );
+    }
+    editor = editor.push(
+        iced::widget::button(
+            iced::widget::text("Add Player").size(row_text_size),
+        )
+        .on_press(Message::AddPlayerProfile),
+    );
+
+    iced::widget::column![header, menu.push(editor)].into()
+}
+
+/// Creates the collapsible "MPD" menu: a header button that toggles it,
+/// naming the server, inputs for the server address, password, and the
+/// local directory matching MPD's music directory, and buttons that replace
+/// or append to the server's queue or save a stored playlist. The buttons
+/// are disabled while a request is running.
+fn create_mpd_menu(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let arrow = if app.mpd_menu_expanded { "▼" } else { "▶" };
+    let header = iced::widget::button(
+        iced::widget::text(format!(
+            "{arrow} MPD: {}:{}",
+            app.mpd_settings.host, app.mpd_settings.port
+        ))
+        .size(menu_style.text_size)
+        .style(move |_theme| iced::widget::text::Style {
+            color: Some(menu_style.text_color.into()),
+        }),
+    )
+    .on_press(Message::ToggleMpdMenu);
+
+    if !app.mpd_menu_expanded {
+        return iced::widget::column![header].into();
+    }
+
+    let settings = &app.mpd_settings;
+    let server = iced::widget::Row::new()
+        .push(
+            iced::widget::text_input("Host", &settings.host)
+                .on_input(Message::MpdHostChanged)
+                .size(row_text_size)
+                .width(Length::FillPortion(2)),
+        )
+        .push(
+            iced::widget::text_input("Port", &app.mpd_port_input)
+                .on_input(Message::MpdPortChanged)
+                .size(row_text_size)
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            iced::widget::text_input("Password", &settings.password)
+                .on_input(Message::MpdPasswordChanged)
+                .secure(true)
+                .size(row_text_size)
+                .width(Length::FillPortion(2)),
+        )
+        .spacing(menu_style.spacing);
+    let music_directory = iced::widget::text_input(
+        "Local folder matching MPD's music directory",
+        &settings.music_directory,
+    )
+    .on_input(Message::MpdMusicDirectoryChanged)
+    .size(row_text_size)
+    .width(Length::Fill);
+
+    let action_button = |label: &str, action: Option<MpdAction>| {
+        iced::widget::button(
+            iced::widget::text(label.to_string()).size(row_text_size),
+        )
+        .on_press_maybe(
+            action.filter(|_| !app.mpd_busy).map(Message::SendToMpd),
+        )
     };
-    let duration_arrow = if app.right_panel_sort_column == SortColumn::Duration
-    {
-        match app.right_panel_sort_order {
-            SortOrder::Desc => " ↑",
-            SortOrder::Asc => " ↓",
-        }
-    } else {
-        ""
+    let playlist_name = app.mpd_playlist_name.trim();
+    let actions = iced::widget::Row::new()
+        .push(action_button("Replace Queue", Some(MpdAction::ReplaceQueue)))
+        .push(action_button("Append to Queue", Some(MpdAction::AppendToQueue)))
+        .push(
+            iced::widget::text_input("Playlist name", &app.mpd_playlist_name)
+                .on_input(Message::MpdPlaylistNameChanged)
+                .size(row_text_size)
+                .width(Length::Fill),
+        )
+        .push(action_button(
+            "Save as MPD Playlist",
+            (!playlist_name.is_empty())
+                .then(|| MpdAction::SavePlaylist(playlist_name.to_string())),
+        ))
+        .spacing(menu_style.spacing);
+
+    iced::widget::column![header, server, music_directory, actions].into()
+}
+
+/// Creates the collapsible path rewrite menu: a header button that toggles
+/// it, one row per saved rule (select for export, remove), a "None" choice,
+/// and inputs for adding a rule. The header names the rule selected for
+/// export, if any.
+fn create_path_rewrite_menu(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let selected = app.export_options.rewrite_rule.as_ref().map(|r| &r.name);
+    let header_label = match (app.path_rewrite_menu_expanded, selected) {
+        (true, Some(name)) => format!("▼ Path Rewrite: {name}"),
+        (true, None) => "▼ Path Rewrite: None".to_string(),
+        (false, Some(name)) => format!("▶ Path Rewrite: {name}"),
+        (false, None) => "▶ Path Rewrite: None".to_string(),
     };
+    let header = iced::widget::button(
+        iced::widget::text(header_label).size(menu_style.text_size).style(
+            move |_theme| iced::widget::text::Style {
+                color: Some(menu_style.text_color.into()),
+            },
+        ),
+    )
+    .on_press(Message::TogglePathRewriteMenu);
 
-    let mut header_row = iced::widget::Row::new()
+    if !app.path_rewrite_menu_expanded {
+        return iced::widget::column![header].into();
+    }
+
+    let mut menu = iced::widget::Column::new().push(
+        iced::widget::button(
+            iced::widget::text(if selected.is_none() {
+                "(x) None"
+            } else {
+                "( ) None"
+            })
+            .size(row_text_size),
+        )
+        .on_press(Message::SelectPathRewriteRule(None)),
+    );
+    for rule in &app.path_rewrite_rules {
+        let mark = if selected == Some(&rule.name) { "(x)" } else { "( )" };
+        let separators = if rule.windows_separators { "  [\\]" } else { "" };
+        let label = format!(
+            "{mark} {}: {} → {}{separators}",
+            rule.name, rule.source_prefix, rule.target_prefix
+        );
+        menu = menu.push(
+            iced::widget::Row::new()
+                .push(
+                    iced::widget::button(
+                        iced::widget::text(label).size(row_text_size),
+                    )
+                    .on_press(
+                        Message::SelectPathRewriteRule(Some(rule.name.clone())),
+                    ),
+                )
+                .push(
+                    iced::widget::button(
+                        iced::widget::text("X").size(row_text_size),
+                    )
+                    .on_press(
+                        Message::RemovePathRewriteRule(rule.name.clone()),
+                    ),
+                )
+                .spacing(menu_style.spacing),
+        );
+    }
+
+    let draft = &app.path_rewrite_draft;
+    let editor = iced::widget::Row::new()
+        .push(
+            iced::widget::text_input("Name", &draft.name)
+                .on_input(Message::PathRewriteNameChanged)
+                .size(row_text_size)
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            iced::widget::text_input("Source prefix", &draft.source_prefix)
+                .on_input(Message::PathRewriteSourceChanged)
+                .size(row_text_size)
+                .width(Length::FillPortion(2)),
+        )
+        .push(
+            iced::widget::text_input("Target prefix", &draft.target_prefix)
+                .on_input(Message::PathRewriteTargetChanged)
+                .size(row_text_size)
+                .width(Length::FillPortion(2)),
+        )
         .push(
             iced::widget::button(
-                iced::widget::text(format!("Directory{dir_arrow}"))
-                    .width(Length::FillPortion(1))
-                    .size(header_text_size)
-                    .style(move |_theme| iced::widget::text::Style {
-                        color: Some(header_text_color.into()),
-                    }),
+                iced::widget::text(if draft.windows_separators {
+                    "[x] \\ separators"
+                } else {
+                    "[ ] \\ separators"
+                })
+                .size(row_text_size),
             )
-            .on_press(Message::SortRightPanelByDirectory)
-            .width(Length::FillPortion(1)),
+            .on_press(Message::TogglePathRewriteWindowsSeparators),
         )
         .push(
             iced::widget::button(
-                iced::widget::text(format!("File{file_arrow}"))
-                    .width(Length::FillPortion(1))
-                    .size(header_text_size)
-                    .style(move |_theme| iced::widget::text::Style {
-                        color: Some(header_text_color.into()),
-                    }),
+                iced::widget::text("Add Rule").size(row_text_size),
             )
-            .on_press(Message::SortRightPanelByFile)
-            .width(Length::FillPortion(1)),
-        );
+            .on_press(Message::AddPathRewriteRule),
+        )
+        .spacing(menu_style.spacing);
 
-    if audio_column_toggles.show_creator {
-        header_row = header_row.push(
+    iced::widget::column![header, menu.push(editor)].into()
+}
+
+/// Creates the collapsible "Table Export" menu: a header button that toggles
+/// it, a checkbox-style toggle per column, one export button per format, and
+/// buttons for printable HTML and Markdown track listings. The table export
+/// buttons are disabled while no column is selected.
+fn create_table_export_menu(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let header = iced::widget::button(
+        iced::widget::text(if app.table_export_menu_expanded {
+            "▼ Table Export"
+        } else {
+            "▶ Table Export"
+        })
+        .size(menu_style.text_size)
+        .style(move |_theme| iced::widget::text::Style {
+            color: Some(menu_style.text_color.into()),
+        }),
+    )
+    .on_press(Message::ToggleTableExportMenu);
+
+    if !app.table_export_menu_expanded {
+        return iced::widget::column![header].into();
+    }
+
+    let mut columns = iced::widget::Row::new().spacing(menu_style.spacing);
+    for column in TableColumn::ALL {
+        let mark = if app.table_export_columns.contains(&column) {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+        columns = columns.push(
             iced::widget::button(
-                iced::widget::text(format!("Musician{creator_arrow}"))
-                    .width(Length::FillPortion(1))
-                    .size(header_text_size)
-                    .style(move |_theme| iced::widget::text::Style {
-                        color: Some(header_text_color.into()),
-                    }),
+                iced::widget::text(format!("{mark} {}", column.label()))
+                    .size(row_text_size),
             )
-            .on_press(Message::SortRightPanelByCreator)
-            .width(Length::FillPortion(1)),
+            .on_press(Message::ToggleTableExportColumn(column)),
         );
     }
-    if audio_column_toggles.show_album {
-        header_row = header_row.push(
+
+    let mut formats = iced::widget::Row::new().spacing(menu_style.spacing);
+    for format in [TableFormat::Csv, TableFormat::Tsv, TableFormat::Json] {
+        let button = iced::widget::button(
+            iced::widget::text(format!("Export {}", format.name()))
+                .size(row_text_size),
+        )
+        .on_press_maybe(
+            (!app.table_export_columns.is_empty())
+                .then_some(Message::ExportRightPanelAsTable(format)),
+        );
+        formats = formats.push(button);
+    }
+
+    let mut listings = iced::widget::Row::new()
+        .push(iced::widget::text("Printable listing:").size(row_text_size))
+        .spacing(menu_style.spacing)
+        .align_y(iced::Alignment::Center);
+    for format in [ListingFormat::Html, ListingFormat::Markdown] {
+        listings = listings.push(
             iced::widget::button(
-                iced::widget::text(format!("Album{album_arrow}"))
-                    .width(Length::FillPortion(1))
-                    .size(header_text_size)
-                    .style(move |_theme| iced::widget::text::Style {
-                        color: Some(header_text_color.into()),
-                    }),
+                iced::widget::text(format!("Export {}", format.name()))
+                    .size(row_text_size),
             )
-            .on_press(Message::SortRightPanelByAlbum)
-            .width(Length::FillPortion(1)),
+            .on_press(Message::ExportRightPanelAsListing(format)),
         );
     }
-    if audio_column_toggles.show_title {
-        header_row = header_row.push(
+
+    iced::widget::column![header, columns, formats, listings].into()
+}
+
+/// Creates the collapsible "Copy to Folder" menu: a header button that
+/// toggles it, the filename template input, a button that picks the
+/// destination folder and starts the copy (replaced by "Cancel" while a copy
+/// runs), and a status line for the latest copy, shown even when collapsed.
+fn create_device_sync_menu(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let header = iced::widget::button(
+        iced::widget::text(if app.sync_menu_expanded {
+            "▼ Copy to Folder"
+        } else {
+            "▶ Copy to Folder"
+        })
+        .size(menu_style.text_size)
+        .style(move |_theme| iced::widget::text::Style {
+            color: Some(menu_style.text_color.into()),
+        }),
+    )
+    .on_press(Message::ToggleDeviceSyncMenu);
+
+    let mut col = iced::widget::Column::new().push(header);
+    if app.sync_menu_expanded {
+        let action = if app.sync_cancel.is_some() {
             iced::widget::button(
-                iced::widget::text(format!("Title{title_arrow}"))
-                    .width(Length::FillPortion(1))
-                    .size(header_text_size)
-                    .style(move |_theme| iced::widget::text::Style {
-                        color: Some(header_text_color.into()),
-                    }),
+                iced::widget::text("Cancel").size(row_text_size),
             )
-            .on_press(Message::SortRightPanelByTitle)
-            .width(Length::FillPortion(1)),
+            .on_press(Message::CancelDeviceSync)
+        } else {
+            iced::widget::button(
+                iced::widget::text("Choose Folder and Copy")
+                    .size(row_text_size),
+            )
+            .on_press(Message::SyncRightPanelToFolder)
+        };
+        col = col.push(
+            iced::widget::Row::new()
+                .push(iced::widget::text("Template:").size(row_text_size))
+                .push(
+                    iced::widget::text_input(
+                        "{index:03} - {creator} - {title}.{ext}",
+                        &app.sync_template,
+                    )
+                    .on_input(Message::SyncTemplateChanged)
+                    .size(row_text_size)
+                    .width(Length::Fill),
+                )
+                .push(action)
+                .spacing(menu_style.spacing)
+                .align_y(iced::Alignment::Center),
         );
     }
-    if audio_column_toggles.show_genre {
-        header_row = header_row.push(
+
+    let status = match &app.sync_progress {
+        None => return col.into(),
+        Some(SyncProgress::Running { done, total }) => {
+            (format!(" Copying tracks… {done}/{total}"), menu_style.text_color)
+        },
+        Some(SyncProgress::Finished(summary)) => {
+            let outcome = if summary.cancelled {
+                "Copy cancelled"
+            } else {
+                "Copy finished"
+            };
+            let mut status = format!(
+                " {outcome}: {} copied, {} already up to date",
+                summary.copied, summary.skipped
+            );
+            if !summary.failed.is_empty() {
+                status.push_str(&format!(", {} failed", summary.failed.len()));
+            }
+            if let Some(playlist) = &summary.playlist {
+                status.push_str(&format!(" ({})", playlist.display()));
+            }
+            let color = if summary.failed.is_empty() {
+                menu_style.text_color
+            } else {
+                [1.0, 0.6, 0.2, 1.0]
+            };
+            (status, color)
+        },
+        Some(SyncProgress::Failed(message)) => {
+            (format!(" {message}"), [1.0, 0.4, 0.4, 1.0])
+        },
+    };
+    col.push(iced::widget::text(status.0).size(menu_style.text_size).style(
+        move |_theme| iced::widget::text::Style {
+            color: Some(status.1.into()),
+        },
+    ))
+    .into()
+}
+
+/// Creates a status line for the latest playlist export: the number of tracks
+/// written while it runs, then the saved path or the error message.
+fn create_export_status_display(
+    progress: &ExportProgress,
+    menu_style: MenuStyle,
+) -> Element<'static, Message> {
+    let (status, color) = match progress {
+        ExportProgress::Running { done, total } => (
+            format!(" Exporting playlist… {done}/{total}"),
+            menu_style.text_color,
+        ),
+        ExportProgress::Finished(path) => (
+            format!(" Exported playlist to {}", path.display()),
+            menu_style.text_color,
+        ),
+        ExportProgress::Failed(message) => {
+            (format!(" {message}"), [1.0, 0.4, 0.4, 1.0])
+        },
+    };
+    iced::widget::text(status)
+        .size(menu_style.text_size)
+        .style(move |_theme| iced::widget::text::Style {
+            color: Some(color.into()),
+        })
+        .into()
+}
+
+/// Creates a notice listing the playlist entries that could not be found
+/// when the playlist was imported, with a button to dismiss
+/// it. Only the first few paths are listed; the rest are summarized by count.
+fn create_missing_files_display(
+    missing_files: &[std::path::PathBuf],
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    const MAX_LISTED: usize = 5;
+    let warning_color = [1.0, 0.6, 0.2, 1.0];
+
+    let count = missing_files.len();
+    let header = iced::widget::Row::new()
+        .push(
+            iced::widget::text(format!(
+                " {} playlist entr{} not found",
+                count,
+                if count == 1 { "y" } else { "ies" },
+            ))
+            .size(menu_style.text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(warning_color.into()),
+            }),
+        )
+        .push(
             iced::widget::button(
-                iced::widget::text(format!("Genre{genre_arrow}"))
-                    .width(Length::FillPortion(1))
-                    .size(header_text_size)
-                    .style(move |_theme| iced::widget::text::Style {
-                        color: Some(header_text_color.into()),
-                    }),
+                iced::widget::text("Dismiss").size(row_text_size).style(
+                    move |_theme| iced::widget::text::Style {
+                        color: Some(menu_style.text_color.into()),
+                    },
+                ),
             )
-            .on_press(Message::SortRightPanelByGenre)
-            .width(Length::FillPortion(1)),
+            .on_press(Message::DismissMissingPlaylistFiles),
+        )
+        .spacing(menu_style.spacing);
+
+    let mut col = iced::widget::Column::new().push(header);
+    for path in missing_files.iter().take(MAX_LISTED) {
+        col = col.push(
+            iced::widget::text(format!("   {}", path.display()))
+                .size(row_text_size),
         );
     }
-    if audio_column_toggles.show_duration {
+    if count > MAX_LISTED {
+        col = col.push(
+            iced::widget::text(format!("   … and {} more", count - MAX_LISTED))
+                .size(row_text_size),
+        );
+    }
+    col.into()
+}
+
+/// Creates a notice counting the playlist's tracks that were not found when
+/// it was restored, which are shown dimmed, with a button to remove them.
+fn create_missing_rows_display(
+    count: usize,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    let warning_color = [1.0, 0.6, 0.2, 1.0];
+    iced::widget::Row::new()
+        .push(
+            iced::widget::text(format!(
+                " {} track{} not found (shown dimmed)",
+                count,
+                if count == 1 { "" } else { "s" },
+            ))
+            .size(menu_style.text_size)
+            .style(move |_theme| iced::widget::text::Style {
+                color: Some(warning_color.into()),
+            }),
+        )
+        .push(
+            iced::widget::button(
+                iced::widget::text("Remove Missing").size(row_text_size).style(
+                    move |_theme| iced::widget::text::Style {
+                        color: Some(menu_style.text_color.into()),
+                    },
+                ),
+            )
+            .on_press(Message::RemoveMissingFromRightPanel),
+        )
+        .spacing(menu_style.spacing)
+        .into()
+}
+
+/// Builds the header row for the right panel table: one sortable button per
+/// visible column of the user's column layout, in its order and with its
+/// width. Each sort key's column is marked with an arrow and, when the
+/// playlist has further sort keys, the key's priority. Shift-clicking a
+/// header adds it as a further sort key. Column spacing and text size are
+/// configurable via parameters.
+fn create_right_panel_header_row(
+    app: &FileTreeApp,
+    column_spacing: u16,
+    header_text_size: u16,
+    header_text_color: [f32; 4],
+) -> iced::widget::Row<'static, Message> {
+    let mut header_row = iced::widget::Row::new()
+        .push(Space::with_width(Length::Fixed(DRAG_HANDLE_WIDTH)));
+    let sort_keys = app.playlist().sort_keys();
+    for entry in app.playlist_columns.iter().filter(|e| e.visible) {
+        let sort_column = entry.column.sort_column();
+        let marker =
+            match sort_keys.iter().position(|k| k.column == sort_column) {
+                Some(index) => {
+                    let arrow = match sort_keys[index].order {
+                        SortOrder::Desc => "↑",
+                        SortOrder::Asc => "↓",
+                    };
+                    if sort_keys.len() > 1 {
+                        format!(" {arrow}{}", index + 1)
+                    } else {
+                        format!(" {arrow}")
+                    }
+                },
+                None => String::new(),
+            };
         header_row = header_row.push(
             iced::widget::button(
-                iced::widget::text(format!("Duration{duration_arrow}"))
-                    .width(Length::FillPortion(1))
+                iced::widget::text(format!("{}{marker}", entry.column.label()))
+                    .width(Length::FillPortion(entry.width))
                     .size(header_text_size)
                     .style(move |_theme| iced::widget::text::Style {
                         color: Some(header_text_color.into()),
                     }),
             )
-            .on_press(Message::SortRightPanelByDuration)
-            .width(Length::FillPortion(1)),
+            .on_press(Message::SortRightPanelBy(sort_column))
+            .width(Length::FillPortion(entry.width)),
         );
     }
 
@@ -293,11 +1166,34 @@ fn create_right_panel_header_row(
     header_row
 }
 
+/// Creates the cell of `column` for a right panel row, with the column's
+/// relative `width`. The directory and file cells carry context menus.
+fn create_right_panel_cell(
+    file: &RightPanelFile,
+    column: PlaylistColumn,
+    width: u16,
+    row_text_size: u16,
+) -> Element<'static, Message> {
+    match column {
+        PlaylistColumn::Directory => {
+            create_right_panel_directory_widget(file, width, row_text_size)
+        },
+        PlaylistColumn::File => {
+            create_right_panel_file_context_menu(file, width, row_text_size)
+        },
+        _ => iced::widget::text(file.cell_text(column))
+            .width(Length::FillPortion(width))
+            .size(row_text_size)
+            .into(),
+    }
+}
+
 /// Creates the directory cell widget for a right panel row, displaying the
 /// parent directory name with the specified text size and providing a context
 /// menu for directory actions.
 fn create_right_panel_directory_widget(
     file: &RightPanelFile,
+    width: u16,
     row_text_size: u16,
 ) -> Element<'static, Message> {
     let directory_name = file
@@ -311,7 +1207,7 @@ fn create_right_panel_directory_widget(
         let directory_path = path.clone();
         iced_aw::widgets::ContextMenu::new(
             iced::widget::text(directory_name.clone())
-                .width(Length::FillPortion(1))
+                .width(Length::FillPortion(width))
                 .size(row_text_size),
             Box::new(move || {
                 iced::widget::column![
@@ -327,7 +1223,7 @@ fn create_right_panel_directory_widget(
     } else {
         iced_aw::widgets::ContextMenu::new(
             iced::widget::text(directory_name.clone())
-                .width(Length::FillPortion(1))
+                .width(Length::FillPortion(width))
                 .size(row_text_size),
             Box::new(|| iced::widget::column![].into())
                 as Box<dyn Fn() -> iced::Element<'static, Message>>,
@@ -338,9 +1234,11 @@ fn create_right_panel_directory_widget(
 
 /// Creates the file cell widget for a right panel row, displaying the file name
 /// with the  specified text size and providing a context menu for file-specific
-/// actions.
+/// actions: open, delete, and moving the row up, down, to the top, or to the
+/// bottom.
 fn create_right_panel_file_context_menu(
     file: &RightPanelFile,
+    width: u16,
     row_text_size: u16,
 ) -> Element<'static, Message> {
     let filename = file
@@ -350,15 +1248,30 @@ fn create_right_panel_file_context_menu(
         .unwrap_or_default();
     let file_context_menu = iced_aw::widgets::ContextMenu::new(
         iced::widget::text(filename.clone())
-            .width(Length::FillPortion(1))
+            .width(Length::FillPortion(width))
             .size(row_text_size),
         {
             let file_path = file.path.clone();
             Box::new(move || {
+                let move_button = |label, row_move| {
+                    iced::widget::button(label).on_press(
+                        Message::MoveRightPanelFile(
+                            file_path.clone(),
+                            row_move,
+                        ),
+                    )
+                };
                 iced::widget::column![
+                    iced::widget::button("Open").on_press(
+                        Message::OpenRightPanelFile(file_path.clone())
+                    ),
                     iced::widget::button("Delete").on_press(
                         Message::RemoveFromRightPanel(file_path.clone())
-                    )
+                    ),
+                    move_button("Move Up", RowMove::Up),
+                    move_button("Move Down", RowMove::Down),
+                    move_button("Move to Top", RowMove::ToTop),
+                    move_button("Move to Bottom", RowMove::ToBottom),
                 ]
                 .into()
             }) as Box<dyn Fn() -> iced::Element<'static, Message>>
@@ -375,41 +1288,27 @@ pub(crate) fn create_right_panel(
     menu_style: MenuStyle,
     item_list_style: ItemListStyle,
 ) -> Element<'_, Message> {
-    // Show all files in the playlist, sorted. The right panel is the
-    // cumulative playlist and should not be filtered by search.
-    let displayed_files: Vec<RightPanelFile> = app.sorted_right_panel_files();
-
-    // Determine which columns to show
-    let show_creator = displayed_files
-        .iter()
-        .any(|f| f.creator.as_ref().map(|s| !s.is_empty()).unwrap_or(false));
-    let show_album = displayed_files
-        .iter()
-        .any(|f| f.album.as_ref().map(|s| !s.is_empty()).unwrap_or(false));
-    let show_title = displayed_files
-        .iter()
-        .any(|f| f.title.as_ref().map(|s| !s.is_empty()).unwrap_or(false));
-    let show_genre = displayed_files
-        .iter()
-        .any(|f| f.genre.as_ref().map(|s| !s.is_empty()).unwrap_or(false));
-    let show_duration = displayed_files.iter().any(|f| f.duration_ms.is_some());
-
-    let audio_column_toggles = AudioColumnToggles {
-        show_creator,
-        show_album,
-        show_title,
-        show_genre,
-        show_duration,
-    };
+    // Show the playlist's files, sorted, that match the panel's own filter.
+    // The left panel's search does not filter the playlist.
+    let displayed_files: Vec<RightPanelFile> = app.filtered_right_panel_files();
 
-    let totals_display = create_totals_display(&displayed_files, menu_style);
+    let totals_display = create_totals_display(
+        &displayed_files,
+        &app.playlist().files,
+        menu_style,
+    );
     let header_text_size = item_list_style.row_text_size + 4;
-    let menu_row =
-        create_right_panel_menu_row(menu_style, Some(totals_display));
+    let menu_row = create_right_panel_menu_row(
+        menu_style,
+        app.playlist().sort_column == SortColumn::Manual,
+        app.export_options.relative_paths,
+        app.playlist().history.can_undo(),
+        app.playlist().history.can_redo(),
+        Some(totals_display),
+    );
 
     let header_row = create_right_panel_header_row(
         app,
-        audio_column_toggles,
         item_list_style.column_row_spacing,
         header_text_size,
         item_list_style.header_text_color,
@@ -419,58 +1318,45 @@ pub(crate) fn create_right_panel(
     for (i, file_ref) in displayed_files.iter().enumerate() {
         let file = file_ref.clone();
 
-        let dir_widget = create_right_panel_directory_widget(
-            &file,
-            item_list_style.row_text_size,
-        );
-        let file_context_menu = create_right_panel_file_context_menu(
-            &file,
-            item_list_style.row_text_size,
-        );
-
-        let mut row =
-            iced::widget::Row::new().push(dir_widget).push(file_context_menu);
+        // Pressing the handle starts dragging the row
+        let drag_handle = iced::widget::mouse_area(
+            iced::widget::text("≡")
+                .width(Length::Fixed(DRAG_HANDLE_WIDTH))
+                .size(item_list_style.row_text_size),
+        )
+        .on_press(Message::RightPanelDragStarted(file.path.clone()))
+        .interaction(iced::mouse::Interaction::Grab);
 
-        if show_creator {
-            row = row.push(
-                iced::widget::text(file.creator.clone().unwrap_or_default())
-                    .width(Length::FillPortion(1))
-                    .size(item_list_style.row_text_size),
-            );
-        }
-        if show_album {
-            row = row.push(
-                iced::widget::text(file.album.clone().unwrap_or_default())
-                    .width(Length::FillPortion(1))
-                    .size(item_list_style.row_text_size),
-            );
-        }
-        if show_title {
-            row = row.push(
-                iced::widget::text(file.title.clone().unwrap_or_default())
-                    .width(Length::FillPortion(1))
-                    .size(item_list_style.row_text_size),
-            );
-        }
-        if show_genre {
-            row = row.push(
-                iced::widget::text(file.genre.clone().unwrap_or_default())
-                    .width(Length::FillPortion(1))
-                    .size(item_list_style.row_text_size),
-            );
-        }
-        if show_duration {
-            row = row.push(
-                iced::widget::text(format_duration(file.duration_ms))
-                    .width(Length::FillPortion(1))
-                    .size(item_list_style.row_text_size),
-            );
+        let mut row = iced::widget::Row::new().push(drag_handle);
+        for entry in app.playlist_columns.iter().filter(|e| e.visible) {
+            row = row.push(create_right_panel_cell(
+                &file,
+                entry.column,
+                entry.width,
+                item_list_style.row_text_size,
+            ));
         }
         row = row.spacing(item_list_style.column_row_spacing);
 
         // Shade alternating pairs of rows
         let pair = (i / 2) % 2;
-        let bg_color = if pair == 0 {
+        let is_drop_target = app
+            .right_panel_drag
+            .as_ref()
+            .is_some_and(|drag| drag.target.as_ref() == Some(&file.path));
+        let bg_color = if is_drop_target {
+            iced::Color::from_rgb(
+                item_list_style.drop_target_shade[0],
+                item_list_style.drop_target_shade[1],
+                item_list_style.drop_target_shade[2],
+            )
+        } else if app.right_panel_selection.contains(&file.path) {
+            iced::Color::from_rgb(
+                item_list_style.selected_row_shade[0],
+                item_list_style.selected_row_shade[1],
+                item_list_style.selected_row_shade[2],
+            )
+        } else if pair == 0 {
             // iced::Color::from_rgb(0.13, 0.13, 0.13) // darker
             iced::Color::from_rgb(
                 item_list_style.dark_row_shade[0],
@@ -486,22 +1372,109 @@ pub(crate) fn create_right_panel(
             )
         };
 
+        // Tracks not found on restore are dimmed
+        let text_color = if file.missing {
+            iced::Color::from_rgb(0.5, 0.5, 0.5)
+        } else {
+            iced::Color::WHITE
+        };
         let clickable_row = iced::widget::button(row)
-            .on_press(Message::OpenRightPanelFile(file.path.clone()))
+            .on_press(Message::SelectRightPanelFile(file.path.clone()))
             .style(move |_theme, _style| iced::widget::button::Style {
                 background: Some(iced::Background::Color(bg_color)),
                 border: iced::Border::default(),
                 shadow: iced::Shadow::default(),
-                text_color: iced::Color::WHITE,
+                text_color,
             });
 
-        rows.push(clickable_row.into());
+        // While a row is dragged, the row under the pointer is the target
+        if app.right_panel_drag.is_some() {
+            rows.push(
+                iced::widget::mouse_area(clickable_row)
+                    .on_enter(Message::RightPanelDragEntered(file.path.clone()))
+                    .into(),
+            );
+        } else {
+            rows.push(clickable_row.into());
+        }
     }
 
-    let col = iced::widget::Column::new()
+    let mut col = iced::widget::Column::new()
         .push(Space::with_height(item_list_style.column_height_spacing))
+        .push(create_playlist_tabs(
+            app,
+            menu_style,
+            item_list_style.row_text_size,
+        ))
         .push(menu_row)
+        .push_maybe(create_selection_menu(
+            app,
+            menu_style,
+            item_list_style.row_text_size,
+        ))
+        .push(create_columns_menu(
+            app,
+            menu_style,
+            item_list_style.row_text_size,
+        ))
+        .push(create_playlist_info_menu(
+            app,
+            menu_style,
+            item_list_style.row_text_size,
+        ))
+        .push(create_player_menu(
+            app,
+            menu_style,
+            item_list_style.row_text_size,
+        ))
+        .push(create_mpd_menu(app, menu_style, item_list_style.row_text_size))
+        .push(create_path_rewrite_menu(
+            app,
+            menu_style,
+            item_list_style.row_text_size,
+        ))
+        .push(create_table_export_menu(
+            app,
+            menu_style,
+            item_list_style.row_text_size,
+        ))
+        .push(create_device_sync_menu(
+            app,
+            menu_style,
+            item_list_style.row_text_size,
+        ));
+    if let Some(progress) = &app.export_progress {
+        col = col
+            .push(Space::with_height(item_list_style.column_height_spacing))
+            .push(create_export_status_display(progress, menu_style));
+    }
+    if !app.playlist().missing_files.is_empty() {
+        col = col
+            .push(Space::with_height(item_list_style.column_height_spacing))
+            .push(create_missing_files_display(
+                &app.playlist().missing_files,
+                menu_style,
+                item_list_style.row_text_size,
+            ));
+    }
+    let missing_rows =
+        app.playlist().files.iter().filter(|f| f.missing).count();
+    if missing_rows > 0 {
+        col = col
+            .push(Space::with_height(item_list_style.column_height_spacing))
+            .push(create_missing_rows_display(
+                missing_rows,
+                menu_style,
+                item_list_style.row_text_size,
+            ));
+    }
+    let col = col
         .push(Space::with_height(item_list_style.column_height_spacing))
+        .push(create_right_panel_filter_row(
+            app,
+            menu_style,
+            !displayed_files.is_empty(),
+        ))
         .push(header_row)
         .push(Scrollable::new(iced::widget::column(rows)));
 
@@ -11,22 +11,42 @@
 //!         SearchCleared      — clear the current search query
 //!     TagTreeNode           — genre/creator/album hierarchy node
 //!     RightPanelFile        — a file entry with metadata
+//!     ImportedRightPanelPlaylist — a playlist file read for the right panel
 //!     LeftPanelSelectMode   — directory / genre / creator
 //!     LeftPanelSortMode     — alphanumeric / modified-date / file-count
 //!     SortColumn            — column key for sorting
 //!     SortOrder             — ascending / descending
+//!     SortKey               — a further sort column and its order
 //!     TextSearchMode        — search mode (all, path, filename, tags)
 
 use crate::db::sled_store::SledStore;
+use crate::fs::device_sync::{DEFAULT_SYNC_TEMPLATE, SyncProgress};
 use crate::fs::file_tree::{FileNode, scan_directory};
+use crate::fs::listing_export::ListingFormat;
+use crate::fs::playlist_export::{
+    ExportOptions, ExportProgress, PathRewriteRule, PlaylistFormat,
+    PlaylistInfo,
+};
+use crate::fs::table_export::{TableColumn, TableFormat};
+use crate::gui::columns::{
+    ColumnLayout, PlaylistColumn, default_column_layout,
+    normalize_column_layout,
+};
+use crate::gui::history::{History, PlaylistState};
+use crate::gui::notifications::Notifications;
 use crate::gui::tantivy_search::{
     TantivyIndexWrapper, build_tantivy_index, prune_file_tree, prune_tag_node,
 };
 use crate::gui::update::restore_expansion_state;
+use crate::player::external::{PlayerProfile, default_profiles};
+use crate::player::mpd::{MpdAction, MpdSettings, MpdSummary};
+use crate::utils::{format_duration, format_file_size};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 
 const TOP_DIRS_FILE: &str = ".playlist_ui_top_dirs.json";
 
@@ -51,25 +71,111 @@ pub enum Message {
     AddDirectoryToRightPanel(PathBuf),
     RemoveFromRightPanel(PathBuf),
     RemoveDirectoryFromRightPanel(PathBuf),
-    SortRightPanelByDirectory,
-    SortRightPanelByFile,
-    SortRightPanelByCreator,
-    SortRightPanelByAlbum,
-    SortRightPanelByTitle,
-    SortRightPanelByGenre,
-    SortRightPanelByDuration,
+    /// Sorts by a column; with Shift held, adds it as a further sort key.
+    SortRightPanelBy(SortColumn),
     ShuffleRightPanel,
+    SortRightPanelManually,
+    MoveRightPanelFile(PathBuf, RowMove),
+    RightPanelDragStarted(PathBuf),
+    RightPanelDragEntered(PathBuf),
+    RightPanelDragReleased,
+    KeyboardModifiersChanged(iced::keyboard::Modifiers),
+    SelectRightPanelFile(PathBuf),
+    ClearRightPanelSelection,
+    RemoveSelectedFromRightPanel,
+    MoveSelectedRightPanelFiles(RowMove),
+    CopySelectedToPlaylist(usize),
+    KeepOnlySelectedInRightPanel,
+    RightPanelFilterChanged(String),
+    RightPanelFilterCleared,
+    SelectAllRightPanelFiles,
+    ToggleColumnsMenu,
+    TogglePlaylistColumn(PlaylistColumn),
+    MovePlaylistColumnLeft(PlaylistColumn),
+    MovePlaylistColumnRight(PlaylistColumn),
+    NarrowPlaylistColumn(PlaylistColumn),
+    WidenPlaylistColumn(PlaylistColumn),
+    ResetPlaylistColumns,
     ExportRightPanelAsXspf,
     ExportRightPanelAsXspfTo(PathBuf),
-    ExportAndPlayRightPanelAsXspf,
+    ExportRightPanelAsJspf,
+    ExportRightPanelAsJspfTo(PathBuf),
+    ExportRightPanelAsM3u,
+    ExportRightPanelAsM3uTo(PathBuf),
+    ToggleTableExportMenu,
+    ToggleTableExportColumn(TableColumn),
+    ExportRightPanelAsTable(TableFormat),
+    ExportRightPanelAsTableTo(TableFormat, PathBuf),
+    ExportRightPanelAsListing(ListingFormat),
+    ExportRightPanelAsListingTo(ListingFormat, PathBuf),
+    ExportRightPanelAsPls,
+    ExportRightPanelAsPlsTo(PathBuf),
+    PlayRightPanel,
+    TogglePlayerMenu,
+    SelectPlayerProfile(String),
+    PlayerNameChanged(String),
+    PlayerExecutableChanged(String),
+    PlayerArgsChanged(String),
+    SelectPlayerFormat(PlaylistFormat),
+    AddPlayerProfile,
+    RemovePlayerProfile(String),
+    SelectPlaylist(usize),
+    NewPlaylist,
+    DuplicatePlaylist,
+    DeletePlaylist,
+    PlaylistNameDraftChanged(String),
+    RenamePlaylist,
+    ToggleMpdMenu,
+    MpdHostChanged(String),
+    MpdPortChanged(String),
+    MpdPasswordChanged(String),
+    MpdMusicDirectoryChanged(String),
+    MpdPlaylistNameChanged(String),
+    SendToMpd(MpdAction),
+    MpdFinished(MpdAction, Result<MpdSummary, String>),
+    TogglePlaylistInfoMenu,
+    PlaylistTitleChanged(String),
+    PlaylistCreatorChanged(String),
+    PlaylistAnnotationChanged(String),
+    SavePlaylistDetails,
+    PlaylistExportProgress(ExportProgress),
+    ToggleDeviceSyncMenu,
+    SyncTemplateChanged(String),
+    SyncRightPanelToFolder,
+    SyncRightPanelToFolderAt(PathBuf),
+    DeviceSyncProgress(SyncProgress),
+    CancelDeviceSync,
+    ToggleExportRelativePaths,
+    TogglePathRewriteMenu,
+    SelectPathRewriteRule(Option<String>),
+    PathRewriteNameChanged(String),
+    PathRewriteSourceChanged(String),
+    PathRewriteTargetChanged(String),
+    TogglePathRewriteWindowsSeparators,
+    AddPathRewriteRule,
+    RemovePathRewriteRule(String),
+    ImportRightPanelPlaylist,
+    ImportRightPanelPlaylistFrom(Option<PathBuf>),
+    RightPanelPlaylistImported(
+        PathBuf,
+        Result<ImportedRightPanelPlaylist, String>,
+    ),
+    DismissMissingPlaylistFiles,
+    RemoveMissingFromRightPanel,
+    RestoredFilesRead(Vec<RightPanelFile>),
     OpenRightPanelFile(PathBuf),
     ClearRightPanel,
+    UndoRightPanel,
+    RedoRightPanel,
     SearchQueryChanged(String),
     SearchCleared,
     ToggleSearchMode,
     RandomCountChanged(String),
     AddRandomTagNodeToRightPanel(Vec<String>),
     AddRandomDirectoryToRightPanel(PathBuf),
+    DismissNotification(u64),
+    ClearNotifications,
+    ToggleNotificationLog,
 }
 
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -118,7 +224,16 @@ pub enum LeftPanelSortMode {
     FileCount,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    bincode::Encode,
+    bincode::Decode,
+)]
 pub enum SortColumn {
     Directory,
     File,
@@ -127,15 +242,61 @@ pub enum SortColumn {
     Title,
     Genre,
     Duration,
+    /// The playlist's stored order, arranged by hand.
+    Manual,
+    TrackNumber,
+    DiscNumber,
+    Year,
+    AlbumArtist,
+    Bitrate,
+    SampleRate,
+    Channels,
+    BitDepth,
+    Codec,
+    FileSize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    bincode::Encode,
+    bincode::Decode,
+)]
 pub enum SortOrder {
     Asc,
     Desc,
 }
 
+/// A sort column with its order. A playlist's further keys break ties of its
+/// main sort column, in priority order.
+#[derive(Debug, Clone, Copy, PartialEq, bincode::Encode, bincode::Decode)]
+pub struct SortKey {
+    pub column: SortColumn,
+    pub order: SortOrder,
+}
+
+/// Where a context-menu move puts a playlist row in manual order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowMove {
+    Up,
+    Down,
+    ToTop,
+    ToBottom,
+}
+
+/// A playlist row being dragged by its handle, and the row the pointer is
+/// over, where it will be dropped.
 #[derive(Debug, Clone, PartialEq)]
+pub struct RightPanelDrag {
+    pub dragged: PathBuf,
+    pub target: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct RightPanelFile {
     pub path: PathBuf,
     pub creator: Option<String>,
@@ -143,6 +304,264 @@ pub struct RightPanelFile {
     pub title: Option<String>,
     pub genre: Option<String>,
     pub duration_ms: Option<u64>,
+    pub track_num: Option<u32>,
+    pub identifier: Option<String>,
+    pub annotation: Option<String>,
+    pub image_uri: Option<String>,
+    pub disc_num: Option<u32>,
+    pub year: Option<u32>,
+    pub album_artist: Option<String>,
+    pub bitrate_kbps: Option<u32>,
+    pub sample_rate_hz: Option<u32>,
+    pub channels: Option<u8>,
+    pub bit_depth: Option<u8>,
+    pub codec: Option<String>,
+    pub file_size: Option<u64>,
+    /// Whether the file was not found when its playlist was restored. The
+    /// row stays, dimmed, until the user removes missing tracks.
+    pub missing: bool,
+}
+
+impl RightPanelFile {
+    /// The text the playlist table shows in `column` for this file, e.g.
+    /// "320 kbps" or "44.1 kHz"; empty when the value is unknown.
+    pub(crate) fn cell_text(&self, column: PlaylistColumn) -> String {
+        let text = |value: &Option<String>| value.clone().unwrap_or_default();
+        let number = |value: Option<u32>, unit: &str| {
+            value.map(|v| format!("{v}{unit}")).unwrap_or_default()
+        };
+        match column {
+            PlaylistColumn::Directory => self
+                .path
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|d| d.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            PlaylistColumn::File => self
+                .path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            PlaylistColumn::Creator => text(&self.creator),
+            PlaylistColumn::Album => text(&self.album),
+            PlaylistColumn::Title => text(&self.title),
+            PlaylistColumn::Genre => text(&self.genre),
+            PlaylistColumn::Duration => format_duration(self.duration_ms),
+            PlaylistColumn::TrackNumber => number(self.track_num, ""),
+            PlaylistColumn::DiscNumber => number(self.disc_num, ""),
+            PlaylistColumn::Year => number(self.year, ""),
+            PlaylistColumn::AlbumArtist => text(&self.album_artist),
+            PlaylistColumn::Bitrate => number(self.bitrate_kbps, " kbps"),
+            PlaylistColumn::SampleRate => self
+                .sample_rate_hz
+                .map(|hz| format!("{:.1} kHz", f64::from(hz) / 1000.0))
+                .unwrap_or_default(),
+            PlaylistColumn::Channels => {
+                number(self.channels.map(u32::from), "")
+            },
+            PlaylistColumn::BitDepth => {
+                number(self.bit_depth.map(u32::from), " bit")
+            },
+            PlaylistColumn::Codec => text(&self.codec),
+            PlaylistColumn::FileSize => format_file_size(self.file_size),
+        }
+    }
+
+    /// Whether every term of `terms` (see `filter_terms`) appears,
+    /// case-insensitively, in the file's path, its annotation, or the text
+    /// of one of its table columns (see `cell_text`), shown or not. Fields
+    /// are formatted one at a time, only until every term is found. No terms
+    /// match every file.
+    pub(crate) fn matches_filter(&self, terms: &[String]) -> bool {
+        if terms.is_empty() {
+            return true;
+        }
+        let mut unmatched: Vec<&str> =
+            terms.iter().map(String::as_str).collect();
+        let fields = std::iter::once(self.path.to_string_lossy().into_owned())
+            .chain(self.annotation.iter().cloned())
+            .chain(PlaylistColumn::ALL.iter().map(|&c| self.cell_text(c)));
+        for field in fields {
+            let field = field.to_lowercase();
+            unmatched.retain(|term| !field.contains(term));
+            if unmatched.is_empty() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Splits a right-panel filter into lowercased terms, once for all the rows
+/// it is matched against. A blank filter has no terms.
+pub(crate) fn filter_terms(filter: &str) -> Vec<String> {
+    filter.split_whitespace().map(str::to_lowercase).collect()
+}
+
+/// Compares two right-panel files by `column` alone, in ascending order.
+/// Text is compared case-insensitively; missing values sort first.
+pub(crate) fn compare_right_panel_files(
+    a: &RightPanelFile,
+    b: &RightPanelFile,
+    column: SortColumn,
+) -> std::cmp::Ordering {
+    let file_name = |f: &RightPanelFile| {
+        f.path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_ascii_lowercase()
+    };
+    let lowercase = |value: &Option<String>| {
+        value.as_deref().unwrap_or_default().to_ascii_lowercase()
+    };
+    match column {
+        SortColumn::Directory => {
+            let directory = |f: &RightPanelFile| {
+                f.path
+                    .parent()
+                    .and_then(|p| p.file_name())
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_ascii_lowercase()
+            };
+            directory(a).cmp(&directory(b))
+        },
+        SortColumn::File => file_name(a).cmp(&file_name(b)),
+        SortColumn::Creator => {
+            lowercase(&a.creator).cmp(&lowercase(&b.creator))
+        },
+        SortColumn::Album => lowercase(&a.album).cmp(&lowercase(&b.album)),
+        SortColumn::Title => lowercase(&a.title).cmp(&lowercase(&b.title)),
+        SortColumn::Genre => lowercase(&a.genre).cmp(&lowercase(&b.genre)),
+        SortColumn::Duration => {
+            a.duration_ms.unwrap_or(0).cmp(&b.duration_ms.unwrap_or(0))
+        },
+        // The sort is stable, so the stored order is kept
+        SortColumn::Manual => std::cmp::Ordering::Equal,
+        SortColumn::TrackNumber => a.track_num.cmp(&b.track_num),
+        SortColumn::DiscNumber => a.disc_num.cmp(&b.disc_num),
+        SortColumn::Year => a.year.cmp(&b.year),
+        SortColumn::AlbumArtist => {
+            lowercase(&a.album_artist).cmp(&lowercase(&b.album_artist))
+        },
+        SortColumn::Bitrate => a.bitrate_kbps.cmp(&b.bitrate_kbps),
+        SortColumn::SampleRate => a.sample_rate_hz.cmp(&b.sample_rate_hz),
+        SortColumn::Channels => a.channels.cmp(&b.channels),
+        SortColumn::BitDepth => a.bit_depth.cmp(&b.bit_depth),
+        SortColumn::Codec => lowercase(&a.codec).cmp(&lowercase(&b.codec)),
+        SortColumn::FileSize => a.file_size.cmp(&b.file_size),
+    }
+}
+
+/// A playlist file read for the right panel: its tracks with their metadata
+/// already read, the entries whose files were not found, and the playlist's
+/// details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRightPanelPlaylist {
+    pub files: Vec<RightPanelFile>,
+    pub missing: Vec<PathBuf>,
+    pub info: PlaylistInfo,
+}
+
+/// Name of the playlist a fresh start begins with.
+pub(crate) const DEFAULT_PLAYLIST_NAME: &str = "Playlist";
+
+/// A named playlist of the right panel. Each playlist keeps its own tracks,
+/// sort keys, shuffle flag, and details; the panel shows the active one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playlist {
+    pub name: String,
+    /// Tracks in the order they were added, or shuffled.
+    pub files: Vec<RightPanelFile>,
+    pub sort_column: SortColumn,
+    pub sort_order: SortOrder,
+    /// Further sort keys, in priority order, for rows equal by
+    /// `sort_column`. Empty in manual order.
+    pub then_by: Vec<SortKey>,
+    /// Whether `files` is shown in its own order rather than sorted.
+    pub shuffled: bool,
+    /// Playlist-level title, creator, and annotation written by XSPF
+    /// exports; empty fields are left out.
+    pub title: String,
+    pub creator: String,
+    pub annotation: String,
+    /// Entries of the imported playlist whose files were not found, listed
+    /// above the table until dismissed.
+    pub missing_files: Vec<PathBuf>,
+    /// Edits of this playlist that can be undone or redone.
+    pub(crate) history: History,
+}
+
+impl Playlist {
+    /// An empty playlist sorted by directory.
+    pub fn new(name: impl Into<String>) -> Self {
+        Playlist {
+            name: name.into(),
+            files: Vec::new(),
+            sort_column: SortColumn::Directory,
+            sort_order: SortOrder::Asc,
+            then_by: Vec::new(),
+            shuffled: false,
+            title: String::new(),
+            creator: String::new(),
+            annotation: String::new(),
+            missing_files: Vec::new(),
+            history: History::default(),
+        }
+    }
+
+    /// Every sort key of this playlist in priority order, starting with its
+    /// main sort column.
+    pub(crate) fn sort_keys(&self) -> Vec<SortKey> {
+        let main = SortKey { column: self.sort_column, order: self.sort_order };
+        std::iter::once(main).chain(self.then_by.iter().copied()).collect()
+    }
+
+    /// The part of this playlist an edit can change.
+    pub(crate) fn state(&self) -> PlaylistState {
+        PlaylistState {
+            files: self.files.clone(),
+            sort_column: self.sort_column,
+            sort_order: self.sort_order,
+            then_by: self.then_by.clone(),
+            shuffled: self.shuffled,
+            title: self.title.clone(),
+            creator: self.creator.clone(),
+            annotation: self.annotation.clone(),
+            missing_files: self.missing_files.clone(),
+        }
+    }
+
+    /// Replaces this playlist's tracks, sorting, and details with `state`.
+    pub(crate) fn set_state(&mut self, state: PlaylistState) {
+        self.files = state.files;
+        self.sort_column = state.sort_column;
+        self.sort_order = state.sort_order;
+        self.then_by = state.then_by;
+        self.shuffled = state.shuffled;
+        self.title = state.title;
+        self.creator = state.creator;
+        self.annotation = state.annotation;
+        self.missing_files = state.missing_files;
+    }
+}
+
+/// A playlist as persisted in Sled: its name, the track paths in playlist
+/// order, how it is displayed, and its details. Metadata is read again on
+/// restore.
+#[derive(Debug, Clone, PartialEq, bincode::Encode, bincode::Decode)]
+pub struct SavedPlaylist {
+    pub name: String,
+    pub paths: Vec<PathBuf>,
+    pub sort_column: SortColumn,
+    pub sort_order: SortOrder,
+    pub then_by: Vec<SortKey>,
+    pub shuffled: bool,
+    pub title: String,
+    pub creator: String,
+    pub annotation: String,
+    pub missing_files: Vec<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -184,12 +603,110 @@ pub struct FileTreeApp {
     pub extensions_menu_expanded: bool,
     #[serde(skip)]
     pub expanded_dirs: HashSet<PathBuf>,
+    /// The named playlists shown as tabs; never empty.
+    #[serde(skip)]
+    pub playlists: Vec<Playlist>,
+    /// Index into `playlists` of the playlist the right panel shows.
+    #[serde(skip)]
+    pub active_playlist: usize,
+    /// Text of the playlist name input in the tab strip.
+    #[serde(skip)]
+    pub playlist_name_draft: String,
+    /// Text of the playlist details inputs, saved to the active playlist by
+    /// `SavePlaylistDetails`.
+    #[serde(skip)]
+    pub playlist_title_draft: String,
+    #[serde(skip)]
+    pub playlist_creator_draft: String,
+    #[serde(skip)]
+    pub playlist_annotation_draft: String,
+    /// The row drag in progress, if any.
+    #[serde(skip)]
+    pub right_panel_drag: Option<RightPanelDrag>,
+    /// Selected tracks of the active playlist, by path, so the selection
+    /// survives re-sorting.
+    #[serde(skip)]
+    pub right_panel_selection: HashSet<PathBuf>,
+    /// The track a shift-click selects a range from: the last one clicked
+    /// without shift.
+    #[serde(skip)]
+    pub right_panel_selection_anchor: Option<PathBuf>,
+    /// Text narrowing the playlist rows shown; see
+    /// `RightPanelFile::matches_filter`. Empty shows every row.
+    #[serde(skip)]
+    pub right_panel_filter: String,
+    /// Whether the saved playlists could not be loaded, in which case they
+    /// are not overwritten.
+    #[serde(skip)]
+    pub playlists_unreadable: bool,
+    /// Modifier keys currently held, read when a row is clicked.
+    #[serde(skip)]
+    pub keyboard_modifiers: iced::keyboard::Modifiers,
+    /// Columns of the playlist table in display order, with their
+    /// visibility and widths, persisted in Sled.
+    #[serde(skip)]
+    pub playlist_columns: Vec<ColumnLayout>,
+    #[serde(skip)]
+    pub columns_menu_expanded: bool,
+    #[serde(skip)]
+    pub export_options: ExportOptions,
+    /// Progress or result of the latest background playlist export, shown
+    /// in the right panel until the next export starts.
+    #[serde(skip)]
+    pub export_progress: Option<ExportProgress>,
+    #[serde(skip)]
+    pub playlist_info_menu_expanded: bool,
     #[serde(skip)]
-    pub right_panel_files: Vec<RightPanelFile>,
-    pub right_panel_sort_column: SortColumn,
-    pub right_panel_sort_order: SortOrder,
+    pub path_rewrite_rules: Vec<PathRewriteRule>,
     #[serde(skip)]
-    pub right_panel_shuffled: bool,
+    pub path_rewrite_draft: PathRewriteRule,
+    #[serde(skip)]
+    pub path_rewrite_menu_expanded: bool,
+    /// Columns written by table exports, persisted in Sled.
+    #[serde(skip)]
+    pub table_export_columns: Vec<TableColumn>,
+    #[serde(skip)]
+    pub table_export_menu_expanded: bool,
+    /// Filename template for copies made by device sync, persisted in Sled.
+    #[serde(skip)]
+    pub sync_template: String,
+    #[serde(skip)]
+    pub sync_menu_expanded: bool,
+    /// Progress or result of the latest device sync.
+    #[serde(skip)]
+    pub sync_progress: Option<SyncProgress>,
+    /// Cancellation flag of the running device sync, if any.
+    #[serde(skip)]
+    pub sync_cancel: Option<Arc<AtomicBool>>,
+    /// External player profiles, persisted in Sled.
+    #[serde(skip)]
+    pub player_profiles: Vec<PlayerProfile>,
+    /// Name of the profile used by "Play", persisted in Sled.
+    #[serde(skip)]
+    pub selected_player: String,
+    #[serde(skip)]
+    pub player_draft: PlayerProfile,
+    #[serde(skip)]
+    pub player_menu_expanded: bool,
+    /// MPD server address and music directory, persisted in Sled.
+    #[serde(skip)]
+    pub mpd_settings: MpdSettings,
+    /// Text of the MPD port input; may be empty while the user is typing.
+    #[serde(skip)]
+    pub mpd_port_input: String,
+    /// Name of the stored playlist "Save as MPD Playlist" writes.
+    #[serde(skip)]
+    pub mpd_playlist_name: String,
+    #[serde(skip)]
+    pub mpd_menu_expanded: bool,
+    /// Whether a request to the MPD server is in progress.
+    #[serde(skip)]
+    pub mpd_busy: bool,
+    /// Outcomes of side effects, shown in the status bar.
+    #[serde(skip)]
+    pub notifications: Notifications,
+    #[serde(skip)]
+    pub notification_log_expanded: bool,
     #[serde(skip)]
     pub random_count: usize,
     #[serde(skip)]
@@ -233,6 +750,35 @@ impl FileTreeApp {
             restore_expansion_state(root, &expanded_dirs);
         }
         let tantivy_index = Some(build_tantivy_index(&root_nodes));
+        let path_rewrite_rules = sled_store
+            .as_ref()
+            .and_then(|store| store.load_path_rewrite_rules())
+            .unwrap_or_default();
+        let table_export_columns = sled_store
+            .as_ref()
+            .and_then(|store| store.load_table_export_columns())
+            .unwrap_or_else(|| TableColumn::ALL.to_vec());
+        let sync_template = sled_store
+            .as_ref()
+            .and_then(|store| store.load_sync_template())
+            .unwrap_or_else(|| DEFAULT_SYNC_TEMPLATE.to_string());
+        let player_profiles = sled_store
+            .as_ref()
+            .and_then(|store| store.load_player_profiles())
+            .unwrap_or_else(default_profiles);
+        let selected_player = sled_store
+            .as_ref()
+            .and_then(|store| store.load_selected_player())
+            .or_else(|| player_profiles.first().map(|p| p.name.clone()))
+            .unwrap_or_default();
+        let mpd_settings = sled_store
+            .as_ref()
+            .and_then(|store| store.load_mpd_settings())
+            .unwrap_or_default();
+        let playlist_columns = sled_store
+            .as_ref()
+            .and_then(|store| store.load_playlist_columns())
+            .map_or_else(default_column_layout, normalize_column_layout);
         FileTreeApp {
             sled_store,
             left_panel_selection_mode: LeftPanelSelectMode::Directory,
@@ -253,10 +799,43 @@ impl FileTreeApp {
             last_search_matches: None,
             extensions_menu_expanded: false,
             expanded_dirs,
-            right_panel_files: Vec::new(),
-            right_panel_sort_column: SortColumn::Directory,
-            right_panel_sort_order: SortOrder::Asc,
-            right_panel_shuffled: false,
+            playlists: vec![Playlist::new(DEFAULT_PLAYLIST_NAME)],
+            active_playlist: 0,
+            playlist_name_draft: DEFAULT_PLAYLIST_NAME.to_string(),
+            playlist_title_draft: String::new(),
+            playlist_creator_draft: String::new(),
+            playlist_annotation_draft: String::new(),
+            right_panel_drag: None,
+            right_panel_selection: HashSet::new(),
+            right_panel_selection_anchor: None,
+            right_panel_filter: String::new(),
+            playlists_unreadable: false,
+            keyboard_modifiers: iced::keyboard::Modifiers::default(),
+            playlist_columns,
+            columns_menu_expanded: false,
+            export_options: ExportOptions::default(),
+            export_progress: None,
+            playlist_info_menu_expanded: false,
+            path_rewrite_rules,
+            path_rewrite_draft: PathRewriteRule::default(),
+            path_rewrite_menu_expanded: false,
+            table_export_columns,
+            table_export_menu_expanded: false,
+            sync_template,
+            sync_menu_expanded: false,
+            sync_progress: None,
+            sync_cancel: None,
+            player_profiles,
+            selected_player,
+            player_draft: PlayerProfile::default(),
+            player_menu_expanded: false,
+            mpd_port_input: mpd_settings.port.to_string(),
+            mpd_settings,
+            mpd_playlist_name: String::new(),
+            mpd_menu_expanded: false,
+            mpd_busy: false,
+            notifications: Notifications::default(),
+            notification_log_expanded: false,
             random_count: 6,
             random_count_input: "6".to_string(),
         }
@@ -282,7 +861,131 @@ impl FileTreeApp {
         } else {
             Vec::new()
         };
-        FileTreeApp::new(top_dirs, all_extensions, persist_path, sled_store)
+        let mut app = FileTreeApp::new(
+            top_dirs,
+            all_extensions,
+            persist_path,
+            sled_store,
+        );
+        app.restore_playlists();
+        app
+    }
+
+    /// Restores the playlists saved in the Sled store and the active one.
+    /// Tracks come back as rows holding only their paths, so nothing is read
+    /// from disk before the first frame; `read_restored_files_task` fills in
+    /// their metadata, or marks them `missing`, in the background.
+    ///
+    /// Saved playlists that cannot be read are left in the store untouched:
+    /// the error is logged and notified, and `persist_playlists` stops
+    /// saving until the next start.
+    pub(crate) fn restore_playlists(&mut self) {
+        let Some(store) = &self.sled_store else {
+            return;
+        };
+        let saved = match store.load_playlists() {
+            Ok(Some(saved)) if !saved.is_empty() => saved,
+            Ok(_) => return,
+            Err(e) => {
+                log::error!("Failed to load saved playlists: {e}");
+                self.notifications.error(format!(
+                    "Saved playlists could not be loaded ({e}); they are \
+                     kept, and playlist changes are not saved"
+                ));
+                self.playlists_unreadable = true;
+                return;
+            },
+        };
+        self.playlists = saved
+            .into_iter()
+            .map(|saved| Playlist {
+                name: saved.name,
+                files: saved
+                    .paths
+                    .into_iter()
+                    .map(|path| RightPanelFile { path, ..Default::default() })
+                    .collect(),
+                sort_column: saved.sort_column,
+                sort_order: saved.sort_order,
+                then_by: saved.then_by,
+                shuffled: saved.shuffled,
+                title: saved.title,
+                creator: saved.creator,
+                annotation: saved.annotation,
+                missing_files: saved.missing_files,
+                history: History::default(),
+            })
+            .collect();
+        let active = self
+            .sled_store
+            .as_ref()
+            .and_then(|store| store.load_active_playlist())
+            .unwrap_or(0);
+        self.active_playlist = active.min(self.playlists.len() - 1);
+        self.reset_playlist_drafts();
+    }
+
+    /// Resets the playlist name and details inputs to the active playlist's
+    /// values, dropping text that was not saved.
+    pub(crate) fn reset_playlist_drafts(&mut self) {
+        let playlist = &self.playlists[self.active_playlist];
+        self.playlist_name_draft = playlist.name.clone();
+        self.playlist_title_draft = playlist.title.clone();
+        self.playlist_creator_draft = playlist.creator.clone();
+        self.playlist_annotation_draft = playlist.annotation.clone();
+    }
+
+    /// Persists every playlist and which one is active to the Sled store,
+    /// if one is attached and the playlists saved there could be loaded.
+    pub(crate) fn persist_playlists(&self) -> Result<(), sled::Error> {
+        match &self.sled_store {
+            Some(_) if self.playlists_unreadable => Ok(()),
+            Some(store) => {
+                let saved: Vec<SavedPlaylist> = self
+                    .playlists
+                    .iter()
+                    .map(|playlist| SavedPlaylist {
+                        name: playlist.name.clone(),
+                        paths: playlist
+                            .files
+                            .iter()
+                            .map(|f| f.path.clone())
+                            .collect(),
+                        sort_column: playlist.sort_column,
+                        sort_order: playlist.sort_order,
+                        then_by: playlist.then_by.clone(),
+                        shuffled: playlist.shuffled,
+                        title: playlist.title.clone(),
+                        creator: playlist.creator.clone(),
+                        annotation: playlist.annotation.clone(),
+                        missing_files: playlist.missing_files.clone(),
+                    })
+                    .collect();
+                store.save_playlists(&saved)?;
+                store.save_active_playlist(self.active_playlist)
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// The playlist the right panel shows.
+    pub(crate) fn playlist(&self) -> &Playlist {
+        &self.playlists[self.active_playlist]
+    }
+
+    /// The playlist the right panel shows, for changing it.
+    pub(crate) fn playlist_mut(&mut self) -> &mut Playlist {
+        &mut self.playlists[self.active_playlist]
+    }
+
+    /// `base`, or `base` followed by the lowest free number, so that no two
+    /// playlists share a name.
+    pub(crate) fn unique_playlist_name(&self, base: &str) -> String {
+        let taken = |name: &str| self.playlists.iter().any(|p| p.name == name);
+        if !taken(base) {
+            return base.to_string();
+        }
+        (2..).map(|n| format!("{base} {n}")).find(|name| !taken(name)).unwrap()
     }
 
     /// Persists the current list of top-level directories to disk as JSON,
@@ -295,6 +998,78 @@ impl FileTreeApp {
         Ok(())
     }
 
+    /// Persists the path rewrite rules to the Sled store, if one is
+    /// attached.
+    pub(crate) fn persist_path_rewrite_rules(&self) -> Result<(), sled::Error> {
+        match &self.sled_store {
+            Some(store) => {
+                store.save_path_rewrite_rules(&self.path_rewrite_rules)
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Persists the columns chosen for table exports to the Sled store, if
+    /// one is attached.
+    pub(crate) fn persist_table_export_columns(
+        &self,
+    ) -> Result<(), sled::Error> {
+        match &self.sled_store {
+            Some(store) => {
+                store.save_table_export_columns(&self.table_export_columns)
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Persists the device sync filename template to the Sled store, if one
+    /// is attached.
+    pub(crate) fn persist_sync_template(&self) -> Result<(), sled::Error> {
+        match &self.sled_store {
+            Some(store) => store.save_sync_template(&self.sync_template),
+            None => Ok(()),
+        }
+    }
+
+    /// Persists the player profiles and the selected player to the Sled
+    /// store, if one is attached.
+    pub(crate) fn persist_player_profiles(&self) -> Result<(), sled::Error> {
+        match &self.sled_store {
+            Some(store) => {
+                store.save_player_profiles(&self.player_profiles)?;
+                store.save_selected_player(&self.selected_player)
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Persists the playlist table's column layout to the Sled store, if one
+    /// is attached.
+    pub(crate) fn persist_playlist_columns(&self) -> Result<(), sled::Error> {
+        match &self.sled_store {
+            Some(store) => store.save_playlist_columns(&self.playlist_columns),
+            None => Ok(()),
+        }
+    }
+
+    /// Persists the MPD server settings to the Sled store, if one is
+    /// attached.
+    pub(crate) fn persist_mpd_settings(&self) -> Result<(), sled::Error> {
+        match &self.sled_store {
+            Some(store) => store.save_mpd_settings(&self.mpd_settings),
+            None => Ok(()),
+        }
+    }
+
+    /// The profile "Play" launches: the selected one, or the first profile
+    /// when the selection no longer exists.
+    pub(crate) fn selected_player_profile(&self) -> Option<&PlayerProfile> {
+        self.player_profiles
+            .iter()
+            .find(|p| p.name == self.selected_player)
+            .or_else(|| self.player_profiles.first())
+    }
+
     /// Executes a full-text search against the tantivy index, pruning both
     /// the file tree and tag tree to only show matching paths. Skips the
     /// search if the query is empty or the index is unavailable.
@@ -343,162 +1118,47 @@ impl FileTreeApp {
     }
 
     /// Returns a sorted vector of files currently in the right panel, using the
-    ///     configured sort column and order, unless the panel is marked as
-    ///     shuffled.
+    ///     configured sort column and order and then its further sort keys,
+    ///     unless the panel is marked as shuffled or sorted manually.
     pub(crate) fn sorted_right_panel_files(&self) -> Vec<RightPanelFile> {
-        let mut files = self.right_panel_files.clone();
-        if !self.right_panel_shuffled {
+        let playlist = self.playlist();
+        let mut files = playlist.files.clone();
+        if !playlist.shuffled {
+            let keys = playlist.sort_keys();
             files.sort_by(|a, b| {
-                let filename_cmp = || {
-                    let a_name = a
-                        .path
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_ascii_lowercase();
-                    let b_name = b
-                        .path
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_ascii_lowercase();
-                    a_name.cmp(&b_name)
-                };
-                match self.right_panel_sort_column {
-                    SortColumn::Directory => {
-                        let a_dir = a
-                            .path
-                            .parent()
-                            .and_then(|p| p.file_name())
-                            .unwrap_or_default()
-                            .to_string_lossy()
-                            .to_ascii_lowercase();
-                        let b_dir = b
-                            .path
-                            .parent()
-                            .and_then(|p| p.file_name())
-                            .unwrap_or_default()
-                            .to_string_lossy()
-                            .to_ascii_lowercase();
-                        let primary =
-                            if self.right_panel_sort_order == SortOrder::Asc {
-                                a_dir.cmp(&b_dir)
-                            } else {
-                                b_dir.cmp(&a_dir)
-                            };
-                        primary.then_with(filename_cmp)
-                    },
-                    SortColumn::File => {
-                        let a_file = a
-                            .path
-                            .file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy()
-                            .to_ascii_lowercase();
-                        let b_file = b
-                            .path
-                            .file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy()
-                            .to_ascii_lowercase();
-                        if self.right_panel_sort_order == SortOrder::Asc {
-                            a_file.cmp(&b_file)
-                        } else {
-                            b_file.cmp(&a_file)
-                        }
-                    },
-                    SortColumn::Creator => {
-                        let a_creator = a
-                            .creator
-                            .as_deref()
-                            .unwrap_or_default()
-                            .to_ascii_lowercase();
-                        let b_creator = b
-                            .creator
-                            .as_deref()
-                            .unwrap_or_default()
-                            .to_ascii_lowercase();
-                        let primary =
-                            if self.right_panel_sort_order == SortOrder::Asc {
-                                a_creator.cmp(&b_creator)
-                            } else {
-                                b_creator.cmp(&a_creator)
-                            };
-                        primary.then_with(filename_cmp)
-                    },
-                    SortColumn::Album => {
-                        let a_album = a
-                            .album
-                            .as_deref()
-                            .unwrap_or_default()
-                            .to_ascii_lowercase();
-                        let b_album = b
-                            .album
-                            .as_deref()
-                            .unwrap_or_default()
-                            .to_ascii_lowercase();
-                        let primary =
-                            if self.right_panel_sort_order == SortOrder::Asc {
-                                a_album.cmp(&b_album)
-                            } else {
-                                b_album.cmp(&a_album)
-                            };
-                        primary.then_with(filename_cmp)
-                    },
-                    SortColumn::Title => {
-                        let a_title = a
-                            .title
-                            .as_deref()
-                            .unwrap_or_default()
-                            .to_ascii_lowercase();
-                        let b_title = b
-                            .title
-                            .as_deref()
-                            .unwrap_or_default()
-                            .to_ascii_lowercase();
-                        let primary =
-                            if self.right_panel_sort_order == SortOrder::Asc {
-                                a_title.cmp(&b_title)
-                            } else {
-                                b_title.cmp(&a_title)
-                            };
-                        primary.then_with(filename_cmp)
-                    },
-                    SortColumn::Genre => {
-                        let a_genre = a
-                            .genre
-                            .as_deref()
-                            .unwrap_or_default()
-                            .to_ascii_lowercase();
-                        let b_genre = b
-                            .genre
-                            .as_deref()
-                            .unwrap_or_default()
-                            .to_ascii_lowercase();
-                        let primary =
-                            if self.right_panel_sort_order == SortOrder::Asc {
-                                a_genre.cmp(&b_genre)
-                            } else {
-                                b_genre.cmp(&a_genre)
-                            };
-                        primary.then_with(filename_cmp)
-                    },
-                    SortColumn::Duration => {
-                        let a_dur = a.duration_ms.unwrap_or(0);
-                        let b_dur = b.duration_ms.unwrap_or(0);
-                        let primary =
-                            if self.right_panel_sort_order == SortOrder::Asc {
-                                a_dur.cmp(&b_dur)
-                            } else {
-                                b_dur.cmp(&a_dur)
-                            };
-                        primary.then_with(filename_cmp)
+                let ordering = keys.iter().fold(
+                    std::cmp::Ordering::Equal,
+                    |ordering, key| {
+                        ordering.then_with(|| {
+                            let ordering =
+                                compare_right_panel_files(a, b, key.column);
+                            match key.order {
+                                SortOrder::Asc => ordering,
+                                SortOrder::Desc => ordering.reverse(),
+                            }
+                        })
                     },
+                );
+                // Ties are broken by ascending file name
+                match playlist.sort_column {
+                    SortColumn::Manual => ordering,
+                    _ => ordering.then_with(|| {
+                        compare_right_panel_files(a, b, SortColumn::File)
+                    }),
                 }
             });
         }
         files
     }
+
+    /// The sorted files of the right panel that match `right_panel_filter`:
+    ///     the rows the panel shows, and the ones selection acts on.
+    pub(crate) fn filtered_right_panel_files(&self) -> Vec<RightPanelFile> {
+        let mut files = self.sorted_right_panel_files();
+        let terms = filter_terms(&self.right_panel_filter);
+        files.retain(|f| f.matches_filter(&terms));
+        files
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,129 @@
+//! Status bar UI construction for the Playlist UI.
+//!
+//! Builds the bar shown under both panels while there are notifications:
+//! the newest notification with its time, coloured by level, followed by
+//! buttons to show the earlier ones, dismiss the newest, or clear them all.
+//! The expanded history lists every queued notification, newest first, each
+//! with its own dismiss button.
+//!
+//! Public API:
+//!     create_status_bar — assemble the status bar Element, if any
+
+use crate::gui::notifications::{Notification, NotificationLevel};
+use crate::gui::view::MenuStyle;
+use crate::gui::{FileTreeApp, Message};
+use iced::{Element, Length, widget::Scrollable};
+
+/// Text colour of a notification of the given level.
+fn level_color(level: NotificationLevel, menu_style: MenuStyle) -> [f32; 4] {
+    match level {
+        NotificationLevel::Info => menu_style.text_color,
+        NotificationLevel::Warning => [1.0, 0.6, 0.2, 1.0],
+        NotificationLevel::Error => [1.0, 0.4, 0.4, 1.0],
+    }
+}
+
+/// One notification as a line of text: its time, then its message.
+fn notification_text(
+    notification: &Notification,
+    menu_style: MenuStyle,
+    text_size: u16,
+) -> iced::widget::Text<'static> {
+    let color = level_color(notification.level, menu_style);
+    iced::widget::text(format!(
+        "[{}] {}",
+        notification.timestamp.format("%H:%M:%S"),
+        notification.message
+    ))
+    .size(text_size)
+    .style(move |_theme| iced::widget::text::Style {
+        color: Some(color.into()),
+    })
+}
+
+/// Creates the status bar, or `None` while there is nothing to report.
+pub(crate) fn create_status_bar(
+    app: &FileTreeApp,
+    menu_style: MenuStyle,
+    row_text_size: u16,
+) -> Option<Element<'static, Message>> {
+    let latest = app.notifications.latest()?;
+
+    let mut bar = iced::widget::Row::new()
+        .push(
+            iced::widget::container(notification_text(
+                latest,
+                menu_style,
+                row_text_size,
+            ))
+            .width(Length::Fill),
+        )
+        .spacing(menu_style.spacing)
+        .align_y(iced::Alignment::Center);
+    if app.notifications.len() > 1 {
+        let label = if app.notification_log_expanded {
+            "Hide History".to_string()
+        } else {
+            format!("History ({})", app.notifications.len())
+        };
+        bar = bar.push(
+            iced::widget::button(iced::widget::text(label).size(row_text_size))
+                .on_press(Message::ToggleNotificationLog),
+        );
+    }
+    bar = bar
+        .push(
+            iced::widget::button(
+                iced::widget::text("Dismiss").size(row_text_size),
+            )
+            .on_press(Message::DismissNotification(latest.id)),
+        )
+        .push(
+            iced::widget::button(
+                iced::widget::text("Clear All").size(row_text_size),
+            )
+            .on_press(Message::ClearNotifications),
+        );
+
+    let mut col = iced::widget::Column::new();
+    if app.notification_log_expanded {
+        let mut history = iced::widget::Column::new();
+        for notification in app.notifications.iter().rev().skip(1) {
+            history = history.push(
+                iced::widget::Row::new()
+                    .push(
+                        iced::widget::container(notification_text(
+                            notification,
+                            menu_style,
+                            row_text_size,
+                        ))
+                        .width(Length::Fill),
+                    )
+                    .push(
+                        iced::widget::button(
+                            iced::widget::text("X").size(row_text_size),
+                        )
+                        .on_press(
+                            Message::DismissNotification(notification.id),
+                        ),
+                    )
+                    .spacing(menu_style.spacing)
+                    .align_y(iced::Alignment::Center),
+            );
+        }
+        col = col.push(Scrollable::new(history).height(Length::Fixed(150.0)));
+    }
+
+    Some(
+        iced::widget::container(col.push(bar))
+            .width(Length::Fill)
+            .padding(6)
+            .style(|_theme| iced::widget::container::Style {
+                background: Some(iced::Background::Color(
+                    iced::Color::from_rgb(0.1, 0.1, 0.1),
+                )),
+                ..Default::default()
+            })
+            .into(),
+    )
+}
@@ -0,0 +1,105 @@
+//! Event subscriptions for the Playlist UI.
+//!
+//! Maps key presses that no widget handled (a focused text input keeps its
+//! own keys) to messages: Ctrl+Z (Cmd+Z on macOS) undoes the latest edit of
+//! the active playlist, and Ctrl+Shift+Z or Ctrl+Y redoes it. The held
+//! modifier keys are tracked so that clicking a playlist row can extend or
+//! toggle the selection. While a playlist row is being dragged, releasing
+//! the left mouse button anywhere drops it.
+//!
+//! Public API:
+//!     subscription — subscriptions wired into `main.rs`
+
+use crate::gui::{FileTreeApp, Message};
+use iced::keyboard::{self, Key, Modifiers};
+use iced::{Event, Subscription, event, mouse};
+
+/// Listens for the application's keyboard shortcuts and modifier keys, and
+/// for the end of a row drag while one is in progress.
+pub fn subscription(app: &FileTreeApp) -> Subscription<Message> {
+    let mut subscriptions = vec![
+        keyboard::on_key_press(shortcut),
+        event::listen_with(modifiers_changed),
+    ];
+    if app.right_panel_drag.is_some() {
+        subscriptions.push(event::listen_with(drag_release));
+    }
+    Subscription::batch(subscriptions)
+}
+
+/// The message for a key press, if it is a shortcut.
+fn shortcut(key: Key, modifiers: Modifiers) -> Option<Message> {
+    if !modifiers.command() {
+        return None;
+    }
+    match key.as_ref() {
+        Key::Character(c) if c.eq_ignore_ascii_case("z") => {
+            Some(if modifiers.shift() {
+                Message::RedoRightPanel
+            } else {
+                Message::UndoRightPanel
+            })
+        },
+        Key::Character(c) if c.eq_ignore_ascii_case("y") => {
+            Some(Message::RedoRightPanel)
+        },
+        _ => None,
+    }
+}
+
+/// Reports the held modifier keys whenever they change, even while a text
+/// input has focus.
+fn modifiers_changed(
+    event: Event,
+    _status: event::Status,
+    _window: iced::window::Id,
+) -> Option<Message> {
+    match event {
+        Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+            Some(Message::KeyboardModifiersChanged(modifiers))
+        },
+        _ => None,
+    }
+}
+
+/// Ends a row drag when the left mouse button is released, whether or not a
+/// widget handled the release.
+fn drag_release(
+    event: Event,
+    _status: event::Status,
+    _window: iced::window::Id,
+) -> Option<Message> {
+    match event {
+        Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+            Some(Message::RightPanelDragReleased)
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(c: &str, modifiers: Modifiers) -> Option<Message> {
+        shortcut(Key::Character(c.into()), modifiers)
+    }
+
+    #[test]
+    fn test_undo_redo_shortcuts() {
+        assert!(matches!(
+            press("z", Modifiers::COMMAND),
+            Some(Message::UndoRightPanel)
+        ));
+        assert!(matches!(
+            press("Z", Modifiers::COMMAND | Modifiers::SHIFT),
+            Some(Message::RedoRightPanel)
+        ));
+        assert!(matches!(
+            press("y", Modifiers::COMMAND),
+            Some(Message::RedoRightPanel)
+        ));
+        assert!(press("z", Modifiers::empty()).is_none());
+        assert!(press("x", Modifiers::COMMAND).is_none());
+    }
+}
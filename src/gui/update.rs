@@ -3,30 +3,54 @@
 //! Implements the pure state-transition function required by the iced
 //! Elm architecture. Each `Message` variant is handled by a corresponding
 //! arm that mutates `FileTreeApp` and returns an optional `Task` for
-//! side effects (file dialogs, exports, etc.).
+//! side effects (file dialogs, exports, etc.). Arms with side effects report
+//! their outcome to `FileTreeApp::notifications` for the status bar.
 //!
 //! Public API:
 //!     update — handle a message and transition the app state
+//!     read_restored_files_task — read restored tracks' metadata in the
+//!                                background
 //!     restore_expansion_state — walk a tree restoring expanded dirs
 //!     find_tag_node_mut — locate a tag tree node by label path
 //!     collect_tag_node_files — gather all file paths under a tag node
 
+use crate::fs::device_sync::{SyncProgress, sync_to_folder_stream};
 use crate::fs::file_tree::{FileNode, NodeType, scan_directory};
+use crate::fs::listing_export::export_listing_stream;
 use crate::fs::media_metadata::{
-    build_creator_tag_tree, build_genre_tag_tree, extract_media_metadata,
+    MediaMetadata, build_creator_tag_tree, build_genre_tag_tree,
+    extract_media_metadata, read_media_metadata,
 };
+use crate::fs::playlist_export::{
+    ExportProgress, PathRewriteRule, PlaylistFormat, PlaylistInfo,
+    PlaylistView, export_playlist_stream,
+};
+use crate::fs::playlist_import::ImportedTrack;
+use crate::fs::table_export::{TableColumn, export_table_stream};
+use crate::gui::columns::{
+    MAX_COLUMN_WIDTH, MIN_COLUMN_WIDTH, default_column_layout,
+};
+use crate::gui::history::History;
 use crate::gui::left_panel::{filter_file_node, filter_tag_node};
+use crate::gui::state::{DEFAULT_PLAYLIST_NAME, filter_terms};
 use crate::gui::tantivy_search::{
     build_tantivy_index, prune_file_tree, prune_tag_node,
 };
 use crate::gui::{
-    FileTreeApp, LeftPanelSelectMode, LeftPanelSortMode, Message,
-    RightPanelFile, SortColumn, SortOrder, TagTreeNode, TextSearchMode,
+    FileTreeApp, ImportedRightPanelPlaylist, LeftPanelSelectMode,
+    LeftPanelSortMode, Message, Playlist, RightPanelDrag, RightPanelFile,
+    RowMove, SortColumn, SortKey, SortOrder, TagTreeNode, TextSearchMode,
 };
+use crate::player::external::{PlayerProfile, launch_player};
+use crate::player::mpd::{MpdAction, send_to_mpd};
+use crate::utils::run_blocking;
 use iced::Task;
+use iced::futures::Stream;
 use rfd::FileDialog;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Recomputes `filtered_root_nodes` from `app.root_nodes` using the current
 /// search query and mode. Called whenever the query string or search mode
@@ -65,158 +89,415 @@ fn recompute_filtered_tag_nodes(app: &FileTreeApp) -> Vec<TagTreeNode> {
 /// current sort settings. The right panel is the cumulative playlist
 /// and is never filtered by the search query.
 fn displayed_right_panel_files(app: &FileTreeApp) -> Vec<RightPanelFile> {
-    let mut files = app.right_panel_files.clone();
-    if !app.right_panel_shuffled {
-        files.sort_by(|a, b| {
-            let filename_cmp = || {
-                let a_name = a
-                    .path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_ascii_lowercase();
-                let b_name = b
-                    .path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_ascii_lowercase();
-                a_name.cmp(&b_name)
-            };
-            match app.right_panel_sort_column {
-                SortColumn::Directory => {
-                    let a_dir = a
-                        .path
-                        .parent()
-                        .and_then(|p| p.file_name())
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_ascii_lowercase();
-                    let b_dir = b
-                        .path
-                        .parent()
-                        .and_then(|p| p.file_name())
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_ascii_lowercase();
-                    let primary =
-                        if app.right_panel_sort_order == SortOrder::Asc {
-                            a_dir.cmp(&b_dir)
-                        } else {
-                            b_dir.cmp(&a_dir)
-                        };
-                    primary.then_with(filename_cmp)
-                },
-                SortColumn::File => {
-                    let a_file = a
-                        .path
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_ascii_lowercase();
-                    let b_file = b
-                        .path
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_ascii_lowercase();
-                    if app.right_panel_sort_order == SortOrder::Asc {
-                        a_file.cmp(&b_file)
-                    } else {
-                        b_file.cmp(&a_file)
-                    }
-                },
-                SortColumn::Creator => {
-                    let a_creator = a
-                        .creator
-                        .as_deref()
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    let b_creator = b
-                        .creator
-                        .as_deref()
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    let primary =
-                        if app.right_panel_sort_order == SortOrder::Asc {
-                            a_creator.cmp(&b_creator)
-                        } else {
-                            b_creator.cmp(&a_creator)
-                        };
-                    primary.then_with(filename_cmp)
-                },
-                SortColumn::Album => {
-                    let a_album = a
-                        .album
-                        .as_deref()
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    let b_album = b
-                        .album
-                        .as_deref()
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    let primary =
-                        if app.right_panel_sort_order == SortOrder::Asc {
-                            a_album.cmp(&b_album)
-                        } else {
-                            b_album.cmp(&a_album)
-                        };
-                    primary.then_with(filename_cmp)
-                },
-                SortColumn::Title => {
-                    let a_title = a
-                        .title
-                        .as_deref()
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    let b_title = b
-                        .title
-                        .as_deref()
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    let primary =
-                        if app.right_panel_sort_order == SortOrder::Asc {
-                            a_title.cmp(&b_title)
-                        } else {
-                            b_title.cmp(&a_title)
-                        };
-                    primary.then_with(filename_cmp)
-                },
-                SortColumn::Genre => {
-                    let a_genre = a
-                        .genre
-                        .as_deref()
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    let b_genre = b
-                        .genre
-                        .as_deref()
-                        .unwrap_or_default()
-                        .to_ascii_lowercase();
-                    let primary =
-                        if app.right_panel_sort_order == SortOrder::Asc {
-                            a_genre.cmp(&b_genre)
-                        } else {
-                            b_genre.cmp(&a_genre)
-                        };
-                    primary.then_with(filename_cmp)
-                },
-                SortColumn::Duration => {
-                    let a_dur = a.duration_ms.unwrap_or(0);
-                    let b_dur = b.duration_ms.unwrap_or(0);
-                    let primary =
-                        if app.right_panel_sort_order == SortOrder::Asc {
-                            a_dur.cmp(&b_dur)
-                        } else {
-                            b_dur.cmp(&a_dur)
-                        };
-                    primary.then_with(filename_cmp)
-                },
+    app.sorted_right_panel_files()
+}
+
+/// Returns the displayed right-panel files whose extensions are among the
+/// application's audio extensions, in display order. Used by every playlist
+/// exporter so that non-audio files never end up in an exported playlist.
+fn displayed_audio_right_panel_files(app: &FileTreeApp) -> Vec<RightPanelFile> {
+    let audio_exts: &Vec<String> = &app.all_extensions;
+    displayed_right_panel_files(app)
+        .into_iter()
+        .filter(|f| {
+            f.path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|ext| audio_exts.iter().any(|ae| ae == ext))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Starts a background export of the displayed audio files to `path` in the
+/// given format, resetting the export status. Returns the stream of progress
+/// events to hand to `Task::run`.
+fn start_playlist_export(
+    app: &mut FileTreeApp,
+    format: PlaylistFormat,
+    path: PathBuf,
+) -> impl Stream<Item = ExportProgress> + use<> {
+    let files = displayed_audio_right_panel_files(app);
+    app.export_progress =
+        Some(ExportProgress::Running { done: 0, total: files.len() });
+    export_playlist_stream(
+        format,
+        files,
+        path,
+        app.export_options.clone(),
+        playlist_info(app),
+    )
+}
+
+/// Builds the playlist-level metadata written on export: the title, creator,
+/// and annotation the user entered, the current local time, and the right
+/// panel's sort state.
+fn playlist_info(app: &FileTreeApp) -> PlaylistInfo {
+    let non_empty =
+        |s: &str| (!s.trim().is_empty()).then(|| s.trim().to_string());
+    PlaylistInfo {
+        title: non_empty(&app.playlist().title),
+        creator: non_empty(&app.playlist().creator),
+        annotation: non_empty(&app.playlist().annotation),
+        date: Some(
+            chrono::Local::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        ),
+        view: Some(PlaylistView {
+            sort_column: app.playlist().sort_column,
+            sort_order: app.playlist().sort_order,
+            then_by: app.playlist().then_by.clone(),
+            shuffled: app.playlist().shuffled,
+        }),
+    }
+}
+
+/// Starts a background copy of the displayed audio files into `dest_dir`,
+/// saving the filename template and resetting the sync status. Returns the
+/// stream of progress events to hand to `Task::run`, or `None` while another
+/// sync is still running.
+fn start_device_sync(
+    app: &mut FileTreeApp,
+    dest_dir: PathBuf,
+) -> Option<impl Stream<Item = SyncProgress> + use<>> {
+    if app.sync_cancel.is_some() {
+        return None;
+    }
+    if let Err(e) = app.persist_sync_template() {
+        app.notifications.error(format!("Failed to save sync template: {e}"));
+    }
+    let files = displayed_audio_right_panel_files(app);
+    let cancel = Arc::new(AtomicBool::new(false));
+    app.sync_cancel = Some(cancel.clone());
+    app.sync_progress =
+        Some(SyncProgress::Running { done: 0, total: files.len() });
+    Some(sync_to_folder_stream(
+        files,
+        dest_dir,
+        app.sync_template.clone(),
+        cancel,
+    ))
+}
+
+/// Starts sending the displayed audio files to the MPD server, saving the
+/// server settings first. Returns a future resolving to `MpdFinished`, or
+/// `None` while another request is running or when saving a playlist
+/// without a name. The connection runs on its own thread, since it blocks.
+fn start_mpd_send(
+    app: &mut FileTreeApp,
+    action: MpdAction,
+) -> Option<impl Future<Output = Message> + use<>> {
+    if app.mpd_busy {
+        return None;
+    }
+    let action = match action {
+        MpdAction::SavePlaylist(name) => {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return None;
             }
-        });
+            MpdAction::SavePlaylist(name)
+        },
+        action => action,
+    };
+    if let Err(e) = app.persist_mpd_settings() {
+        app.notifications.error(format!("Failed to save MPD settings: {e}"));
+    }
+    app.mpd_busy = true;
+    let settings = app.mpd_settings.clone();
+    let files = displayed_audio_right_panel_files(app);
+    Some(run_blocking(move || {
+        let result =
+            send_to_mpd(&settings, &action, &files).map_err(|e| e.to_string());
+        Message::MpdFinished(action, result)
+    }))
+}
+
+/// Saves the right-panel playlist after an arm changed its tracks, their
+/// order, or how they are sorted, reporting a failure.
+fn right_panel_changed(app: &mut FileTreeApp) {
+    prune_right_panel_selection(app);
+    if let Err(e) = app.persist_playlists() {
+        app.notifications.error(format!("Failed to save playlist: {e}"));
+    }
+}
+
+/// Forgets selected tracks the panel no longer shows: ones the active
+/// playlist does not have, or that the filter hides. Bulk actions on the
+/// selection thus only reach the filtered rows.
+fn prune_right_panel_selection(app: &mut FileTreeApp) {
+    if app.right_panel_selection.is_empty() {
+        return;
+    }
+    let terms = filter_terms(&app.right_panel_filter);
+    let shown: HashSet<&PathBuf> = app.playlists[app.active_playlist]
+        .files
+        .iter()
+        .filter(|f| f.matches_filter(&terms))
+        .map(|f| &f.path)
+        .collect();
+    app.right_panel_selection.retain(|path| shown.contains(path));
+}
+
+/// Saves the playlist table's column layout after a change, reporting
+/// failures in the notification queue.
+fn playlist_columns_changed(app: &mut FileTreeApp) {
+    if let Err(e) = app.persist_playlist_columns() {
+        app.notifications.error(format!("Failed to save column layout: {e}"));
+    }
+}
+
+/// Sorts the active playlist by `column`, reversing the order when it is
+/// already sorted by that column, and ends any shuffle. Sorting by another
+/// column drops the further sort keys.
+///
+/// With Shift held, `column` becomes a further sort key instead: it is added
+/// ascending, then made descending, then removed again. Shuffled and
+/// manually ordered playlists have no sort to add keys to, so they are
+/// sorted by `column` alone.
+fn sort_right_panel_by(app: &mut FileTreeApp, column: SortColumn) {
+    let add_key = app.keyboard_modifiers.shift();
+    let playlist = app.playlist_mut();
+    let sorted =
+        !playlist.shuffled && playlist.sort_column != SortColumn::Manual;
+    if add_key && sorted && playlist.sort_column != column {
+        let position = playlist.then_by.iter().position(|k| k.column == column);
+        match position {
+            None => {
+                playlist.then_by.push(SortKey { column, order: SortOrder::Asc })
+            },
+            Some(i) if playlist.then_by[i].order == SortOrder::Asc => {
+                playlist.then_by[i].order = SortOrder::Desc;
+            },
+            Some(i) => {
+                playlist.then_by.remove(i);
+            },
+        }
+    } else if playlist.sort_column == column {
+        playlist.sort_order = match playlist.sort_order {
+            SortOrder::Asc => SortOrder::Desc,
+            SortOrder::Desc => SortOrder::Asc,
+        };
+    } else {
+        playlist.sort_column = column;
+        playlist.sort_order = SortOrder::Asc;
+        playlist.then_by.clear();
+    }
+    playlist.shuffled = false;
+    right_panel_changed(app);
+}
+
+/// Switches the active playlist to manual order, keeping its tracks in the
+/// order they are displayed in, so rows can be moved from there.
+fn freeze_manual_order(app: &mut FileTreeApp) {
+    let files = displayed_right_panel_files(app);
+    let playlist = app.playlist_mut();
+    playlist.files = files;
+    playlist.sort_column = SortColumn::Manual;
+    playlist.sort_order = SortOrder::Asc;
+    playlist.then_by.clear();
+    playlist.shuffled = false;
+}
+
+/// Updates the right-panel selection for a click on the row of `path`,
+/// using the modifier keys held: shift selects the displayed range from the
+/// anchor (added to the selection with Ctrl/Cmd), Ctrl/Cmd toggles the row,
+/// and a plain click selects only this row.
+fn select_right_panel_file(app: &mut FileTreeApp, path: PathBuf) {
+    let modifiers = app.keyboard_modifiers;
+    if modifiers.shift()
+        && let Some(anchor) = &app.right_panel_selection_anchor
+    {
+        let files = app.filtered_right_panel_files();
+        let from = files.iter().position(|f| &f.path == anchor);
+        let to = files.iter().position(|f| f.path == path);
+        if let (Some(from), Some(to)) = (from, to) {
+            if !modifiers.command() {
+                app.right_panel_selection.clear();
+            }
+            app.right_panel_selection.extend(
+                files[from.min(to)..=from.max(to)]
+                    .iter()
+                    .map(|f| f.path.clone()),
+            );
+            return;
+        }
+    }
+    if modifiers.command() {
+        if !app.right_panel_selection.remove(&path) {
+            app.right_panel_selection.insert(path.clone());
+        }
+    } else {
+        app.right_panel_selection = HashSet::from([path.clone()]);
+    }
+    app.right_panel_selection_anchor = Some(path);
+}
+
+/// Indices of the tracks of `files` that `filter` shows, in order.
+fn visible_indices(files: &[RightPanelFile], filter: &str) -> Vec<usize> {
+    let terms = filter_terms(filter);
+    (0..files.len()).filter(|&i| files[i].matches_filter(&terms)).collect()
+}
+
+/// Moves the selected tracks of `files` as a block, keeping their relative
+/// order: one row up or down, or to the top or bottom. Up and down step
+/// over the rows `filter` shows, leaving hidden rows where they are.
+fn move_selected_files(
+    files: &mut [RightPanelFile],
+    selection: &HashSet<PathBuf>,
+    filter: &str,
+    row_move: RowMove,
+) {
+    let selected = |file: &RightPanelFile| selection.contains(&file.path);
+    let visible = visible_indices(files, filter);
+    match row_move {
+        // Sorting is stable, so both groups keep their order
+        RowMove::ToTop => files.sort_by_key(|f| !selected(f)),
+        RowMove::ToBottom => files.sort_by_key(selected),
+        RowMove::Up => {
+            for pair in visible.windows(2) {
+                let (above, below) = (pair[0], pair[1]);
+                if selected(&files[below]) && !selected(&files[above]) {
+                    files.swap(above, below);
+                }
+            }
+        },
+        RowMove::Down => {
+            for pair in visible.windows(2).rev() {
+                let (above, below) = (pair[0], pair[1]);
+                if selected(&files[above]) && !selected(&files[below]) {
+                    files.swap(above, below);
+                }
+            }
+        },
+    }
+}
+
+/// Builds a right-panel entry for the given file, reading its creator, album,
+/// title, genre, duration, and the remaining fields written by playlist
+/// exports from the file's media metadata. Exports use these cached values
+/// rather than re-reading every file.
+pub(crate) fn right_panel_file_from_path(path: PathBuf) -> RightPanelFile {
+    let meta = extract_media_metadata(&path);
+    right_panel_file_from_metadata(path, meta)
+}
+
+/// Reads the metadata of restored playlist tracks, without saving cover
+/// art, marking the tracks whose files are gone as `missing`.
+fn read_restored_files(paths: Vec<PathBuf>) -> Vec<RightPanelFile> {
+    paths
+        .into_iter()
+        .map(|path| {
+            if path.is_file() {
+                let meta = read_media_metadata(&path, false);
+                right_panel_file_from_metadata(path, meta)
+            } else {
+                RightPanelFile { path, missing: true, ..Default::default() }
+            }
+        })
+        .collect()
+}
+
+/// Every track path of every playlist, each once.
+fn restored_paths(app: &FileTreeApp) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    app.playlists
+        .iter()
+        .flat_map(|playlist| &playlist.files)
+        .filter(|file| seen.insert(file.path.clone()))
+        .map(|file| file.path.clone())
+        .collect()
+}
+
+/// Starts reading the metadata of every track of the restored playlists on
+/// a background thread; `RestoredFilesRead` fills in the rows, which show
+/// only their paths until then.
+pub fn read_restored_files_task(app: &FileTreeApp) -> Task<Message> {
+    let paths = restored_paths(app);
+    if paths.is_empty() {
+        return Task::none();
+    }
+    Task::perform(
+        run_blocking(move || read_restored_files(paths)),
+        Message::RestoredFilesRead,
+    )
+}
+
+/// Builds a right-panel entry for the given file from its media metadata.
+fn right_panel_file_from_metadata(
+    path: PathBuf,
+    meta: MediaMetadata,
+) -> RightPanelFile {
+    RightPanelFile {
+        path,
+        creator: meta.creator,
+        album: meta.album,
+        title: meta.title,
+        genre: meta.genre,
+        duration_ms: meta.duration_ms,
+        track_num: meta.track_num,
+        identifier: meta.identifier,
+        annotation: meta.annotation,
+        image_uri: meta.image_uri,
+        disc_num: meta.disc_num,
+        year: meta.year,
+        album_artist: meta.album_artist,
+        bitrate_kbps: meta.bitrate_kbps,
+        sample_rate_hz: meta.sample_rate_hz,
+        channels: meta.channels,
+        bit_depth: meta.bit_depth,
+        codec: meta.codec,
+        file_size: meta.file_size,
+        missing: false,
     }
-    files
+}
+
+/// Builds a right-panel entry for an imported playlist track. The file's own
+/// tags take precedence; the playlist's hints fill in fields the tags leave
+/// empty (e.g. untagged files listed with `#EXTINF` titles).
+fn right_panel_file_from_import(track: ImportedTrack) -> RightPanelFile {
+    let mut file = right_panel_file_from_path(track.path);
+    file.creator = file.creator.or(track.creator);
+    file.album = file.album.or(track.album);
+    file.title = file.title.or(track.title);
+    file.duration_ms = file.duration_ms.or(track.duration_ms);
+    file
+}
+
+/// Reads the playlist file at `path` and the metadata of every track it
+/// lists, skipping repeated entries. Runs on a background thread, since it
+/// reads the tags of each file.
+fn read_imported_playlist(
+    path: &Path,
+) -> Result<ImportedRightPanelPlaylist, String> {
+    let imported = crate::fs::playlist_import::import_playlist(path)
+        .map_err(|e| e.to_string())?;
+    let mut seen = HashSet::new();
+    let files = imported
+        .found
+        .into_iter()
+        .filter(|track| seen.insert(track.path.clone()))
+        .map(right_panel_file_from_import)
+        .collect();
+    Ok(ImportedRightPanelPlaylist {
+        files,
+        missing: imported.missing,
+        info: imported.info,
+    })
+}
+
+/// Opens `path` with the desktop's default application for its type.
+fn open_with_default_app(path: &Path) -> std::io::Result<std::process::Child> {
+    use std::process::Command;
+
+    #[cfg(target_os = "windows")]
+    return Command::new("cmd").args(["/C", "start", ""]).arg(path).spawn();
+
+    #[cfg(target_os = "macos")]
+    return Command::new("open").arg(path).spawn();
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    Command::new("xdg-open").arg(path).spawn()
 }
 
 /// Restores the expansion state of a file tree node and its descendants based
@@ -300,7 +581,53 @@ pub fn collect_tag_node_files(node: &TagTreeNode, files: &mut Vec<PathBuf>) {
 /// Handles all application state updates in response to user actions or
 /// messages, modifying the `FileTreeApp` state and returning an optional
 /// asynchronous task.
+///
+/// Edits of the active playlist are recorded in its undo history when they
+/// change its tracks or sorting.
 pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
+    if !edits_playlist(&message) {
+        return apply(app, message);
+    }
+    let active = app.active_playlist;
+    let before = app.playlist().state();
+    let task = apply(app, message);
+    if app.active_playlist == active
+        && let Some(playlist) = app.playlists.get_mut(active)
+        && playlist.state() != before
+    {
+        playlist.history.record(before);
+    }
+    task
+}
+
+/// Whether `message` edits the active playlist's tracks, sorting, or
+/// details, and so can be undone.
+fn edits_playlist(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::AddTagNodeToRightPanel(_)
+            | Message::AddToRightPanel(_)
+            | Message::AddDirectoryToRightPanel(_)
+            | Message::RemoveFromRightPanel(_)
+            | Message::RemoveDirectoryFromRightPanel(_)
+            | Message::SortRightPanelBy(_)
+            | Message::ShuffleRightPanel
+            | Message::SortRightPanelManually
+            | Message::MoveRightPanelFile(..)
+            | Message::RightPanelDragReleased
+            | Message::RemoveSelectedFromRightPanel
+            | Message::KeepOnlySelectedInRightPanel
+            | Message::MoveSelectedRightPanelFiles(_)
+            | Message::RightPanelPlaylistImported(..)
+            | Message::ClearRightPanel
+            | Message::RemoveMissingFromRightPanel
+            | Message::SavePlaylistDetails
+            | Message::AddRandomTagNodeToRightPanel(_)
+            | Message::AddRandomDirectoryToRightPanel(_)
+    )
+}
+
+fn apply(app: &mut FileTreeApp, message: Message) -> Task<Message> {
     match message {
         Message::ToggleExpansion(path) => {
             if app.expanded_dirs.contains(&path) {
@@ -363,7 +690,9 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
                 app.top_dirs.remove(idx);
                 app.root_nodes.remove(idx);
                 if let Err(e) = app.persist_top_dirs() {
-                    log::error!("Failed to persist top dirs: {e}");
+                    app.notifications.error(format!(
+                        "Failed to save the directory list: {e}"
+                    ));
                 }
                 app.tantivy_index = Some(build_tantivy_index(&app.root_nodes));
                 if !app.search_query.is_empty() {
@@ -396,7 +725,9 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
                         .collect::<Vec<_>>(),
                 ));
                 if let Err(e) = app.persist_top_dirs() {
-                    log::error!("Failed to persist top dirs: {e}");
+                    app.notifications.error(format!(
+                        "Failed to save the directory list: {e}"
+                    ));
                 }
                 app.tantivy_index = Some(build_tantivy_index(&app.root_nodes));
                 if !app.search_query.is_empty() {
@@ -405,27 +736,34 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
                     app.filtered_root_nodes = app.root_nodes.clone();
                     app.filtered_tag_tree_roots = app.tag_tree_roots.clone();
                 }
+                app.notifications
+                    .info(format!("Added directory {}", path.display()));
+            } else if app.top_dirs.contains(&path) {
+                app.notifications.warning(format!(
+                    "Directory {} is already in the list",
+                    path.display()
+                ));
+            } else {
+                app.notifications.error(format!(
+                    "Cannot add {}: not an existing directory",
+                    path.display()
+                ));
             }
             Task::none()
         },
         Message::DirectoryAdded(None) => Task::none(),
         Message::AddToRightPanel(path) => {
-            app.right_panel_shuffled = false;
-            if !app.right_panel_files.iter().any(|f| f.path == path) {
-                let meta = extract_media_metadata(&path);
-                app.right_panel_files.push(RightPanelFile {
-                    path,
-                    creator: meta.creator,
-                    album: meta.album,
-                    title: meta.title,
-                    genre: meta.genre,
-                    duration_ms: meta.duration_ms,
-                });
+            app.playlist_mut().shuffled = false;
+            if !app.playlist().files.iter().any(|f| f.path == path) {
+                app.playlist_mut().files.push(right_panel_file_from_path(path));
             }
+            right_panel_changed(app);
             Task::none()
         },
         Message::AddDirectoryToRightPanel(dir_path) => {
-            app.right_panel_shuffled = false;
+            // Borrow the playlist by field so the trees can be walked
+            let playlist = &mut app.playlists[app.active_playlist];
+            playlist.shuffled = false;
             for root in app.root_nodes.iter().flatten() {
                 if let Some(node) = find_node_by_path(root, &dir_path) {
                     let mut files = Vec::new();
@@ -435,288 +773,988 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
                         files.retain(|f| matches.contains(f));
                     }
                     for file in files {
-                        if !app.right_panel_files.iter().any(|f| f.path == file)
-                        {
-                            let meta = extract_media_metadata(&file);
-                            app.right_panel_files.push(RightPanelFile {
-                                path: file,
-                                creator: meta.creator,
-                                album: meta.album,
-                                title: meta.title,
-                                genre: meta.genre,
-                                duration_ms: meta.duration_ms,
-                            });
+                        if !playlist.files.iter().any(|f| f.path == file) {
+                            playlist
+                                .files
+                                .push(right_panel_file_from_path(file));
                         }
                     }
                 }
             }
+            right_panel_changed(app);
             Task::none()
         },
         Message::RemoveFromRightPanel(path) => {
-            app.right_panel_files.retain(|f| f.path != path);
+            app.playlist_mut().files.retain(|f| f.path != path);
+            right_panel_changed(app);
             Task::none()
         },
         Message::RemoveDirectoryFromRightPanel(dir_path) => {
-            app.right_panel_files.retain(|file| {
+            app.playlist_mut().files.retain(|file| {
                 // Remove if file is not in dir_path or its subdirectories
                 !file.path.starts_with(&dir_path)
             });
+            right_panel_changed(app);
             Task::none()
         },
-        Message::SortRightPanelByDirectory => {
-            if app.right_panel_sort_column == SortColumn::Directory {
-                app.right_panel_sort_order = match app.right_panel_sort_order {
-                    SortOrder::Asc => SortOrder::Desc,
-                    SortOrder::Desc => SortOrder::Asc,
-                };
-            } else {
-                app.right_panel_sort_column = SortColumn::Directory;
-                app.right_panel_sort_order = SortOrder::Asc;
-            }
-            app.right_panel_shuffled = false;
+        Message::SortRightPanelBy(column) => {
+            sort_right_panel_by(app, column);
             Task::none()
         },
-        Message::SortRightPanelByFile => {
-            if app.right_panel_sort_column == SortColumn::File {
-                app.right_panel_sort_order = match app.right_panel_sort_order {
-                    SortOrder::Asc => SortOrder::Desc,
-                    SortOrder::Desc => SortOrder::Asc,
-                };
-            } else {
-                app.right_panel_sort_column = SortColumn::File;
-                app.right_panel_sort_order = SortOrder::Asc;
-            }
-            app.right_panel_shuffled = false;
+        Message::ShuffleRightPanel => {
+            use rand::seq::SliceRandom;
+            let mut rng = rand::rng();
+            app.playlist_mut().files.shuffle(&mut rng);
+            app.playlist_mut().shuffled = true;
+            right_panel_changed(app);
             Task::none()
         },
-        Message::SortRightPanelByCreator => {
-            if app.right_panel_sort_column == SortColumn::Creator {
-                app.right_panel_sort_order = match app.right_panel_sort_order {
-                    SortOrder::Asc => SortOrder::Desc,
-                    SortOrder::Desc => SortOrder::Asc,
-                };
-            } else {
-                app.right_panel_sort_column = SortColumn::Creator;
-                app.right_panel_sort_order = SortOrder::Asc;
+        Message::SortRightPanelManually => {
+            freeze_manual_order(app);
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::MoveRightPanelFile(path, row_move) => {
+            freeze_manual_order(app);
+            let filter = app.right_panel_filter.clone();
+            let files = &mut app.playlist_mut().files;
+            if let Some(from) = files.iter().position(|f| f.path == path) {
+                // Up and down pass the neighbouring row the filter shows
+                let visible = visible_indices(files, &filter);
+                let to = match row_move {
+                    RowMove::Up => {
+                        visible.iter().rev().find(|&&i| i < from).copied()
+                    },
+                    RowMove::Down => {
+                        visible.iter().find(|&&i| i > from).copied()
+                    },
+                    RowMove::ToTop => Some(0),
+                    RowMove::ToBottom => Some(files.len() - 1),
+                }
+                .unwrap_or(from);
+                let file = files.remove(from);
+                files.insert(to, file);
             }
-            app.right_panel_shuffled = false;
+            right_panel_changed(app);
             Task::none()
         },
-        Message::SortRightPanelByAlbum => {
-            if app.right_panel_sort_column == SortColumn::Album {
-                app.right_panel_sort_order = match app.right_panel_sort_order {
-                    SortOrder::Asc => SortOrder::Desc,
-                    SortOrder::Desc => SortOrder::Asc,
-                };
-            } else {
-                app.right_panel_sort_column = SortColumn::Album;
-                app.right_panel_sort_order = SortOrder::Asc;
+        Message::RightPanelDragStarted(path) => {
+            app.right_panel_drag =
+                Some(RightPanelDrag { dragged: path, target: None });
+            Task::none()
+        },
+        Message::RightPanelDragEntered(path) => {
+            if let Some(drag) = &mut app.right_panel_drag {
+                drag.target = Some(path);
             }
-            app.right_panel_shuffled = false;
             Task::none()
         },
-        Message::SortRightPanelByTitle => {
-            if app.right_panel_sort_column == SortColumn::Title {
-                app.right_panel_sort_order = match app.right_panel_sort_order {
-                    SortOrder::Asc => SortOrder::Desc,
-                    SortOrder::Desc => SortOrder::Asc,
-                };
-            } else {
-                app.right_panel_sort_column = SortColumn::Title;
-                app.right_panel_sort_order = SortOrder::Asc;
+        Message::RightPanelDragReleased => {
+            // Dropping a row on another one moves it to that row's place
+            if let Some(drag) = app.right_panel_drag.take()
+                && let Some(target) = drag.target
+                && target != drag.dragged
+            {
+                freeze_manual_order(app);
+                let files = &mut app.playlist_mut().files;
+                let from = files.iter().position(|f| f.path == drag.dragged);
+                let to = files.iter().position(|f| f.path == target);
+                if let (Some(from), Some(to)) = (from, to) {
+                    let file = files.remove(from);
+                    files.insert(to, file);
+                }
+                right_panel_changed(app);
             }
-            app.right_panel_shuffled = false;
             Task::none()
         },
-        Message::SortRightPanelByGenre => {
-            if app.right_panel_sort_column == SortColumn::Genre {
-                app.right_panel_sort_order = match app.right_panel_sort_order {
-                    SortOrder::Asc => SortOrder::Desc,
-                    SortOrder::Desc => SortOrder::Asc,
-                };
+        Message::ExportRightPanelAsXspf => Task::future(async move {
+            rfd::FileDialog::new()
+                .set_file_name("playlist.xspf")
+                .add_filter("XSPF playlist", &["xspf"])
+                .save_file()
+        })
+        .and_then(|path| Task::done(Message::ExportRightPanelAsXspfTo(path))),
+        Message::ExportRightPanelAsXspfTo(path) => Task::run(
+            start_playlist_export(app, PlaylistFormat::Xspf, path),
+            Message::PlaylistExportProgress,
+        ),
+        Message::ExportRightPanelAsJspf => Task::future(async move {
+            rfd::FileDialog::new()
+                .set_file_name("playlist.jspf")
+                .add_filter("JSPF playlist", &["jspf"])
+                .save_file()
+        })
+        .and_then(|path| Task::done(Message::ExportRightPanelAsJspfTo(path))),
+        Message::ExportRightPanelAsJspfTo(path) => Task::run(
+            start_playlist_export(app, PlaylistFormat::Jspf, path),
+            Message::PlaylistExportProgress,
+        ),
+        Message::ExportRightPanelAsM3u => Task::future(async move {
+            rfd::FileDialog::new()
+                .set_file_name("playlist.m3u8")
+                .add_filter("M3U8 playlist", &["m3u8", "m3u"])
+                .save_file()
+        })
+        .and_then(|path| Task::done(Message::ExportRightPanelAsM3uTo(path))),
+        Message::ExportRightPanelAsM3uTo(path) => Task::run(
+            start_playlist_export(app, PlaylistFormat::M3u, path),
+            Message::PlaylistExportProgress,
+        ),
+        Message::ToggleTableExportMenu => {
+            app.table_export_menu_expanded = !app.table_export_menu_expanded;
+            Task::none()
+        },
+        Message::ToggleTableExportColumn(column) => {
+            if app.table_export_columns.contains(&column) {
+                app.table_export_columns.retain(|c| *c != column);
             } else {
-                app.right_panel_sort_column = SortColumn::Genre;
-                app.right_panel_sort_order = SortOrder::Asc;
+                app.table_export_columns.push(column);
+            }
+            // Keep the columns in the order they are written
+            app.table_export_columns = TableColumn::ALL
+                .into_iter()
+                .filter(|c| app.table_export_columns.contains(c))
+                .collect();
+            if let Err(e) = app.persist_table_export_columns() {
+                app.notifications
+                    .error(format!("Failed to save table export columns: {e}"));
             }
-            app.right_panel_shuffled = false;
             Task::none()
         },
-        Message::SortRightPanelByDuration => {
-            if app.right_panel_sort_column == SortColumn::Duration {
-                app.right_panel_sort_order = match app.right_panel_sort_order {
-                    SortOrder::Asc => SortOrder::Desc,
-                    SortOrder::Desc => SortOrder::Asc,
-                };
-            } else {
-                app.right_panel_sort_column = SortColumn::Duration;
-                app.right_panel_sort_order = SortOrder::Asc;
+        Message::ExportRightPanelAsTable(format) => Task::future(async move {
+            rfd::FileDialog::new()
+                .set_file_name(format!("playlist.{}", format.extension()))
+                .add_filter(format.name(), &[format.extension()])
+                .save_file()
+        })
+        .and_then(move |path| {
+            Task::done(Message::ExportRightPanelAsTableTo(format, path))
+        }),
+        Message::ExportRightPanelAsTableTo(format, path) => {
+            if app.table_export_columns.is_empty() {
+                app.notifications
+                    .warning("No columns selected for table export");
+                return Task::none();
+            }
+            let files = displayed_right_panel_files(app);
+            app.export_progress =
+                Some(ExportProgress::Running { done: 0, total: files.len() });
+            Task::run(
+                export_table_stream(
+                    files,
+                    app.table_export_columns.clone(),
+                    format,
+                    path,
+                ),
+                Message::PlaylistExportProgress,
+            )
+        },
+        Message::ExportRightPanelAsListing(format) => {
+            Task::future(async move {
+                rfd::FileDialog::new()
+                    .set_file_name(format!("playlist.{}", format.extension()))
+                    .add_filter(format.name(), &[format.extension()])
+                    .save_file()
+            })
+            .and_then(move |path| {
+                Task::done(Message::ExportRightPanelAsListingTo(format, path))
+            })
+        },
+        Message::ExportRightPanelAsListingTo(format, path) => {
+            let files = displayed_right_panel_files(app);
+            app.export_progress =
+                Some(ExportProgress::Running { done: 0, total: files.len() });
+            Task::run(
+                export_listing_stream(files, format, path),
+                Message::PlaylistExportProgress,
+            )
+        },
+        Message::ExportRightPanelAsPls => Task::future(async move {
+            rfd::FileDialog::new()
+                .set_file_name("playlist.pls")
+                .add_filter("PLS playlist", &["pls"])
+                .save_file()
+        })
+        .and_then(|path| Task::done(Message::ExportRightPanelAsPlsTo(path))),
+        Message::ExportRightPanelAsPlsTo(path) => Task::run(
+            start_playlist_export(app, PlaylistFormat::Pls, path),
+            Message::PlaylistExportProgress,
+        ),
+        Message::PlayRightPanel => {
+            let Some(profile) = app.selected_player_profile().cloned() else {
+                app.notifications.error("No player profile is configured");
+                return Task::none();
+            };
+            // Temp playlists never move, so their locations stay absolute
+            let audio_files = displayed_audio_right_panel_files(app);
+            match launch_player(&profile, &audio_files, &playlist_info(app)) {
+                Ok(_) => app.notifications.info(format!(
+                    "Playing {} tracks in {}",
+                    audio_files.len(),
+                    profile.name
+                )),
+                Err(e) => app
+                    .notifications
+                    .error(format!("Failed to start {}: {e}", profile.name)),
             }
-            app.right_panel_shuffled = false;
             Task::none()
         },
-        Message::ShuffleRightPanel => {
-            use rand::seq::SliceRandom;
-            let mut rng = rand::rng();
-            app.right_panel_files.shuffle(&mut rng);
-            app.right_panel_shuffled = true;
+        Message::TogglePlayerMenu => {
+            app.player_menu_expanded = !app.player_menu_expanded;
             Task::none()
         },
-        Message::ExportRightPanelAsXspf => {
-            Task::perform(
-                async move {
-                    rfd::FileDialog::new()
-                        .set_file_name("playlist.xspf")
-                        .save_file()
-                },
-                |opt| match opt {
-                    Some(path) => Message::ExportRightPanelAsXspfTo(path),
-                    None => Message::ToggleExtensionsMenu, // no-op or feedback
-                },
-            )
+        Message::SelectPlayerProfile(name) => {
+            app.selected_player = name;
+            if let Err(e) = app.persist_player_profiles() {
+                app.notifications
+                    .error(format!("Failed to save player profiles: {e}"));
+            }
+            Task::none()
         },
-        Message::ExportRightPanelAsXspfTo(path) => {
-            let audio_exts: &Vec<String> = &app.all_extensions;
-            let audio_files: Vec<RightPanelFile> =
-                displayed_right_panel_files(app)
-                    .into_iter()
-                    .filter(|f| {
-                        f.path
-                            .extension()
-                            .and_then(|e| e.to_str())
-                            .map(|ext| audio_exts.iter().any(|ae| ae == ext))
-                            .unwrap_or(false)
-                    })
-                    .collect();
-            let _ = crate::fs::xspf::export_xspf_playlist(&audio_files, &path);
-            Task::none()
-        },
-        Message::ExportAndPlayRightPanelAsXspf => {
-            use std::env::temp_dir;
-            use std::process::Command;
-
-            let audio_exts: &Vec<String> = &app.all_extensions;
-            let audio_files: Vec<RightPanelFile> =
-                displayed_right_panel_files(app)
-                    .into_iter()
-                    .filter(|f| {
-                        f.path
-                            .extension()
-                            .and_then(|e| e.to_str())
-                            .map(|ext| audio_exts.iter().any(|ae| ae == ext))
-                            .unwrap_or(false)
-                    })
-                    .collect();
-
-            let xspf_path = temp_dir().join("playlist.xspf");
-            let _ =
-                crate::fs::xspf::export_xspf_playlist(&audio_files, &xspf_path);
-
-            // Launch VLC with the playlist
-            let _ =
-                Command::new("vlc").arg(xspf_path.to_str().unwrap()).spawn();
-
+        Message::PlayerNameChanged(name) => {
+            app.player_draft.name = name;
             Task::none()
         },
-        Message::OpenRightPanelFile(path) => {
-            #[cfg(target_os = "windows")]
-            let _ = std::process::Command::new("cmd")
-                .args(&["/C", "start", "", path.to_str().unwrap()])
-                .spawn();
-
-            #[cfg(target_os = "macos")]
-            let _ = std::process::Command::new("open")
-                .arg(path.to_str().unwrap())
-                .spawn();
-
-            #[cfg(target_os = "linux")]
-            let _ = std::process::Command::new("xdg-open")
-                .arg(path.to_str().unwrap())
-                .spawn();
-
+        Message::PlayerExecutableChanged(executable) => {
+            app.player_draft.executable = executable;
             Task::none()
         },
-        Message::ToggleLeftPanel => {
-            app.left_panel_expanded = !app.left_panel_expanded;
+        Message::PlayerArgsChanged(args) => {
+            app.player_draft.args = args;
             Task::none()
         },
-        Message::ToggleLeftPanelSortMode => {
-            app.left_panel_sort_mode = match app.left_panel_sort_mode {
-                LeftPanelSortMode::Alphanumeric => {
-                    LeftPanelSortMode::ModifiedDate
-                },
-                LeftPanelSortMode::ModifiedDate => LeftPanelSortMode::FileCount,
-                LeftPanelSortMode::FileCount => LeftPanelSortMode::Alphanumeric,
+        Message::SelectPlayerFormat(format) => {
+            app.player_draft.format = format;
+            Task::none()
+        },
+        Message::AddPlayerProfile => {
+            let profile = PlayerProfile {
+                name: app.player_draft.name.trim().to_string(),
+                executable: app.player_draft.executable.trim().to_string(),
+                args: app.player_draft.args.trim().to_string(),
+                format: app.player_draft.format,
             };
+            if profile.name.is_empty() || profile.executable.is_empty() {
+                return Task::none();
+            }
+            // A profile with the same name is replaced, keeping its position
+            match app
+                .player_profiles
+                .iter_mut()
+                .find(|p| p.name == profile.name)
+            {
+                Some(existing) => *existing = profile.clone(),
+                None => app.player_profiles.push(profile.clone()),
+            }
+            app.selected_player = profile.name;
+            app.player_draft = PlayerProfile::default();
+            if let Err(e) = app.persist_player_profiles() {
+                app.notifications
+                    .error(format!("Failed to save player profiles: {e}"));
+            }
             Task::none()
         },
-        Message::SearchCleared => {
-            app.search_query = String::new();
-            app.last_search_matches = None;
-            app.filtered_root_nodes = app.root_nodes.clone();
-            app.filtered_tag_tree_roots = app.tag_tree_roots.clone();
+        Message::RemovePlayerProfile(name) => {
+            app.player_profiles.retain(|p| p.name != name);
+            if app.selected_player == name {
+                app.selected_player = app
+                    .player_profiles
+                    .first()
+                    .map(|p| p.name.clone())
+                    .unwrap_or_default();
+            }
+            if let Err(e) = app.persist_player_profiles() {
+                app.notifications
+                    .error(format!("Failed to save player profiles: {e}"));
+            }
             Task::none()
         },
-        Message::SearchQueryChanged(query) => {
-            app.search_query = query;
-            if app.search_query.is_empty() {
-                app.last_search_matches = None;
-                app.filtered_root_nodes = app.root_nodes.clone();
-                app.filtered_tag_tree_roots = app.tag_tree_roots.clone();
-            } else {
-                app.perform_search();
+        Message::SelectPlaylist(index) => {
+            if index < app.playlists.len() {
+                app.active_playlist = index;
+                app.right_panel_selection.clear();
+                app.right_panel_selection_anchor = None;
+                app.reset_playlist_drafts();
+                right_panel_changed(app);
             }
             Task::none()
         },
-        Message::ToggleSearchMode => {
-            app.search_mode = match app.search_mode {
-                TextSearchMode::All => TextSearchMode::DirectoryPath,
-                TextSearchMode::DirectoryPath => TextSearchMode::TrackFilename,
-                TextSearchMode::TrackFilename => TextSearchMode::Creator,
-                TextSearchMode::Creator => TextSearchMode::Album,
-                TextSearchMode::Album => TextSearchMode::Title,
-                TextSearchMode::Title => TextSearchMode::Genre,
-                TextSearchMode::Genre => TextSearchMode::All,
-            };
-            if !app.search_query.is_empty() {
-                app.perform_search();
+        Message::NewPlaylist => {
+            let name = app.unique_playlist_name(DEFAULT_PLAYLIST_NAME);
+            app.playlists.push(Playlist::new(name.clone()));
+            app.active_playlist = app.playlists.len() - 1;
+            app.reset_playlist_drafts();
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::DuplicatePlaylist => {
+            let mut copy = app.playlist().clone();
+            copy.history = History::default();
+            copy.name =
+                app.unique_playlist_name(&format!("{} copy", copy.name));
+            // The copy opens next to the original
+            app.active_playlist += 1;
+            app.playlists.insert(app.active_playlist, copy);
+            app.reset_playlist_drafts();
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::DeletePlaylist => {
+            app.playlists.remove(app.active_playlist);
+            if app.playlists.is_empty() {
+                app.playlists.push(Playlist::new(DEFAULT_PLAYLIST_NAME));
             }
+            app.active_playlist =
+                app.active_playlist.min(app.playlists.len() - 1);
+            app.reset_playlist_drafts();
+            right_panel_changed(app);
             Task::none()
         },
-        Message::ToggleLeftPanelSelectMode => {
-            app.left_panel_selection_mode = match app.left_panel_selection_mode
+        Message::PlaylistNameDraftChanged(name) => {
+            app.playlist_name_draft = name;
+            Task::none()
+        },
+        Message::RenamePlaylist => {
+            let name = app.playlist_name_draft.trim().to_string();
+            let taken = app
+                .playlists
+                .iter()
+                .enumerate()
+                .any(|(i, p)| i != app.active_playlist && p.name == name);
+            if name.is_empty() {
+                app.playlist_name_draft = app.playlist().name.clone();
+            } else if taken {
+                app.notifications.warning(format!(
+                    "A playlist named \"{name}\" already exists"
+                ));
+            } else {
+                app.playlist_mut().name = name.clone();
+                app.playlist_name_draft = name;
+                right_panel_changed(app);
+            }
+            Task::none()
+        },
+        Message::ToggleMpdMenu => {
+            app.mpd_menu_expanded = !app.mpd_menu_expanded;
+            Task::none()
+        },
+        Message::MpdHostChanged(host) => {
+            app.mpd_settings.host = host;
+            Task::none()
+        },
+        Message::MpdPortChanged(new_text) => {
+            if new_text.is_empty() {
+                // Allow empty input so the user can clear and retype
+                app.mpd_port_input = new_text;
+            } else if let Ok(port) = new_text.parse::<u16>()
+                && port > 0
             {
-                LeftPanelSelectMode::Directory => {
-                    // Switch to tag mode: load from sled if possible
-                    if let Some(ref sled_store) = app.sled_store {
-                        if let Some(tree) = sled_store.load_genre_tag_tree() {
-                            app.tag_tree_roots = tree;
-                        } else {
-                            let tree = build_genre_tag_tree(
-                                &app.top_dirs,
-                                &app.selected_extensions,
-                            );
-                            sled_store.save_genre_tag_tree(&tree).ok();
-                            app.tag_tree_roots = tree;
-                        }
-                    } else {
-                        app.tag_tree_roots = build_genre_tag_tree(
-                            &app.top_dirs,
-                            &app.selected_extensions,
-                        );
+                app.mpd_settings.port = port;
+                app.mpd_port_input = new_text;
+            } else {
+                // Not a valid port — revert
+                app.mpd_port_input = app.mpd_settings.port.to_string();
+            }
+            Task::none()
+        },
+        Message::MpdPasswordChanged(password) => {
+            app.mpd_settings.password = password;
+            Task::none()
+        },
+        Message::MpdMusicDirectoryChanged(directory) => {
+            app.mpd_settings.music_directory = directory;
+            Task::none()
+        },
+        Message::MpdPlaylistNameChanged(name) => {
+            app.mpd_playlist_name = name;
+            Task::none()
+        },
+        Message::SendToMpd(action) => match start_mpd_send(app, action) {
+            Some(request) => Task::future(request),
+            None => Task::none(),
+        },
+        Message::MpdFinished(action, result) => {
+            app.mpd_busy = false;
+            match result {
+                Ok(summary) => {
+                    if !summary.skipped.is_empty() {
+                        app.notifications.warning(format!(
+                            "{} tracks are outside the MPD music directory \
+                             and were not sent",
+                            summary.skipped.len()
+                        ));
                     }
-                    LeftPanelSelectMode::GenreTag
+                    app.notifications.info(match action {
+                        MpdAction::ReplaceQueue => format!(
+                            "Replaced the MPD queue with {} tracks",
+                            summary.sent
+                        ),
+                        MpdAction::AppendToQueue => format!(
+                            "Added {} tracks to the MPD queue",
+                            summary.sent
+                        ),
+                        MpdAction::SavePlaylist(name) => format!(
+                            "Saved {} tracks as MPD playlist \"{name}\"",
+                            summary.sent
+                        ),
+                    });
                 },
-                LeftPanelSelectMode::GenreTag => {
-                    // Switch to creator mode: load from sled if possible
-                    if let Some(ref sled_store) = app.sled_store {
-                        if let Some(tree) = sled_store.load_creator_tag_tree() {
-                            app.tag_tree_roots = tree;
+                Err(e) => app.notifications.error(format!(
+                    "Failed to send to MPD at {}:{}: {e}",
+                    app.mpd_settings.host, app.mpd_settings.port
+                )),
+            }
+            Task::none()
+        },
+        Message::TogglePlaylistInfoMenu => {
+            app.playlist_info_menu_expanded = !app.playlist_info_menu_expanded;
+            // Closing the menu keeps what was typed, as Enter does
+            if app.playlist_info_menu_expanded {
+                Task::none()
+            } else {
+                Task::done(Message::SavePlaylistDetails)
+            }
+        },
+        Message::PlaylistTitleChanged(title) => {
+            app.playlist_title_draft = title;
+            Task::none()
+        },
+        Message::PlaylistCreatorChanged(creator) => {
+            app.playlist_creator_draft = creator;
+            Task::none()
+        },
+        Message::PlaylistAnnotationChanged(annotation) => {
+            app.playlist_annotation_draft = annotation;
+            Task::none()
+        },
+        Message::SavePlaylistDetails => {
+            let title = app.playlist_title_draft.clone();
+            let creator = app.playlist_creator_draft.clone();
+            let annotation = app.playlist_annotation_draft.clone();
+            let playlist = app.playlist_mut();
+            if playlist.title != title
+                || playlist.creator != creator
+                || playlist.annotation != annotation
+            {
+                playlist.title = title;
+                playlist.creator = creator;
+                playlist.annotation = annotation;
+                right_panel_changed(app);
+            }
+            Task::none()
+        },
+        Message::PlaylistExportProgress(progress) => {
+            match &progress {
+                ExportProgress::Running { .. } => {},
+                ExportProgress::Finished(path) => {
+                    app.notifications.info(format!("Saved {}", path.display()))
+                },
+                ExportProgress::Failed(message) => {
+                    app.notifications.error(message.clone())
+                },
+            }
+            app.export_progress = Some(progress);
+            Task::none()
+        },
+        Message::ToggleDeviceSyncMenu => {
+            app.sync_menu_expanded = !app.sync_menu_expanded;
+            Task::none()
+        },
+        Message::SyncTemplateChanged(template) => {
+            app.sync_template = template;
+            Task::none()
+        },
+        Message::SyncRightPanelToFolder => Task::future(async move {
+            rfd::FileDialog::new()
+                .set_title("Copy tracks to folder")
+                .pick_folder()
+        })
+        .and_then(|path| Task::done(Message::SyncRightPanelToFolderAt(path))),
+        Message::SyncRightPanelToFolderAt(path) => {
+            match start_device_sync(app, path) {
+                Some(stream) => Task::run(stream, Message::DeviceSyncProgress),
+                None => Task::none(),
+            }
+        },
+        Message::DeviceSyncProgress(progress) => {
+            match &progress {
+                SyncProgress::Running { .. } => {},
+                SyncProgress::Finished(summary) => {
+                    for (path, error) in &summary.failed {
+                        app.notifications.error(format!(
+                            "Failed to copy {}: {error}",
+                            path.display()
+                        ));
+                    }
+                    let outcome = if summary.cancelled {
+                        "Copy cancelled"
+                    } else {
+                        "Copy finished"
+                    };
+                    app.notifications.info(format!(
+                        "{outcome}: {} copied, {} already up to date",
+                        summary.copied, summary.skipped
+                    ));
+                    app.sync_cancel = None;
+                },
+                SyncProgress::Failed(message) => {
+                    app.notifications.error(message.clone());
+                    app.sync_cancel = None;
+                },
+            }
+            app.sync_progress = Some(progress);
+            Task::none()
+        },
+        Message::CancelDeviceSync => {
+            if let Some(cancel) = &app.sync_cancel {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            Task::none()
+        },
+        Message::ToggleExportRelativePaths => {
+            app.export_options.relative_paths =
+                !app.export_options.relative_paths;
+            Task::none()
+        },
+        Message::TogglePathRewriteMenu => {
+            app.path_rewrite_menu_expanded = !app.path_rewrite_menu_expanded;
+            Task::none()
+        },
+        Message::SelectPathRewriteRule(name) => {
+            app.export_options.rewrite_rule = name.and_then(|name| {
+                app.path_rewrite_rules.iter().find(|r| r.name == name).cloned()
+            });
+            Task::none()
+        },
+        Message::PathRewriteNameChanged(name) => {
+            app.path_rewrite_draft.name = name;
+            Task::none()
+        },
+        Message::PathRewriteSourceChanged(source) => {
+            app.path_rewrite_draft.source_prefix = source;
+            Task::none()
+        },
+        Message::PathRewriteTargetChanged(target) => {
+            app.path_rewrite_draft.target_prefix = target;
+            Task::none()
+        },
+        Message::TogglePathRewriteWindowsSeparators => {
+            app.path_rewrite_draft.windows_separators =
+                !app.path_rewrite_draft.windows_separators;
+            Task::none()
+        },
+        Message::AddPathRewriteRule => {
+            let rule = PathRewriteRule {
+                name: app.path_rewrite_draft.name.trim().to_string(),
+                source_prefix: app
+                    .path_rewrite_draft
+                    .source_prefix
+                    .trim()
+                    .to_string(),
+                target_prefix: app
+                    .path_rewrite_draft
+                    .target_prefix
+                    .trim()
+                    .to_string(),
+                windows_separators: app.path_rewrite_draft.windows_separators,
+            };
+            if rule.name.is_empty() || rule.source_prefix.is_empty() {
+                return Task::none();
+            }
+            // A rule with the same name is replaced, keeping its position
+            match app
+                .path_rewrite_rules
+                .iter_mut()
+                .find(|r| r.name == rule.name)
+            {
+                Some(existing) => *existing = rule.clone(),
+                None => app.path_rewrite_rules.push(rule.clone()),
+            }
+            if app
+                .export_options
+                .rewrite_rule
+                .as_ref()
+                .is_some_and(|selected| selected.name == rule.name)
+            {
+                app.export_options.rewrite_rule = Some(rule);
+            }
+            app.path_rewrite_draft = PathRewriteRule::default();
+            if let Err(e) = app.persist_path_rewrite_rules() {
+                app.notifications
+                    .error(format!("Failed to save path rewrite rules: {e}"));
+            }
+            Task::none()
+        },
+        Message::RemovePathRewriteRule(name) => {
+            app.path_rewrite_rules.retain(|r| r.name != name);
+            if app
+                .export_options
+                .rewrite_rule
+                .as_ref()
+                .is_some_and(|selected| selected.name == name)
+            {
+                app.export_options.rewrite_rule = None;
+            }
+            if let Err(e) = app.persist_path_rewrite_rules() {
+                app.notifications
+                    .error(format!("Failed to save path rewrite rules: {e}"));
+            }
+            Task::none()
+        },
+        Message::ImportRightPanelPlaylist => Task::perform(
+            async move {
+                rfd::FileDialog::new()
+                    .add_filter(
+                        "Playlists",
+                        &["xspf", "jspf", "m3u", "m3u8", "pls"],
+                    )
+                    .pick_file()
+            },
+            Message::ImportRightPanelPlaylistFrom,
+        ),
+        Message::ImportRightPanelPlaylistFrom(Some(path)) => Task::perform(
+            run_blocking({
+                let path = path.clone();
+                move || read_imported_playlist(&path)
+            }),
+            move |result| {
+                Message::RightPanelPlaylistImported(path.clone(), result)
+            },
+        ),
+        Message::ImportRightPanelPlaylistFrom(None) => Task::none(),
+        Message::RightPanelPlaylistImported(path, Ok(imported)) => {
+            for missing in &imported.missing {
+                log::warn!("Playlist entry not found: {}", missing.display());
+            }
+            if imported.missing.is_empty() {
+                app.notifications.info(format!(
+                    "Opened {} ({} tracks)",
+                    path.display(),
+                    imported.files.len()
+                ));
+            } else {
+                app.notifications.warning(format!(
+                    "Opened {} ({} tracks, {} not found)",
+                    path.display(),
+                    imported.files.len(),
+                    imported.missing.len()
+                ));
+            }
+            let info = imported.info;
+            let playlist = app.playlist_mut();
+            playlist.files = imported.files;
+            playlist.missing_files = imported.missing;
+            playlist.title = info.title.unwrap_or_default();
+            playlist.creator = info.creator.unwrap_or_default();
+            playlist.annotation = info.annotation.unwrap_or_default();
+            match info.view {
+                // Restore the ordering the playlist was saved with
+                Some(view) => {
+                    playlist.sort_column = view.sort_column;
+                    playlist.sort_order = view.sort_order;
+                    playlist.then_by = view.then_by;
+                    playlist.shuffled = view.shuffled;
+                },
+                // Keep the playlist's own track order rather than re-sorting
+                // it by the current column
+                None => {
+                    playlist.sort_column = SortColumn::Manual;
+                    playlist.sort_order = SortOrder::Asc;
+                    playlist.then_by.clear();
+                    playlist.shuffled = false;
+                },
+            }
+            app.reset_playlist_drafts();
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::RightPanelPlaylistImported(path, Err(e)) => {
+            app.notifications.error(format!(
+                "Failed to import playlist {}: {e}",
+                path.display()
+            ));
+            Task::none()
+        },
+        Message::DismissMissingPlaylistFiles => {
+            app.playlist_mut().missing_files.clear();
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::KeyboardModifiersChanged(modifiers) => {
+            app.keyboard_modifiers = modifiers;
+            Task::none()
+        },
+        Message::SelectRightPanelFile(path) => {
+            select_right_panel_file(app, path);
+            Task::none()
+        },
+        Message::ClearRightPanelSelection => {
+            app.right_panel_selection.clear();
+            app.right_panel_selection_anchor = None;
+            Task::none()
+        },
+        Message::RightPanelFilterChanged(filter) => {
+            app.right_panel_filter = filter;
+            prune_right_panel_selection(app);
+            Task::none()
+        },
+        Message::RightPanelFilterCleared => {
+            app.right_panel_filter.clear();
+            Task::none()
+        },
+        Message::SelectAllRightPanelFiles => {
+            app.right_panel_selection = app
+                .filtered_right_panel_files()
+                .into_iter()
+                .map(|f| f.path)
+                .collect();
+            app.right_panel_selection_anchor = None;
+            Task::none()
+        },
+        Message::RestoredFilesRead(files) => {
+            let files: HashMap<PathBuf, RightPanelFile> =
+                files.into_iter().map(|f| (f.path.clone(), f)).collect();
+            for playlist in &mut app.playlists {
+                for file in &mut playlist.files {
+                    if let Some(read) = files.get(&file.path) {
+                        *file = read.clone();
+                    }
+                }
+            }
+            let not_found = files.values().filter(|f| f.missing).count();
+            if not_found > 0 {
+                app.notifications.warning(format!(
+                    "Restored {} playlists ({not_found} tracks not found)",
+                    app.playlists.len(),
+                ));
+            }
+            Task::none()
+        },
+        Message::RemoveMissingFromRightPanel => {
+            app.playlist_mut().files.retain(|f| !f.missing);
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::RemoveSelectedFromRightPanel => {
+            let selection = std::mem::take(&mut app.right_panel_selection);
+            app.playlist_mut().files.retain(|f| !selection.contains(&f.path));
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::KeepOnlySelectedInRightPanel => {
+            // Rows the filter hides are not shown as unselected, so they stay
+            let selection = &app.right_panel_selection;
+            let terms = filter_terms(&app.right_panel_filter);
+            app.playlists[app.active_playlist].files.retain(|f| {
+                selection.contains(&f.path) || !f.matches_filter(&terms)
+            });
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::MoveSelectedRightPanelFiles(row_move) => {
+            if !app.right_panel_selection.is_empty() {
+                freeze_manual_order(app);
+                move_selected_files(
+                    &mut app.playlists[app.active_playlist].files,
+                    &app.right_panel_selection,
+                    &app.right_panel_filter,
+                    row_move,
+                );
+                right_panel_changed(app);
+            }
+            Task::none()
+        },
+        Message::CopySelectedToPlaylist(index) => {
+            // Copied in the order they are displayed
+            let files: Vec<RightPanelFile> = displayed_right_panel_files(app)
+                .into_iter()
+                .filter(|f| app.right_panel_selection.contains(&f.path))
+                .collect();
+            if index != app.active_playlist
+                && let Some(target) = app.playlists.get_mut(index)
+            {
+                // The copy is an edit of the target playlist, undone there
+                let before = target.state();
+                for file in files {
+                    if !target.files.iter().any(|f| f.path == file.path) {
+                        target.files.push(file);
+                    }
+                }
+                let added = target.files.len() - before.files.len();
+                if added > 0 {
+                    target.history.record(before);
+                }
+                app.notifications.info(format!(
+                    "Copied {added} tracks to {}",
+                    app.playlists[index].name
+                ));
+                right_panel_changed(app);
+            }
+            Task::none()
+        },
+        Message::ToggleColumnsMenu => {
+            app.columns_menu_expanded = !app.columns_menu_expanded;
+            Task::none()
+        },
+        Message::TogglePlaylistColumn(column) => {
+            let visible = app.playlist_columns.iter().filter(|e| e.visible);
+            // The last visible column stays
+            let is_last = visible.count() == 1;
+            if let Some(entry) =
+                app.playlist_columns.iter_mut().find(|e| e.column == column)
+                && !(entry.visible && is_last)
+            {
+                entry.visible = !entry.visible;
+                playlist_columns_changed(app);
+            }
+            Task::none()
+        },
+        Message::MovePlaylistColumnLeft(column) => {
+            if let Some(index) =
+                app.playlist_columns.iter().position(|e| e.column == column)
+                && index > 0
+            {
+                app.playlist_columns.swap(index - 1, index);
+                playlist_columns_changed(app);
+            }
+            Task::none()
+        },
+        Message::MovePlaylistColumnRight(column) => {
+            if let Some(index) =
+                app.playlist_columns.iter().position(|e| e.column == column)
+                && index + 1 < app.playlist_columns.len()
+            {
+                app.playlist_columns.swap(index, index + 1);
+                playlist_columns_changed(app);
+            }
+            Task::none()
+        },
+        Message::NarrowPlaylistColumn(column) => {
+            if let Some(entry) =
+                app.playlist_columns.iter_mut().find(|e| e.column == column)
+                && entry.width > MIN_COLUMN_WIDTH
+            {
+                entry.width -= 1;
+                playlist_columns_changed(app);
+            }
+            Task::none()
+        },
+        Message::WidenPlaylistColumn(column) => {
+            if let Some(entry) =
+                app.playlist_columns.iter_mut().find(|e| e.column == column)
+                && entry.width < MAX_COLUMN_WIDTH
+            {
+                entry.width += 1;
+                playlist_columns_changed(app);
+            }
+            Task::none()
+        },
+        Message::ResetPlaylistColumns => {
+            app.playlist_columns = default_column_layout();
+            playlist_columns_changed(app);
+            Task::none()
+        },
+        Message::OpenRightPanelFile(path) => {
+            if let Err(e) = open_with_default_app(&path) {
+                app.notifications
+                    .error(format!("Failed to open {}: {e}", path.display()));
+            }
+            Task::none()
+        },
+        Message::ToggleLeftPanel => {
+            app.left_panel_expanded = !app.left_panel_expanded;
+            Task::none()
+        },
+        Message::ToggleLeftPanelSortMode => {
+            app.left_panel_sort_mode = match app.left_panel_sort_mode {
+                LeftPanelSortMode::Alphanumeric => {
+                    LeftPanelSortMode::ModifiedDate
+                },
+                LeftPanelSortMode::ModifiedDate => LeftPanelSortMode::FileCount,
+                LeftPanelSortMode::FileCount => LeftPanelSortMode::Alphanumeric,
+            };
+            Task::none()
+        },
+        Message::SearchCleared => {
+            app.search_query = String::new();
+            app.last_search_matches = None;
+            app.filtered_root_nodes = app.root_nodes.clone();
+            app.filtered_tag_tree_roots = app.tag_tree_roots.clone();
+            Task::none()
+        },
+        Message::SearchQueryChanged(query) => {
+            app.search_query = query;
+            if app.search_query.is_empty() {
+                app.last_search_matches = None;
+                app.filtered_root_nodes = app.root_nodes.clone();
+                app.filtered_tag_tree_roots = app.tag_tree_roots.clone();
+            } else {
+                app.perform_search();
+            }
+            Task::none()
+        },
+        Message::ToggleSearchMode => {
+            app.search_mode = match app.search_mode {
+                TextSearchMode::All => TextSearchMode::DirectoryPath,
+                TextSearchMode::DirectoryPath => TextSearchMode::TrackFilename,
+                TextSearchMode::TrackFilename => TextSearchMode::Creator,
+                TextSearchMode::Creator => TextSearchMode::Album,
+                TextSearchMode::Album => TextSearchMode::Title,
+                TextSearchMode::Title => TextSearchMode::Genre,
+                TextSearchMode::Genre => TextSearchMode::All,
+            };
+            if !app.search_query.is_empty() {
+                app.perform_search();
+            }
+            Task::none()
+        },
+        Message::ToggleLeftPanelSelectMode => {
+            app.left_panel_selection_mode = match app.left_panel_selection_mode
+            {
+                LeftPanelSelectMode::Directory => {
+                    // Switch to tag mode: load from sled if possible
+                    if let Some(ref sled_store) = app.sled_store {
+                        if let Some(tree) = sled_store.load_genre_tag_tree() {
+                            app.tag_tree_roots = tree;
+                        } else {
+                            let tree = build_genre_tag_tree(
+                                &app.top_dirs,
+                                &app.selected_extensions,
+                            );
+                            if let Err(e) =
+                                sled_store.save_genre_tag_tree(&tree)
+                            {
+                                app.notifications.warning(format!(
+                                    "Failed to cache the genre tag tree: {e}"
+                                ));
+                            }
+                            app.tag_tree_roots = tree;
+                        }
+                    } else {
+                        app.tag_tree_roots = build_genre_tag_tree(
+                            &app.top_dirs,
+                            &app.selected_extensions,
+                        );
+                    }
+                    LeftPanelSelectMode::GenreTag
+                },
+                LeftPanelSelectMode::GenreTag => {
+                    // Switch to creator mode: load from sled if possible
+                    if let Some(ref sled_store) = app.sled_store {
+                        if let Some(tree) = sled_store.load_creator_tag_tree() {
+                            app.tag_tree_roots = tree;
                         } else {
                             let tree = build_creator_tag_tree(
                                 &app.top_dirs,
                                 &app.selected_extensions,
                             );
-                            sled_store.save_creator_tag_tree(&tree).ok();
+                            if let Err(e) =
+                                sled_store.save_creator_tag_tree(&tree)
+                            {
+                                app.notifications.warning(format!(
+                                    "Failed to cache the creator tag tree: {e}"
+                                ));
+                            }
                             app.tag_tree_roots = tree;
                         }
                     } else {
@@ -780,12 +1818,32 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
             Task::none()
         },
         Message::ClearRightPanel => {
-            app.right_panel_files.clear();
-            app.right_panel_shuffled = false;
+            app.playlist_mut().files.clear();
+            app.playlist_mut().missing_files.clear();
+            app.playlist_mut().shuffled = false;
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::UndoRightPanel => {
+            let playlist = app.playlist_mut();
+            if let Some(previous) = playlist.history.undo(playlist.state()) {
+                playlist.set_state(previous);
+                app.reset_playlist_drafts();
+                right_panel_changed(app);
+            }
+            Task::none()
+        },
+        Message::RedoRightPanel => {
+            let playlist = app.playlist_mut();
+            if let Some(next) = playlist.history.redo(playlist.state()) {
+                playlist.set_state(next);
+                app.reset_playlist_drafts();
+                right_panel_changed(app);
+            }
             Task::none()
         },
         Message::AddTagNodeToRightPanel(path) => {
-            app.right_panel_shuffled = false;
+            app.playlist_mut().shuffled = false;
             if let Some(node) =
                 find_tag_node_mut(&mut app.tag_tree_roots, &path)
             {
@@ -796,19 +1854,14 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
                     files.retain(|f| matches.contains(f));
                 }
                 for file in files {
-                    if !app.right_panel_files.iter().any(|f| f.path == file) {
-                        let meta = extract_media_metadata(&file);
-                        app.right_panel_files.push(RightPanelFile {
-                            path: file,
-                            creator: meta.creator,
-                            album: meta.album,
-                            title: meta.title,
-                            genre: meta.genre,
-                            duration_ms: meta.duration_ms,
-                        });
+                    if !app.playlist().files.iter().any(|f| f.path == file) {
+                        app.playlist_mut()
+                            .files
+                            .push(right_panel_file_from_path(file));
                     }
                 }
             }
+            right_panel_changed(app);
             Task::none()
         },
         Message::RandomCountChanged(new_text) => {
@@ -830,7 +1883,7 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
             Task::none()
         },
         Message::AddRandomTagNodeToRightPanel(path) => {
-            app.right_panel_shuffled = false;
+            app.playlist_mut().shuffled = false;
             if let Some(node) =
                 find_tag_node_mut(&mut app.tag_tree_roots, &path)
             {
@@ -849,23 +1902,19 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
                     files.truncate(n);
                 }
                 for file in files {
-                    if !app.right_panel_files.iter().any(|f| f.path == file) {
-                        let meta = extract_media_metadata(&file);
-                        app.right_panel_files.push(RightPanelFile {
-                            path: file,
-                            creator: meta.creator,
-                            album: meta.album,
-                            title: meta.title,
-                            genre: meta.genre,
-                            duration_ms: meta.duration_ms,
-                        });
+                    if !app.playlist().files.iter().any(|f| f.path == file) {
+                        app.playlist_mut()
+                            .files
+                            .push(right_panel_file_from_path(file));
                     }
                 }
             }
+            right_panel_changed(app);
             Task::none()
         },
         Message::AddRandomDirectoryToRightPanel(dir_path) => {
-            app.right_panel_shuffled = false;
+            let playlist = &mut app.playlists[app.active_playlist];
+            playlist.shuffled = false;
             for root in app.root_nodes.iter().flatten() {
                 if let Some(node) = find_node_by_path(root, &dir_path) {
                     let mut files = Vec::new();
@@ -883,21 +1932,31 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
                         files.truncate(n);
                     }
                     for file in files {
-                        if !app.right_panel_files.iter().any(|f| f.path == file)
-                        {
-                            let meta = extract_media_metadata(&file);
-                            app.right_panel_files.push(RightPanelFile {
-                                path: file,
-                                creator: meta.creator,
-                                album: meta.album,
-                                title: meta.title,
-                                genre: meta.genre,
-                                duration_ms: meta.duration_ms,
-                            });
+                        if !playlist.files.iter().any(|f| f.path == file) {
+                            playlist
+                                .files
+                                .push(right_panel_file_from_path(file));
                         }
                     }
                 }
             }
+            right_panel_changed(app);
+            Task::none()
+        },
+        Message::DismissNotification(id) => {
+            app.notifications.dismiss(id);
+            if app.notifications.is_empty() {
+                app.notification_log_expanded = false;
+            }
+            Task::none()
+        },
+        Message::ClearNotifications => {
+            app.notifications.clear();
+            app.notification_log_expanded = false;
+            Task::none()
+        },
+        Message::ToggleNotificationLog => {
+            app.notification_log_expanded = !app.notification_log_expanded;
             Task::none()
         },
     }
@@ -906,9 +1965,27 @@ pub fn update(app: &mut FileTreeApp, message: Message) -> Task<Message> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::sled_store::SledStore;
+    use crate::fs::table_export::TableFormat;
     use crate::gui::TextSearchMode;
+    use crate::gui::notifications::NotificationLevel;
+    use crate::player::external::default_profiles;
     use std::path::PathBuf;
 
+    /// Reads the playlist file at `path` as `ImportRightPanelPlaylistFrom`
+    /// does in the background, and applies the result.
+    fn import_into_right_panel(app: &mut FileTreeApp, path: PathBuf) {
+        let result = read_imported_playlist(&path);
+        let _ = update(app, Message::RightPanelPlaylistImported(path, result));
+    }
+
+    /// Reads the restored tracks' metadata as `read_restored_files_task`
+    /// does in the background, and applies it.
+    fn read_restored(app: &mut FileTreeApp) {
+        let files = read_restored_files(restored_paths(app));
+        let _ = update(app, Message::RestoredFilesRead(files));
+    }
+
     #[test]
     fn test_search_query_changed() {
         let mut app = FileTreeApp::new(
@@ -1211,7 +2288,7 @@ mod tests {
             &mut app,
             Message::AddToRightPanel(PathBuf::from("/music/song_a.mp3")),
         );
-        assert_eq!(app.right_panel_files.len(), 1);
+        assert_eq!(app.playlist().files.len(), 1);
 
         // Activate a search — this should NOT affect right_panel_files
         let _ = update(
@@ -1221,7 +2298,7 @@ mod tests {
 
         // The file should still be in the playlist
         assert_eq!(
-            app.right_panel_files.len(),
+            app.playlist().files.len(),
             1,
             "playlist should retain all files despite search"
         );
@@ -1269,12 +2346,12 @@ mod tests {
         let _ = update(&mut app, msg);
 
         assert_eq!(
-            app.right_panel_files.len(),
+            app.playlist().files.len(),
             2,
             "only search-matching files should be added"
         );
-        assert!(app.right_panel_files.iter().any(|f| f.path == file_a));
-        assert!(app.right_panel_files.iter().any(|f| f.path == file_b));
+        assert!(app.playlist().files.iter().any(|f| f.path == file_a));
+        assert!(app.playlist().files.iter().any(|f| f.path == file_b));
     }
 
     /// Without an active search, AddDirectoryToRightPanel should add all
@@ -1309,7 +2386,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         assert_eq!(
-            app.right_panel_files.len(),
+            app.playlist().files.len(),
             2,
             "all files should be added when no search is active"
         );
@@ -1346,8 +2423,8 @@ mod tests {
         let msg = Message::AddRandomDirectoryToRightPanel(dir_path);
         let _ = update(&mut app, msg);
 
-        assert_eq!(app.right_panel_files.len(), 2);
-        assert!(!app.right_panel_shuffled);
+        assert_eq!(app.playlist().files.len(), 2);
+        assert!(!app.playlist().shuffled);
     }
 
     #[test]
@@ -1378,7 +2455,7 @@ mod tests {
         let msg = Message::AddRandomDirectoryToRightPanel(dir_path);
         let _ = update(&mut app, msg);
 
-        assert_eq!(app.right_panel_files.len(), 2);
+        assert_eq!(app.playlist().files.len(), 2);
     }
 
     #[test]
@@ -1417,7 +2494,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         // After filtering, only 2 files remain, and N=5 exceeds 2, so both
-        assert_eq!(app.right_panel_files.len(), 2);
+        assert_eq!(app.playlist().files.len(), 2);
     }
 
     #[test]
@@ -1451,7 +2528,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         // No search filter — all 3 files pass through, N=5 exceeds count
-        assert_eq!(app.right_panel_files.len(), 3);
+        assert_eq!(app.playlist().files.len(), 3);
     }
 
     #[test]
@@ -1476,13 +2553,13 @@ mod tests {
 
         // Add file_a first via AddToRightPanel
         let _ = update(&mut app, Message::AddToRightPanel(file_a.clone()));
-        assert_eq!(app.right_panel_files.len(), 1);
+        assert_eq!(app.playlist().files.len(), 1);
 
         let msg = Message::AddRandomDirectoryToRightPanel(dir_path);
         let _ = update(&mut app, msg);
 
         // Should still be 1 — no duplicates added
-        assert_eq!(app.right_panel_files.len(), 1);
+        assert_eq!(app.playlist().files.len(), 1);
     }
 
     #[test]
@@ -1509,7 +2586,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         // n = min(0, 1) = 0, so no files should be added
-        assert_eq!(app.right_panel_files.len(), 0);
+        assert_eq!(app.playlist().files.len(), 0);
     }
 
     // ── AddTagNodeToRightPanel search-filter tests ──────────────────
@@ -1562,12 +2639,12 @@ mod tests {
         let _ = update(&mut app, msg);
 
         assert_eq!(
-            app.right_panel_files.len(),
+            app.playlist().files.len(),
             2,
             "only search-matching tracks should be added"
         );
-        assert!(app.right_panel_files.iter().any(|f| f.path == track_1));
-        assert!(app.right_panel_files.iter().any(|f| f.path == track_2));
+        assert!(app.playlist().files.iter().any(|f| f.path == track_1));
+        assert!(app.playlist().files.iter().any(|f| f.path == track_2));
     }
 
     /// Without an active search, AddTagNodeToRightPanel should add all
@@ -1614,7 +2691,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         assert_eq!(
-            app.right_panel_files.len(),
+            app.playlist().files.len(),
             2,
             "all tracks should be added when no search is active"
         );
@@ -1654,7 +2731,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         assert_eq!(
-            app.right_panel_files.len(),
+            app.playlist().files.len(),
             0,
             "zero files should be added when search matches nothing"
         );
@@ -1677,7 +2754,7 @@ mod tests {
             &mut app,
             Message::AddToRightPanel(PathBuf::from("/music/song.mp3")),
         );
-        assert_eq!(app.right_panel_files.len(), 1);
+        assert_eq!(app.playlist().files.len(), 1);
 
         // Pre-search state: last_search_matches is None (no tantivy search
         // has run yet). displayed_right_panel_files should still return all
@@ -1845,8 +2922,8 @@ mod tests {
         let msg = Message::AddRandomTagNodeToRightPanel(path);
         let _ = update(&mut app, msg);
 
-        assert_eq!(app.right_panel_files.len(), 2);
-        assert!(!app.right_panel_shuffled);
+        assert_eq!(app.playlist().files.len(), 2);
+        assert!(!app.playlist().shuffled);
     }
 
     #[test]
@@ -1879,7 +2956,7 @@ mod tests {
         let msg = Message::AddRandomTagNodeToRightPanel(path);
         let _ = update(&mut app, msg);
 
-        assert_eq!(app.right_panel_files.len(), 2);
+        assert_eq!(app.playlist().files.len(), 2);
     }
 
     #[test]
@@ -1929,7 +3006,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         // After filtering, only 2 files remain, N=5 exceeds 2, so both
-        assert_eq!(app.right_panel_files.len(), 2);
+        assert_eq!(app.playlist().files.len(), 2);
     }
 
     #[test]
@@ -1973,7 +3050,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         // No filter — all 2 files added, N=5 exceeds count
-        assert_eq!(app.right_panel_files.len(), 2);
+        assert_eq!(app.playlist().files.len(), 2);
     }
 
     #[test]
@@ -2003,14 +3080,14 @@ mod tests {
 
         // Add track first via AddToRightPanel
         let _ = update(&mut app, Message::AddToRightPanel(track.clone()));
-        assert_eq!(app.right_panel_files.len(), 1);
+        assert_eq!(app.playlist().files.len(), 1);
 
         let path = vec!["Genre".to_string(), "Jazz".to_string()];
         let msg = Message::AddRandomTagNodeToRightPanel(path);
         let _ = update(&mut app, msg);
 
         // Should still be 1 — no duplicates
-        assert_eq!(app.right_panel_files.len(), 1);
+        assert_eq!(app.playlist().files.len(), 1);
     }
 
     #[test]
@@ -2043,7 +3120,7 @@ mod tests {
         let _ = update(&mut app, msg);
 
         // n = min(0, 1) = 0, so no files should be added
-        assert_eq!(app.right_panel_files.len(), 0);
+        assert_eq!(app.playlist().files.len(), 0);
     }
 
     // ── RandomCountChanged validation tests ───────────────────────────────
@@ -2164,4 +3241,1981 @@ mod tests {
         assert_eq!(app.random_count, 4);
         assert_eq!(app.random_count_input, "4");
     }
+
+    // ── Playlist export tests ─────────────────────────────────────────────
+
+    /// Runs an export the way the `Export...To` arms do, driving the progress
+    /// stream to completion and feeding each event back through `update`.
+    fn run_playlist_export(
+        app: &mut FileTreeApp,
+        format: PlaylistFormat,
+        path: PathBuf,
+    ) {
+        use iced::futures::StreamExt;
+        let stream = start_playlist_export(app, format, path);
+        let events: Vec<ExportProgress> =
+            iced::futures::executor::block_on(stream.collect());
+        for event in events {
+            let _ = update(app, Message::PlaylistExportProgress(event));
+        }
+    }
+
+    /// Exports build every entry from the cached right-panel metadata and
+    /// record the result as the export status.
+    #[test]
+    fn test_export_uses_cached_metadata_and_reports_result() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        // The file does not exist, so its metadata can only come from the
+        // right-panel entry
+        app.playlist_mut().files.push(RightPanelFile {
+            path: PathBuf::from("/music/missing.mp3"),
+            title: Some("Cached Title".to_string()),
+            track_num: Some(7),
+            annotation: Some("Cached note".to_string()),
+            ..Default::default()
+        });
+
+        let out_file = tempfile::NamedTempFile::new().unwrap();
+        let path = out_file.path().to_path_buf();
+        run_playlist_export(&mut app, PlaylistFormat::Xspf, path.clone());
+
+        let xml = std::fs::read_to_string(&path).unwrap();
+        assert!(xml.contains("<title>Cached Title</title>"));
+        assert!(xml.contains("<trackNum>7</trackNum>"));
+        assert!(xml.contains("<annotation>Cached note</annotation>"));
+        assert_eq!(app.export_progress, Some(ExportProgress::Finished(path)));
+    }
+
+    /// A failed export is recorded as the export status.
+    #[test]
+    fn test_failed_export_reports_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let _ =
+            update(&mut app, Message::AddToRightPanel("/music/a.mp3".into()));
+
+        let path = dir.path().join("no such dir").join("out.m3u8");
+        run_playlist_export(&mut app, PlaylistFormat::M3u, path);
+        assert!(matches!(app.export_progress, Some(ExportProgress::Failed(_))));
+        let latest = app.notifications.latest().unwrap();
+        assert_eq!(latest.level, NotificationLevel::Error);
+        assert!(latest.message.starts_with("Failed to export M3U8 playlist"));
+    }
+
+    // ── Notification tests ────────────────────────────────────────────────
+
+    /// Failing side effects are reported in the notification queue rather
+    /// than only logged.
+    #[test]
+    fn test_side_effect_failures_are_notified() {
+        let dir = tempfile::tempdir().unwrap();
+        // The directory list cannot be saved into a missing directory
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            dir.path().join("missing").join("top_dirs.json"),
+            None,
+        );
+
+        let _ = update(
+            &mut app,
+            Message::DirectoryAdded(Some(dir.path().to_path_buf())),
+        );
+        let messages: Vec<(NotificationLevel, String)> = app
+            .notifications
+            .iter()
+            .map(|n| (n.level, n.message.clone()))
+            .collect();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].0, NotificationLevel::Error);
+        assert!(messages[0].1.starts_with("Failed to save the directory list"));
+        assert_eq!(messages[1].0, NotificationLevel::Info);
+
+        let _ = update(
+            &mut app,
+            Message::DirectoryAdded(Some(dir.path().join("absent"))),
+        );
+        assert_eq!(
+            app.notifications.latest().map(|n| n.level),
+            Some(NotificationLevel::Error)
+        );
+
+        import_into_right_panel(&mut app, dir.path().join("absent.xspf"));
+        let latest = app.notifications.latest().unwrap();
+        assert_eq!(latest.level, NotificationLevel::Error);
+        assert!(latest.message.starts_with("Failed to import playlist"));
+    }
+
+    #[test]
+    fn test_dismiss_and_clear_notifications() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        app.notifications.info("first");
+        app.notifications.warning("second");
+        let _ = update(&mut app, Message::ToggleNotificationLog);
+        assert!(app.notification_log_expanded);
+
+        let latest = app.notifications.latest().unwrap().id;
+        let _ = update(&mut app, Message::DismissNotification(latest));
+        assert_eq!(
+            app.notifications.latest().map(|n| n.message.as_str()),
+            Some("first")
+        );
+        assert!(app.notification_log_expanded);
+
+        let _ = update(&mut app, Message::ClearNotifications);
+        assert!(app.notifications.is_empty());
+        assert!(!app.notification_log_expanded);
+    }
+
+    // ── Device sync tests ─────────────────────────────────────────────────
+
+    /// Runs a sync the way `SyncRightPanelToFolderAt` does, driving the
+    /// progress stream to completion and feeding each event back through
+    /// `update`.
+    fn run_device_sync(app: &mut FileTreeApp, dest_dir: PathBuf) {
+        use iced::futures::StreamExt;
+        let stream = start_device_sync(app, dest_dir).unwrap();
+        let events: Vec<SyncProgress> =
+            iced::futures::executor::block_on(stream.collect());
+        for event in events {
+            let _ = update(app, Message::DeviceSyncProgress(event));
+        }
+    }
+
+    /// A sync copies the displayed audio files with the app's template,
+    /// persists the template, and records the summary once it ends.
+    #[test]
+    fn test_device_sync_copies_with_template_and_reports_summary() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store.clone()),
+        );
+        let src = temp_dir.path().join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        for name in ["b.mp3", "notes.txt", "a.mp3"] {
+            let path = src.join(name);
+            std::fs::write(&path, name).unwrap();
+            let _ = update(&mut app, Message::AddToRightPanel(path));
+        }
+        app.playlist_mut().sort_column = SortColumn::File;
+        app.playlist_mut().sort_order = SortOrder::Asc;
+        let _ = update(
+            &mut app,
+            Message::SyncTemplateChanged("{index:02} {file}.{ext}".into()),
+        );
+
+        let dest = temp_dir.path().join("card");
+        run_device_sync(&mut app, dest.clone());
+
+        assert_eq!(std::fs::read(dest.join("01 a.mp3")).unwrap(), b"a.mp3");
+        assert_eq!(std::fs::read(dest.join("02 b.mp3")).unwrap(), b"b.mp3");
+        assert!(app.sync_cancel.is_none());
+        match &app.sync_progress {
+            Some(SyncProgress::Finished(summary)) => {
+                assert_eq!(summary.copied, 2);
+                assert_eq!(summary.playlist, Some(dest.join("playlist.m3u8")));
+            },
+            other => panic!("expected a summary, got {other:?}"),
+        }
+        assert_eq!(
+            store.load_sync_template().as_deref(),
+            Some("{index:02} {file}.{ext}")
+        );
+    }
+
+    /// Cancelling sets the running sync's flag, and a second sync cannot
+    /// start while one is running.
+    #[test]
+    fn test_cancel_device_sync_sets_flag() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let dest = tempfile::tempdir().unwrap();
+        let stream = start_device_sync(&mut app, dest.path().to_path_buf());
+        assert!(stream.is_some());
+        assert!(
+            start_device_sync(&mut app, dest.path().to_path_buf()).is_none()
+        );
+
+        let cancel = app.sync_cancel.clone().unwrap();
+        let _ = update(&mut app, Message::CancelDeviceSync);
+        assert!(cancel.load(Ordering::Relaxed));
+    }
+
+    // ── Table export tests ────────────────────────────────────────────────
+
+    /// Toggling columns keeps them in written order and persists them.
+    #[test]
+    fn test_toggle_table_export_column_keeps_order_and_persists() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store.clone()),
+        );
+        assert_eq!(app.table_export_columns, TableColumn::ALL.to_vec());
+
+        for column in TableColumn::ALL {
+            let _ = update(&mut app, Message::ToggleTableExportColumn(column));
+        }
+        assert!(app.table_export_columns.is_empty());
+        let _ = update(
+            &mut app,
+            Message::ToggleTableExportColumn(TableColumn::Title),
+        );
+        let _ = update(
+            &mut app,
+            Message::ToggleTableExportColumn(TableColumn::File),
+        );
+        assert_eq!(
+            app.table_export_columns,
+            vec![TableColumn::File, TableColumn::Title]
+        );
+        assert_eq!(
+            store.load_table_export_columns(),
+            Some(vec![TableColumn::File, TableColumn::Title])
+        );
+    }
+
+    /// Exporting a table with no columns warns instead of writing nothing.
+    #[test]
+    fn test_table_export_without_columns_warns() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        app.table_export_columns.clear();
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("playlist.csv");
+
+        let _ = update(
+            &mut app,
+            Message::ExportRightPanelAsTableTo(TableFormat::Csv, path.clone()),
+        );
+        let latest = app.notifications.latest().unwrap();
+        assert_eq!(latest.level, NotificationLevel::Warning);
+        assert_eq!(latest.message, "No columns selected for table export");
+        assert!(app.export_progress.is_none());
+        assert!(!path.exists());
+    }
+
+    // ── ExportRightPanelAsM3uTo tests ─────────────────────────────────────
+
+    /// The M3U export writes the displayed order and skips files whose
+    /// extensions are not audio extensions.
+    #[test]
+    fn test_export_m3u_uses_displayed_order_and_audio_filter() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        for path in ["/music/b.mp3", "/music/notes.txt", "/music/a.mp3"] {
+            let _ = update(&mut app, Message::AddToRightPanel(path.into()));
+        }
+        app.playlist_mut().sort_column = SortColumn::File;
+        app.playlist_mut().sort_order = SortOrder::Asc;
+
+        let out_file = tempfile::NamedTempFile::new().unwrap();
+        run_playlist_export(
+            &mut app,
+            PlaylistFormat::M3u,
+            out_file.path().to_path_buf(),
+        );
+
+        let m3u = std::fs::read_to_string(out_file.path()).unwrap();
+        let locations: Vec<&str> = m3u
+            .lines()
+            .filter(|l| !l.starts_with('#') && !l.is_empty())
+            .collect();
+        assert_eq!(locations, vec!["/music/a.mp3", "/music/b.mp3"]);
+    }
+
+    // ── ExportRightPanelAsPlsTo tests ─────────────────────────────────────
+
+    /// The PLS export numbers entries in displayed order and skips files
+    /// whose extensions are not audio extensions.
+    #[test]
+    fn test_export_pls_uses_displayed_order_and_audio_filter() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        for path in ["/music/b.mp3", "/music/notes.txt", "/music/a.mp3"] {
+            let _ = update(&mut app, Message::AddToRightPanel(path.into()));
+        }
+        app.playlist_mut().sort_column = SortColumn::File;
+        app.playlist_mut().sort_order = SortOrder::Desc;
+
+        let out_file = tempfile::NamedTempFile::new().unwrap();
+        run_playlist_export(
+            &mut app,
+            PlaylistFormat::Pls,
+            out_file.path().to_path_buf(),
+        );
+
+        let pls = std::fs::read_to_string(out_file.path()).unwrap();
+        let files: Vec<&str> =
+            pls.lines().filter(|l| l.starts_with("File")).collect();
+        assert_eq!(files, vec!["File1=/music/b.mp3", "File2=/music/a.mp3"]);
+        assert!(pls.contains("NumberOfEntries=2\n"));
+    }
+
+    // ── ToggleExportRelativePaths tests ───────────────────────────────────
+
+    /// With relative paths enabled, exports write locations relative to the
+    /// playlist's directory.
+    #[test]
+    fn test_toggle_export_relative_paths_applies_to_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let song = dir.path().join("music").join("a.mp3");
+        let _ = update(&mut app, Message::AddToRightPanel(song));
+        assert!(!app.export_options.relative_paths);
+
+        let _ = update(&mut app, Message::ToggleExportRelativePaths);
+        assert!(app.export_options.relative_paths);
+
+        let out_path = dir.path().join("lists").join("out.m3u8");
+        std::fs::create_dir_all(out_path.parent().unwrap()).unwrap();
+        run_playlist_export(&mut app, PlaylistFormat::M3u, out_path.clone());
+        let m3u = std::fs::read_to_string(&out_path).unwrap();
+        assert!(m3u.ends_with("\n../music/a.mp3\n"));
+
+        let _ = update(&mut app, Message::ToggleExportRelativePaths);
+        assert!(!app.export_options.relative_paths);
+    }
+
+    // ── Path rewrite rule tests ───────────────────────────────────────────
+
+    /// Adds a rule through the draft inputs, as the rule editor does.
+    fn add_path_rewrite_rule(
+        app: &mut FileTreeApp,
+        name: &str,
+        source: &str,
+        target: &str,
+    ) {
+        let _ = update(app, Message::PathRewriteNameChanged(name.into()));
+        let _ = update(app, Message::PathRewriteSourceChanged(source.into()));
+        let _ = update(app, Message::PathRewriteTargetChanged(target.into()));
+        let _ = update(app, Message::AddPathRewriteRule);
+    }
+
+    /// Rules are added from the draft, persisted in the Sled store, and
+    /// reloaded by a new app instance.
+    #[test]
+    fn test_path_rewrite_rules_are_persisted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sled_path = temp_dir.path().join("db");
+        let store = SledStore::new(sled_path.to_str().unwrap()).unwrap();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store.clone()),
+        );
+
+        add_path_rewrite_rule(&mut app, " Box ", "/mnt/nas/music", "/storage");
+        assert_eq!(app.path_rewrite_rules.len(), 1);
+        assert_eq!(app.path_rewrite_rules[0].name, "Box");
+        assert_eq!(app.path_rewrite_draft, PathRewriteRule::default());
+
+        // Rules without a name or source prefix are ignored
+        add_path_rewrite_rule(&mut app, "", "/mnt", "/x");
+        add_path_rewrite_rule(&mut app, "No source", "", "/x");
+        assert_eq!(app.path_rewrite_rules.len(), 1);
+
+        let reloaded = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store),
+        );
+        assert_eq!(reloaded.path_rewrite_rules, app.path_rewrite_rules);
+    }
+
+    /// The right-panel playlist is saved on every change and restored by
+    /// `FileTreeApp::load` with its order and shuffle state; tracks deleted
+    /// in the meantime stay in place, marked missing, until removed.
+    #[test]
+    fn test_right_panel_is_restored_on_load() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let persist_path = temp_dir.path().join("top_dirs.json");
+        let mut app = FileTreeApp::load(
+            &["mp3"],
+            Some(persist_path.clone()),
+            Some(store.clone()),
+        );
+        assert!(app.playlist().files.is_empty());
+
+        let paths: Vec<PathBuf> = ["a.mp3", "b.mp3", "c.mp3"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+        for path in &paths {
+            std::fs::write(path, b"").unwrap();
+            let _ = update(&mut app, Message::AddToRightPanel(path.clone()));
+        }
+        let _ = update(&mut app, Message::ShuffleRightPanel);
+        let order = app.sorted_right_panel_files();
+        std::fs::remove_file(&paths[1]).unwrap();
+
+        let mut restored = FileTreeApp::load(
+            &["mp3"],
+            Some(persist_path.clone()),
+            Some(store.clone()),
+        );
+        assert!(restored.playlist().shuffled);
+        // Rows come back before anything is read from disk
+        assert!(restored.playlist().files.iter().all(|f| !f.missing));
+        assert!(restored.notifications.is_empty());
+        read_restored(&mut restored);
+        let expected: Vec<PathBuf> =
+            order.into_iter().map(|f| f.path).collect();
+        let actual: Vec<PathBuf> = restored
+            .sorted_right_panel_files()
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(actual, expected);
+        let missing: Vec<&PathBuf> = restored
+            .playlist()
+            .files
+            .iter()
+            .filter(|f| f.missing)
+            .map(|f| &f.path)
+            .collect();
+        assert_eq!(missing, [&paths[1]]);
+        assert!(restored.playlist().missing_files.is_empty());
+        assert_eq!(
+            restored.notifications.latest().map(|n| n.level),
+            Some(NotificationLevel::Warning)
+        );
+
+        // Missing tracks survive the next save until the user removes them
+        let _ =
+            update(&mut restored, Message::SortRightPanelBy(SortColumn::File));
+        let mut again = FileTreeApp::load(
+            &["mp3"],
+            Some(persist_path.clone()),
+            Some(store.clone()),
+        );
+        read_restored(&mut again);
+        assert_eq!(again.playlist().files.len(), 3);
+        assert_eq!(
+            again.playlist().files.iter().filter(|f| f.missing).count(),
+            1
+        );
+        let _ = update(&mut restored, Message::RemoveMissingFromRightPanel);
+        assert!(restored.playlist().files.iter().all(|f| !f.missing));
+        assert_eq!(restored.playlist().files.len(), 2);
+        let _ = update(&mut restored, Message::UndoRightPanel);
+        assert_eq!(restored.playlist().files.len(), 3);
+        let _ = update(&mut restored, Message::RedoRightPanel);
+        let again =
+            FileTreeApp::load(&["mp3"], Some(persist_path), Some(store));
+        assert_eq!(again.playlist().files.len(), 2);
+    }
+
+    /// Saved playlists that cannot be decoded are reported and left as they
+    /// are, rather than replaced by the empty playlist shown instead.
+    #[test]
+    fn test_unreadable_saved_playlists_are_not_overwritten() {
+        use crate::db::sled_store::PLAYLISTS_KEY;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        store.raw().insert(PLAYLISTS_KEY, vec![0xff; 3]).unwrap();
+
+        let mut app = FileTreeApp::load(
+            &["mp3"],
+            Some(temp_dir.path().join("top_dirs.json")),
+            Some(store.clone()),
+        );
+        assert_eq!(
+            app.notifications.latest().map(|n| n.level),
+            Some(NotificationLevel::Error)
+        );
+        assert!(app.playlist().files.is_empty());
+        let _ = update(&mut app, Message::AddToRightPanel("/a.mp3".into()));
+        assert_eq!(app.playlist().files.len(), 1);
+        assert_eq!(
+            store.raw().get(PLAYLISTS_KEY).unwrap().as_deref(),
+            Some(&[0xff; 3][..])
+        );
+    }
+
+    /// Each playlist keeps its own details and missing entries, and both
+    /// survive a restart.
+    #[test]
+    fn test_playlist_details_are_kept_per_playlist() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let persist_path = temp_dir.path().join("top_dirs.json");
+        let mut app = FileTreeApp::load(
+            &["mp3"],
+            Some(persist_path.clone()),
+            Some(store.clone()),
+        );
+        let gone = temp_dir.path().join("gone.mp3");
+        let playlist_path = temp_dir.path().join("list.m3u");
+        std::fs::write(&playlist_path, format!("{}\n", gone.display()))
+            .unwrap();
+        import_into_right_panel(&mut app, playlist_path);
+        let _ = update(
+            &mut app,
+            Message::PlaylistTitleChanged("Friday Set".to_string()),
+        );
+        let _ = update(&mut app, Message::SavePlaylistDetails);
+        let _ = update(&mut app, Message::NewPlaylist);
+        assert_eq!(app.playlist().title, "");
+        assert_eq!(app.playlist_title_draft, "");
+        assert!(app.playlist().missing_files.is_empty());
+        let _ =
+            update(&mut app, Message::PlaylistCreatorChanged("DJ".to_string()));
+        let _ = update(&mut app, Message::SavePlaylistDetails);
+
+        let mut restored =
+            FileTreeApp::load(&["mp3"], Some(persist_path), Some(store));
+        assert_eq!(restored.playlists[0].title, "Friday Set");
+        assert_eq!(restored.playlists[0].creator, "");
+        assert_eq!(restored.playlists[0].missing_files, vec![gone]);
+        assert_eq!(restored.playlists[1].creator, "DJ");
+        assert!(restored.playlists[1].missing_files.is_empty());
+
+        let _ = update(&mut restored, Message::SelectPlaylist(0));
+        assert_eq!(
+            playlist_info(&restored).title.as_deref(),
+            Some("Friday Set")
+        );
+        let _ = update(&mut restored, Message::DismissMissingPlaylistFiles);
+        assert!(restored.playlist().missing_files.is_empty());
+    }
+
+    /// Typing in the details inputs changes nothing until the text is
+    /// submitted; saving it is one undoable edit, and undoing it puts the
+    /// previous text back in the inputs.
+    #[test]
+    fn test_playlist_details_are_saved_on_submit() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let persist_path = temp_dir.path().join("top_dirs.json");
+        let mut app = FileTreeApp::load(
+            &["mp3"],
+            Some(persist_path.clone()),
+            Some(store.clone()),
+        );
+        for text in ["F", "Fri", "Friday Set"] {
+            let _ = update(
+                &mut app,
+                Message::PlaylistTitleChanged(text.to_string()),
+            );
+        }
+        assert_eq!(app.playlist().title, "");
+        assert!(!app.playlist().history.can_undo());
+        let unsaved = FileTreeApp::load(
+            &["mp3"],
+            Some(persist_path.clone()),
+            Some(store.clone()),
+        );
+        assert_eq!(unsaved.playlist().title, "");
+
+        let _ = update(&mut app, Message::SavePlaylistDetails);
+        assert_eq!(app.playlist().title, "Friday Set");
+        let restored = FileTreeApp::load(
+            &["mp3"],
+            Some(persist_path.clone()),
+            Some(store.clone()),
+        );
+        assert_eq!(restored.playlist().title, "Friday Set");
+        assert_eq!(restored.playlist_title_draft, "Friday Set");
+
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlist().title, "");
+        assert_eq!(app.playlist_title_draft, "");
+        assert!(!app.playlist().history.can_undo());
+    }
+
+    /// Each playlist keeps its own tracks and sorting, and the Add*
+    /// messages go to the active one.
+    #[test]
+    fn test_playlists_keep_their_own_tracks_and_sorting() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let _ = update(&mut app, Message::AddToRightPanel("/a.mp3".into()));
+        let _ = update(&mut app, Message::NewPlaylist);
+        assert_eq!(app.active_playlist, 1);
+        assert_eq!(app.playlist().name, "Playlist 2");
+        assert!(app.playlist().files.is_empty());
+
+        let _ = update(&mut app, Message::AddToRightPanel("/b.mp3".into()));
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        let _ = update(&mut app, Message::SelectPlaylist(0));
+        assert_eq!(app.playlist_name_draft, "Playlist");
+        assert_eq!(app.playlist().sort_column, SortColumn::Directory);
+        assert_eq!(
+            app.sorted_right_panel_files()
+                .into_iter()
+                .map(|f| f.path)
+                .collect::<Vec<_>>(),
+            [PathBuf::from("/a.mp3")]
+        );
+        assert_eq!(app.playlists[1].sort_column, SortColumn::File);
+        assert_eq!(app.playlists[1].files[0].path, PathBuf::from("/b.mp3"));
+
+        // Out-of-range tabs are ignored
+        let _ = update(&mut app, Message::SelectPlaylist(5));
+        assert_eq!(app.active_playlist, 0);
+    }
+
+    /// Playlists can be duplicated next to the original, renamed to a free
+    /// name, and deleted; deleting the last one leaves an empty playlist.
+    #[test]
+    fn test_duplicate_rename_and_delete_playlists() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let _ = update(&mut app, Message::AddToRightPanel("/a.mp3".into()));
+        let _ = update(&mut app, Message::NewPlaylist);
+        let _ = update(&mut app, Message::SelectPlaylist(0));
+        let _ = update(&mut app, Message::DuplicatePlaylist);
+        let names: Vec<&str> =
+            app.playlists.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Playlist", "Playlist copy", "Playlist 2"]);
+        assert_eq!(app.active_playlist, 1);
+        assert_eq!(app.playlist().files, app.playlists[0].files);
+
+        // A name used by another playlist is refused
+        let _ = update(
+            &mut app,
+            Message::PlaylistNameDraftChanged("Playlist 2".into()),
+        );
+        let _ = update(&mut app, Message::RenamePlaylist);
+        assert_eq!(app.playlist().name, "Playlist copy");
+        assert_eq!(
+            app.notifications.latest().map(|n| n.level),
+            Some(NotificationLevel::Warning)
+        );
+        let _ = update(
+            &mut app,
+            Message::PlaylistNameDraftChanged(" Party ".into()),
+        );
+        let _ = update(&mut app, Message::RenamePlaylist);
+        assert_eq!(app.playlist().name, "Party");
+
+        let _ = update(&mut app, Message::DeletePlaylist);
+        assert_eq!(app.playlists.len(), 2);
+        assert_eq!(app.playlist().name, "Playlist 2");
+        let _ = update(&mut app, Message::DeletePlaylist);
+        let _ = update(&mut app, Message::DeletePlaylist);
+        assert_eq!(app.playlists.len(), 1);
+        assert_eq!(app.playlist().name, "Playlist");
+        assert!(app.playlist().files.is_empty());
+    }
+
+    /// All playlists and the active tab survive a restart.
+    #[test]
+    fn test_playlists_are_restored_on_load() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let persist_path = temp_dir.path().join("top_dirs.json");
+        let mut app = FileTreeApp::load(
+            &["mp3"],
+            Some(persist_path.clone()),
+            Some(store.clone()),
+        );
+        let track = temp_dir.path().join("a.mp3");
+        std::fs::write(&track, b"").unwrap();
+        let _ = update(&mut app, Message::NewPlaylist);
+        let _ = update(&mut app, Message::AddToRightPanel(track.clone()));
+        let _ =
+            update(&mut app, Message::SortRightPanelBy(SortColumn::Duration));
+        let _ = update(
+            &mut app,
+            Message::KeyboardModifiersChanged(iced::keyboard::Modifiers::SHIFT),
+        );
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::Title));
+
+        let restored =
+            FileTreeApp::load(&["mp3"], Some(persist_path), Some(store));
+        assert_eq!(restored.playlists.len(), 2);
+        assert_eq!(restored.active_playlist, 1);
+        assert_eq!(restored.playlist_name_draft, "Playlist 2");
+        assert_eq!(restored.playlist().sort_column, SortColumn::Duration);
+        assert_eq!(restored.playlist().then_by, app.playlist().then_by);
+        assert_eq!(restored.playlist().then_by.len(), 1);
+        assert_eq!(restored.playlist().files[0].path, track);
+        assert!(restored.playlists[0].files.is_empty());
+    }
+
+    /// Player profiles can be added, replaced by name, selected, and
+    /// removed, and survive a restart.
+    #[test]
+    fn test_player_profiles_are_persisted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store.clone()),
+        );
+        assert_eq!(app.player_profiles, default_profiles());
+        assert_eq!(app.selected_player, "VLC");
+
+        let _ = update(&mut app, Message::PlayerNameChanged(" mpv ".into()));
+        let _ =
+            update(&mut app, Message::PlayerExecutableChanged("mpv".into()));
+        let _ = update(
+            &mut app,
+            Message::PlayerArgsChanged("--shuffle {files}".into()),
+        );
+        let _ =
+            update(&mut app, Message::SelectPlayerFormat(PlaylistFormat::Pls));
+        let _ = update(&mut app, Message::AddPlayerProfile);
+        assert_eq!(app.player_profiles.len(), default_profiles().len());
+        assert_eq!(app.player_profiles[1].args, "--shuffle {files}");
+        assert_eq!(app.player_profiles[1].format, PlaylistFormat::Pls);
+        assert_eq!(app.selected_player, "mpv");
+        assert_eq!(app.player_draft, PlayerProfile::default());
+
+        // Profiles without an executable are ignored
+        let _ = update(&mut app, Message::PlayerNameChanged("Empty".into()));
+        let _ = update(&mut app, Message::AddPlayerProfile);
+        assert_eq!(app.player_profiles.len(), default_profiles().len());
+
+        let _ = update(&mut app, Message::RemovePlayerProfile("mpv".into()));
+        assert_eq!(app.selected_player, "VLC");
+        let _ =
+            update(&mut app, Message::SelectPlayerProfile("Audacious".into()));
+
+        let reloaded = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store),
+        );
+        assert_eq!(reloaded.player_profiles, app.player_profiles);
+        assert_eq!(reloaded.selected_player, "Audacious");
+    }
+
+    /// Playing with a profile whose executable is missing reports an error
+    /// naming the player instead of failing silently.
+    #[test]
+    fn test_play_with_missing_player_is_notified() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        app.player_profiles = vec![PlayerProfile {
+            name: "Missing".into(),
+            executable: "playlist-ui-no-such-player".into(),
+            ..Default::default()
+        }];
+        app.selected_player = "Missing".into();
+        let _ =
+            update(&mut app, Message::AddToRightPanel("/music/a.mp3".into()));
+        let _ = update(&mut app, Message::PlayRightPanel);
+
+        let latest = app.notifications.latest().unwrap();
+        assert_eq!(latest.level, NotificationLevel::Error);
+        assert!(latest.message.starts_with("Failed to start Missing"));
+        assert!(latest.message.contains("playlist-ui-no-such-player"));
+    }
+
+    /// The MPD port input accepts only valid ports, allowing an empty field
+    /// while typing.
+    #[test]
+    fn test_mpd_port_input_validation() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        assert_eq!(app.mpd_port_input, "6600");
+        let _ = update(&mut app, Message::MpdPortChanged(String::new()));
+        assert_eq!(app.mpd_port_input, "");
+        let _ = update(&mut app, Message::MpdPortChanged("6601".into()));
+        assert_eq!(app.mpd_settings.port, 6601);
+        for invalid in ["0", "70000", "66a"] {
+            let _ = update(&mut app, Message::MpdPortChanged(invalid.into()));
+            assert_eq!(app.mpd_port_input, "6601");
+            assert_eq!(app.mpd_settings.port, 6601);
+        }
+    }
+
+    /// Sending to an MPD server that is not running persists the settings,
+    /// reports the failure, and allows another attempt.
+    #[test]
+    fn test_send_to_unreachable_mpd_is_notified() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store.clone()),
+        );
+        // A port that was just free is very unlikely to be taken again
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let _ = update(&mut app, Message::MpdHostChanged("127.0.0.1".into()));
+        let _ = update(&mut app, Message::MpdPortChanged(port.to_string()));
+        let _ = update(
+            &mut app,
+            Message::MpdMusicDirectoryChanged("/music".into()),
+        );
+        let _ =
+            update(&mut app, Message::AddToRightPanel("/music/a.mp3".into()));
+
+        assert!(
+            start_mpd_send(&mut app, MpdAction::SavePlaylist(" ".into()))
+                .is_none()
+        );
+        let request =
+            start_mpd_send(&mut app, MpdAction::ReplaceQueue).unwrap();
+        assert!(app.mpd_busy);
+        assert!(start_mpd_send(&mut app, MpdAction::AppendToQueue).is_none());
+        let message = iced::futures::executor::block_on(request);
+        let _ = update(&mut app, message);
+
+        assert!(!app.mpd_busy);
+        let latest = app.notifications.latest().unwrap();
+        assert_eq!(latest.level, NotificationLevel::Error);
+        assert!(latest.message.starts_with(&format!(
+            "Failed to send to MPD at 127.0.0.1:{port}"
+        )));
+        assert_eq!(store.load_mpd_settings(), Some(app.mpd_settings.clone()));
+    }
+
+    /// The selected rule is applied by exports; removing it clears the
+    /// selection.
+    #[test]
+    fn test_selected_path_rewrite_rule_applies_to_export() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        add_path_rewrite_rule(&mut app, "Box", "/mnt/nas/music", "/storage");
+        add_path_rewrite_rule(&mut app, "Other", "/other", "/x");
+        let _ = update(
+            &mut app,
+            Message::AddToRightPanel("/mnt/nas/music/a.mp3".into()),
+        );
+        let _ = update(
+            &mut app,
+            Message::SelectPathRewriteRule(Some("Box".into())),
+        );
+        assert_eq!(
+            app.export_options.rewrite_rule.as_ref().map(|r| r.name.as_str()),
+            Some("Box")
+        );
+
+        let out_file = tempfile::NamedTempFile::new().unwrap();
+        run_playlist_export(
+            &mut app,
+            PlaylistFormat::Pls,
+            out_file.path().to_path_buf(),
+        );
+        let pls = std::fs::read_to_string(out_file.path()).unwrap();
+        assert!(pls.contains("File1=/storage/a.mp3\n"));
+
+        let _ =
+            update(&mut app, Message::RemovePathRewriteRule("Other".into()));
+        assert!(app.export_options.rewrite_rule.is_some());
+        let _ = update(&mut app, Message::RemovePathRewriteRule("Box".into()));
+        assert!(app.export_options.rewrite_rule.is_none());
+        assert!(app.path_rewrite_rules.is_empty());
+    }
+
+    // ── ImportRightPanelPlaylistFrom tests ────────────────────────────────
+
+    /// Importing replaces the playlist with the existing entries, keeps
+    /// their playlist order, and records the entries that are missing.
+    #[test]
+    fn test_import_playlist_replaces_right_panel_and_reports_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let song_z = dir.path().join("z.mp3");
+        let song_a = dir.path().join("a.mp3");
+        std::fs::write(&song_z, b"").unwrap();
+        std::fs::write(&song_a, b"").unwrap();
+        let gone = dir.path().join("gone.mp3");
+        let xml = format!(
+            r#"<playlist version="1" xmlns="http://xspf.org/ns/0/"><trackList>
+<track><location>file://{}</location></track>
+<track><location>file://{}</location></track>
+<track><location>file://{}</location></track>
+</trackList></playlist>"#,
+            song_z.display(),
+            gone.display(),
+            song_a.display()
+        );
+        let playlist_path = dir.path().join("playlist.xspf");
+        std::fs::write(&playlist_path, xml).unwrap();
+
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let _ = update(
+            &mut app,
+            Message::AddToRightPanel(PathBuf::from("/music/old.mp3")),
+        );
+
+        // The playlist is read in the background; nothing changes until its
+        // result arrives
+        let _ = update(
+            &mut app,
+            Message::ImportRightPanelPlaylistFrom(Some(playlist_path.clone())),
+        );
+        assert_eq!(app.playlist().files.len(), 1);
+        import_into_right_panel(&mut app, playlist_path);
+
+        let displayed: Vec<PathBuf> = displayed_right_panel_files(&app)
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(displayed, vec![song_z, song_a]);
+        assert_eq!(app.playlist().missing_files, vec![gone]);
+
+        let _ = update(&mut app, Message::DismissMissingPlaylistFiles);
+        assert!(app.playlist().missing_files.is_empty());
+    }
+
+    /// M3U imports resolve relative entries and use `#EXTINF` hints for
+    /// files without tags of their own.
+    #[test]
+    fn test_import_m3u_playlist_uses_extinf_hints() {
+        let dir = tempfile::tempdir().unwrap();
+        let song = dir.path().join("untagged.mp3");
+        std::fs::write(&song, b"").unwrap();
+        let playlist_path = dir.path().join("list.m3u");
+        std::fs::write(
+            &playlist_path,
+            "#EXTM3U\n#EXTINF:200,Artist - Title\nuntagged.mp3\n",
+        )
+        .unwrap();
+
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        import_into_right_panel(&mut app, playlist_path);
+
+        assert_eq!(app.playlist().files.len(), 1);
+        let file = &app.playlist().files[0];
+        assert_eq!(file.path, song);
+        assert_eq!(file.creator.as_deref(), Some("Artist"));
+        assert_eq!(file.title.as_deref(), Some("Title"));
+        assert!(app.playlist().missing_files.is_empty());
+    }
+
+    /// A playlist without a saved view is imported in manual order, so its
+    /// own track order is kept and added tracks go after it.
+    #[test]
+    fn test_import_without_view_keeps_playlist_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let songs: Vec<PathBuf> = ["c.mp3", "a.mp3", "b.mp3"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        for song in &songs {
+            std::fs::write(song, b"").unwrap();
+        }
+        let playlist_path = dir.path().join("list.m3u");
+        std::fs::write(&playlist_path, "c.mp3\na.mp3\n").unwrap();
+
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        import_into_right_panel(&mut app, playlist_path);
+        assert_eq!(app.playlist().sort_column, SortColumn::Manual);
+        assert!(!app.playlist().shuffled);
+        let _ = update(&mut app, Message::AddToRightPanel(songs[2].clone()));
+
+        let paths: Vec<PathBuf> = displayed_right_panel_files(&app)
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(paths, songs);
+    }
+
+    /// An exported XSPF carries the playlist details and every sort key, and
+    /// importing it restores both.
+    #[test]
+    fn test_xspf_import_restores_playlist_details_and_sort() {
+        let dir = tempfile::tempdir().unwrap();
+        let songs: Vec<PathBuf> = ["b.mp3", "c.mp3", "a.mp3"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        for song in &songs {
+            std::fs::write(song, b"").unwrap();
+            let _ = update(&mut app, Message::AddToRightPanel(song.clone()));
+        }
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        let _ = update(
+            &mut app,
+            Message::KeyboardModifiersChanged(iced::keyboard::Modifiers::SHIFT),
+        );
+        for column in [SortColumn::Title, SortColumn::Album, SortColumn::Album]
+        {
+            let _ = update(&mut app, Message::SortRightPanelBy(column));
+        }
+        let _ = update(
+            &mut app,
+            Message::KeyboardModifiersChanged(
+                iced::keyboard::Modifiers::empty(),
+            ),
+        );
+        let _ = update(
+            &mut app,
+            Message::PlaylistTitleChanged("Friday Set".to_string()),
+        );
+        let _ = update(
+            &mut app,
+            Message::PlaylistAnnotationChanged("Warm-up & peak".to_string()),
+        );
+        let _ = update(&mut app, Message::SavePlaylistDetails);
+        let playlist_path = dir.path().join("friday.xspf");
+        run_playlist_export(
+            &mut app,
+            PlaylistFormat::Xspf,
+            playlist_path.clone(),
+        );
+
+        let xml = std::fs::read_to_string(&playlist_path).unwrap();
+        assert!(xml.contains("<title>Friday Set</title>"));
+        assert!(xml.contains("<annotation>Warm-up &amp; peak</annotation>"));
+        assert!(xml.contains("<date>"));
+        assert!(!xml.contains("<creator>"));
+        assert!(xml.contains(r#"<extension application="playlist_ui">"#));
+
+        let mut restored = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        import_into_right_panel(&mut restored, playlist_path);
+        assert_eq!(restored.playlist().title, "Friday Set");
+        assert_eq!(restored.playlist().annotation, "Warm-up & peak");
+        assert_eq!(restored.playlist().creator, "");
+        assert_eq!(restored.playlist().sort_column, SortColumn::File);
+        assert_eq!(restored.playlist().sort_order, SortOrder::Desc);
+        assert_eq!(
+            restored.playlist().then_by,
+            vec![
+                SortKey { column: SortColumn::Title, order: SortOrder::Asc },
+                SortKey { column: SortColumn::Album, order: SortOrder::Desc },
+            ]
+        );
+        assert!(!restored.playlist().shuffled);
+        let displayed: Vec<PathBuf> = displayed_right_panel_files(&restored)
+            .into_iter()
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(
+            displayed,
+            vec![songs[1].clone(), songs[0].clone(), songs[2].clone()]
+        );
+    }
+
+    #[test]
+    fn test_import_playlist_failure_keeps_right_panel() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let _ = update(
+            &mut app,
+            Message::AddToRightPanel(PathBuf::from("/music/song.mp3")),
+        );
+        import_into_right_panel(
+            &mut app,
+            PathBuf::from("/nonexistent/playlist.xspf"),
+        );
+        assert_eq!(app.playlist().files.len(), 1);
+
+        let _ = update(&mut app, Message::ImportRightPanelPlaylistFrom(None));
+        assert_eq!(app.playlist().files.len(), 1);
+    }
+
+    fn playlist_paths(app: &FileTreeApp) -> Vec<PathBuf> {
+        app.playlist().files.iter().map(|f| f.path.clone()).collect()
+    }
+
+    /// Adding, removing a directory, clearing, sorting, shuffling, and
+    /// importing can each be undone and redone, restoring the tracks in their
+    /// order and the playlist's details.
+    #[test]
+    fn test_undo_and_redo_right_panel_edits() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        assert!(!app.playlist().history.can_undo());
+        let _ = update(&mut app, Message::AddToRightPanel("/a/1.mp3".into()));
+        let _ = update(&mut app, Message::AddToRightPanel("/b/2.mp3".into()));
+        let _ = update(
+            &mut app,
+            Message::RemoveDirectoryFromRightPanel("/a".into()),
+        );
+        assert_eq!(playlist_paths(&app), [PathBuf::from("/b/2.mp3")]);
+
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(
+            playlist_paths(&app),
+            [PathBuf::from("/a/1.mp3"), PathBuf::from("/b/2.mp3")]
+        );
+        let _ = update(&mut app, Message::RedoRightPanel);
+        assert_eq!(playlist_paths(&app), [PathBuf::from("/b/2.mp3")]);
+        let _ = update(&mut app, Message::UndoRightPanel);
+
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        assert_eq!(app.playlist().sort_order, SortOrder::Desc);
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlist().sort_column, SortColumn::File);
+        assert_eq!(app.playlist().sort_order, SortOrder::Asc);
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlist().sort_column, SortColumn::Directory);
+
+        let _ = update(&mut app, Message::ShuffleRightPanel);
+        assert!(app.playlist().shuffled);
+        let _ = update(&mut app, Message::ClearRightPanel);
+        assert!(app.playlist().files.is_empty());
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlist().files.len(), 2);
+        assert!(app.playlist().shuffled);
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert!(!app.playlist().shuffled);
+        assert_eq!(
+            playlist_paths(&app),
+            [PathBuf::from("/a/1.mp3"), PathBuf::from("/b/2.mp3")]
+        );
+
+        // Undoing the two additions empties the playlist, and nothing is
+        // left to undo
+        let _ = update(&mut app, Message::UndoRightPanel);
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert!(app.playlist().files.is_empty());
+        assert!(!app.playlist().history.can_undo());
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert!(app.playlist().files.is_empty());
+        assert!(app.playlist().history.can_redo());
+
+        // Undoing a clear and an import brings back the details and the
+        // entries the import did not find
+        let dir = tempfile::tempdir().unwrap();
+        let gone = dir.path().join("gone.mp3");
+        let playlist_path = dir.path().join("list.xspf");
+        std::fs::write(
+            &playlist_path,
+            format!(
+                r#"<playlist version="1" xmlns="http://xspf.org/ns/0/">
+    <title>Imported</title>
+    <annotation>Notes</annotation>
+    <trackList><track><location>{}</location></track></trackList>
+</playlist>"#,
+                gone.display()
+            ),
+        )
+        .unwrap();
+        import_into_right_panel(&mut app, playlist_path);
+        assert_eq!(app.playlist().title, "Imported");
+        let _ = update(&mut app, Message::ClearRightPanel);
+        assert!(app.playlist().missing_files.is_empty());
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlist().missing_files, vec![gone.clone()]);
+        assert_eq!(app.playlist().title, "Imported");
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlist().title, "");
+        assert_eq!(app.playlist().annotation, "");
+        assert!(app.playlist().missing_files.is_empty());
+        let _ = update(&mut app, Message::RedoRightPanel);
+        assert_eq!(app.playlist().annotation, "Notes");
+        assert_eq!(app.playlist().missing_files, vec![gone]);
+    }
+
+    /// A new edit discards what could be redone, edits that change nothing
+    /// are not recorded, and each playlist has its own history.
+    #[test]
+    fn test_undo_history_is_per_playlist() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let _ = update(&mut app, Message::AddToRightPanel("/a.mp3".into()));
+        let _ = update(&mut app, Message::AddToRightPanel("/b.mp3".into()));
+        let _ = update(&mut app, Message::UndoRightPanel);
+        let _ = update(&mut app, Message::AddToRightPanel("/c.mp3".into()));
+        assert!(!app.playlist().history.can_redo());
+        let _ = update(&mut app, Message::RedoRightPanel);
+        assert_eq!(
+            playlist_paths(&app),
+            [PathBuf::from("/a.mp3"), PathBuf::from("/c.mp3")]
+        );
+
+        // Removing a track that is not in the playlist changes nothing
+        let _ =
+            update(&mut app, Message::RemoveFromRightPanel("/x.mp3".into()));
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(playlist_paths(&app), [PathBuf::from("/a.mp3")]);
+
+        let _ = update(&mut app, Message::NewPlaylist);
+        assert!(!app.playlist().history.can_undo());
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlists[0].files.len(), 1);
+
+        let _ = update(&mut app, Message::SelectPlaylist(0));
+        let _ = update(&mut app, Message::DuplicatePlaylist);
+        assert!(!app.playlist().history.can_undo());
+        let _ = update(&mut app, Message::SelectPlaylist(0));
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert!(app.playlist().files.is_empty());
+        assert_eq!(app.playlists[1].files.len(), 1);
+    }
+
+    /// Switching from a column sort to manual order freezes the displayed
+    /// order; rows then move up, down, to the top, and to the bottom.
+    #[test]
+    fn test_manual_order_freezes_display_and_moves_rows() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        for path in ["/d/b.mp3", "/d/c.mp3", "/d/a.mp3"] {
+            let _ = update(&mut app, Message::AddToRightPanel(path.into()));
+        }
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        let _ = update(&mut app, Message::SortRightPanelManually);
+        assert_eq!(app.playlist().sort_column, SortColumn::Manual);
+        let order = |app: &FileTreeApp| -> Vec<PathBuf> {
+            displayed_right_panel_files(app)
+                .into_iter()
+                .map(|f| f.path)
+                .collect()
+        };
+        assert_eq!(
+            order(&app),
+            [
+                PathBuf::from("/d/c.mp3"),
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/a.mp3")
+            ]
+        );
+
+        let _ = update(
+            &mut app,
+            Message::MoveRightPanelFile("/d/a.mp3".into(), RowMove::ToTop),
+        );
+        let _ = update(
+            &mut app,
+            Message::MoveRightPanelFile("/d/c.mp3".into(), RowMove::Down),
+        );
+        assert_eq!(
+            order(&app),
+            [
+                PathBuf::from("/d/a.mp3"),
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/c.mp3")
+            ]
+        );
+        // Moves past either end leave the row where it is
+        let _ = update(
+            &mut app,
+            Message::MoveRightPanelFile("/d/a.mp3".into(), RowMove::Up),
+        );
+        let _ = update(
+            &mut app,
+            Message::MoveRightPanelFile("/d/c.mp3".into(), RowMove::Down),
+        );
+        let _ = update(
+            &mut app,
+            Message::MoveRightPanelFile("/d/a.mp3".into(), RowMove::ToBottom),
+        );
+        assert_eq!(
+            order(&app),
+            [
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/c.mp3"),
+                PathBuf::from("/d/a.mp3")
+            ]
+        );
+
+        // New tracks go to the end, and moves can be undone
+        let _ = update(&mut app, Message::AddToRightPanel("/d/0.mp3".into()));
+        assert_eq!(order(&app)[3], PathBuf::from("/d/0.mp3"));
+        let _ = update(&mut app, Message::UndoRightPanel);
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(
+            order(&app),
+            [
+                PathBuf::from("/d/a.mp3"),
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/c.mp3")
+            ]
+        );
+    }
+
+    /// Moving a row from a column sort freezes the displayed order first,
+    /// and dropping a dragged row on another one moves it to that place.
+    #[test]
+    fn test_move_and_drag_rows_from_column_sort() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        for path in ["/d/c.mp3", "/d/a.mp3", "/d/b.mp3"] {
+            let _ = update(&mut app, Message::AddToRightPanel(path.into()));
+        }
+        let _ = update(
+            &mut app,
+            Message::MoveRightPanelFile("/d/c.mp3".into(), RowMove::Up),
+        );
+        assert_eq!(app.playlist().sort_column, SortColumn::Manual);
+        assert_eq!(
+            playlist_paths(&app),
+            [
+                PathBuf::from("/d/a.mp3"),
+                PathBuf::from("/d/c.mp3"),
+                PathBuf::from("/d/b.mp3")
+            ]
+        );
+
+        // Dragging down drops the row after the target
+        let _ =
+            update(&mut app, Message::RightPanelDragStarted("/d/a.mp3".into()));
+        let _ =
+            update(&mut app, Message::RightPanelDragEntered("/d/b.mp3".into()));
+        let _ = update(&mut app, Message::RightPanelDragReleased);
+        assert!(app.right_panel_drag.is_none());
+        assert_eq!(
+            playlist_paths(&app),
+            [
+                PathBuf::from("/d/c.mp3"),
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/a.mp3")
+            ]
+        );
+
+        // Releasing over the dragged row itself changes nothing
+        let _ =
+            update(&mut app, Message::RightPanelDragStarted("/d/b.mp3".into()));
+        let _ =
+            update(&mut app, Message::RightPanelDragEntered("/d/b.mp3".into()));
+        let _ = update(&mut app, Message::RightPanelDragReleased);
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(
+            playlist_paths(&app),
+            [
+                PathBuf::from("/d/a.mp3"),
+                PathBuf::from("/d/c.mp3"),
+                PathBuf::from("/d/b.mp3")
+            ]
+        );
+    }
+
+    fn click_row(
+        app: &mut FileTreeApp,
+        path: &str,
+        modifiers: iced::keyboard::Modifiers,
+    ) {
+        let _ = update(app, Message::KeyboardModifiersChanged(modifiers));
+        let _ = update(app, Message::SelectRightPanelFile(path.into()));
+    }
+
+    fn selected_paths(app: &FileTreeApp) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> =
+            app.right_panel_selection.iter().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    /// Click selects one row, ctrl-click toggles rows, and shift-click
+    /// selects the displayed range from the last row clicked; the selection
+    /// survives re-sorting.
+    #[test]
+    fn test_right_panel_selection_click_shift_and_ctrl() {
+        use iced::keyboard::Modifiers;
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        for path in ["/d/a.mp3", "/d/b.mp3", "/d/c.mp3", "/d/d.mp3"] {
+            let _ = update(&mut app, Message::AddToRightPanel(path.into()));
+        }
+        click_row(&mut app, "/d/b.mp3", Modifiers::empty());
+        click_row(&mut app, "/d/d.mp3", Modifiers::SHIFT);
+        assert_eq!(
+            selected_paths(&app),
+            [
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/c.mp3"),
+                PathBuf::from("/d/d.mp3")
+            ]
+        );
+
+        click_row(&mut app, "/d/c.mp3", Modifiers::COMMAND);
+        click_row(&mut app, "/d/a.mp3", Modifiers::COMMAND);
+        assert_eq!(
+            selected_paths(&app),
+            [
+                PathBuf::from("/d/a.mp3"),
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/d.mp3")
+            ]
+        );
+
+        // The range follows the displayed order: descending by file name
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        let _ = update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+        assert_eq!(app.right_panel_selection.len(), 3);
+        click_row(&mut app, "/d/c.mp3", Modifiers::empty());
+        click_row(&mut app, "/d/a.mp3", Modifiers::SHIFT);
+        assert_eq!(
+            selected_paths(&app),
+            [
+                PathBuf::from("/d/a.mp3"),
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/c.mp3")
+            ]
+        );
+
+        // Removed tracks leave the selection, and switching tabs clears it
+        let _ =
+            update(&mut app, Message::RemoveFromRightPanel("/d/b.mp3".into()));
+        assert_eq!(app.right_panel_selection.len(), 2);
+        let _ = update(&mut app, Message::NewPlaylist);
+        let _ = update(&mut app, Message::SelectPlaylist(0));
+        assert!(app.right_panel_selection.is_empty());
+    }
+
+    /// Bulk actions remove, move, keep only, and copy the selected tracks,
+    /// and can be undone.
+    #[test]
+    fn test_right_panel_bulk_actions_on_selection() {
+        use iced::keyboard::Modifiers;
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        for path in ["/d/a.mp3", "/d/b.mp3", "/d/c.mp3", "/d/d.mp3"] {
+            let _ = update(&mut app, Message::AddToRightPanel(path.into()));
+        }
+        click_row(&mut app, "/d/b.mp3", Modifiers::empty());
+        click_row(&mut app, "/d/d.mp3", Modifiers::COMMAND);
+
+        let _ = update(
+            &mut app,
+            Message::MoveSelectedRightPanelFiles(RowMove::ToTop),
+        );
+        assert_eq!(app.playlist().sort_column, SortColumn::Manual);
+        assert_eq!(
+            playlist_paths(&app),
+            [
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/d.mp3"),
+                PathBuf::from("/d/a.mp3"),
+                PathBuf::from("/d/c.mp3")
+            ]
+        );
+        let _ = update(
+            &mut app,
+            Message::MoveSelectedRightPanelFiles(RowMove::ToBottom),
+        );
+        assert_eq!(
+            playlist_paths(&app),
+            [
+                PathBuf::from("/d/a.mp3"),
+                PathBuf::from("/d/c.mp3"),
+                PathBuf::from("/d/b.mp3"),
+                PathBuf::from("/d/d.mp3")
+            ]
+        );
+
+        let _ = update(&mut app, Message::NewPlaylist);
+        let _ = update(&mut app, Message::AddToRightPanel("/d/d.mp3".into()));
+        let _ = update(&mut app, Message::SelectPlaylist(0));
+        click_row(&mut app, "/d/c.mp3", Modifiers::empty());
+        click_row(&mut app, "/d/d.mp3", Modifiers::SHIFT);
+        let _ = update(&mut app, Message::CopySelectedToPlaylist(1));
+        assert_eq!(
+            app.playlists[1]
+                .files
+                .iter()
+                .map(|f| f.path.clone())
+                .collect::<Vec<_>>(),
+            [
+                PathBuf::from("/d/d.mp3"),
+                PathBuf::from("/d/c.mp3"),
+                PathBuf::from("/d/b.mp3")
+            ]
+        );
+        assert!(app.playlists[1].history.can_undo());
+
+        let _ = update(&mut app, Message::KeepOnlySelectedInRightPanel);
+        assert_eq!(app.playlist().files.len(), 3);
+        let _ = update(&mut app, Message::RemoveSelectedFromRightPanel);
+        assert!(app.playlist().files.is_empty());
+        assert!(app.right_panel_selection.is_empty());
+        let _ = update(&mut app, Message::UndoRightPanel);
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlist().files.len(), 4);
+    }
+
+    /// Columns can be hidden, moved, and resized, within bounds; the layout
+    /// is saved and restored by the next app.
+    #[test]
+    fn test_playlist_column_layout_is_persisted() {
+        use crate::gui::PlaylistColumn;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store =
+            SledStore::new(temp_dir.path().join("db").to_str().unwrap())
+                .unwrap();
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store.clone()),
+        );
+        assert_eq!(app.playlist_columns, default_column_layout());
+
+        let _ = update(
+            &mut app,
+            Message::TogglePlaylistColumn(PlaylistColumn::Directory),
+        );
+        let _ = update(
+            &mut app,
+            Message::MovePlaylistColumnLeft(PlaylistColumn::Title),
+        );
+        let _ = update(
+            &mut app,
+            Message::MovePlaylistColumnLeft(PlaylistColumn::Directory),
+        );
+        for _ in 0..MAX_COLUMN_WIDTH + 2 {
+            let _ = update(
+                &mut app,
+                Message::WidenPlaylistColumn(PlaylistColumn::Title),
+            );
+        }
+        let _ = update(
+            &mut app,
+            Message::NarrowPlaylistColumn(PlaylistColumn::File),
+        );
+        let order: Vec<PlaylistColumn> =
+            app.playlist_columns.iter().map(|e| e.column).collect();
+        assert_eq!(order.len(), PlaylistColumn::ALL.len());
+        assert_eq!(
+            order[..7],
+            [
+                PlaylistColumn::Directory,
+                PlaylistColumn::File,
+                PlaylistColumn::Creator,
+                PlaylistColumn::Title,
+                PlaylistColumn::Album,
+                PlaylistColumn::Genre,
+                PlaylistColumn::Duration,
+            ]
+        );
+        // Columns added after the original seven start hidden
+        assert!(app.playlist_columns[7..].iter().all(|e| !e.visible));
+        assert!(!app.playlist_columns[0].visible);
+        assert_eq!(app.playlist_columns[1].width, MIN_COLUMN_WIDTH);
+        assert_eq!(app.playlist_columns[3].width, MAX_COLUMN_WIDTH);
+
+        let restored = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            Some(store),
+        );
+        assert_eq!(restored.playlist_columns, app.playlist_columns);
+
+        // The last visible column cannot be hidden
+        for column in PlaylistColumn::ALL {
+            if app
+                .playlist_columns
+                .iter()
+                .any(|e| e.column == column && e.visible)
+            {
+                let _ = update(&mut app, Message::TogglePlaylistColumn(column));
+            }
+        }
+        let visible: Vec<PlaylistColumn> = app
+            .playlist_columns
+            .iter()
+            .filter(|e| e.visible)
+            .map(|e| e.column)
+            .collect();
+        assert_eq!(visible, [PlaylistColumn::Duration]);
+        let _ = update(&mut app, Message::ResetPlaylistColumns);
+        assert_eq!(app.playlist_columns, default_column_layout());
+    }
+
+    /// The track, disc, year and audio-property columns sort numerically,
+    /// missing values first, ties broken by file name.
+    #[test]
+    fn test_sort_right_panel_by_metadata_columns() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let file = |name: &str, disc, track, size| RightPanelFile {
+            path: PathBuf::from(format!("/d/{name}.mp3")),
+            disc_num: disc,
+            track_num: track,
+            file_size: size,
+            ..Default::default()
+        };
+        app.playlist_mut().files = vec![
+            file("a", Some(2), Some(1), Some(300)),
+            file("b", Some(1), Some(10), Some(100)),
+            file("c", None, Some(2), None),
+            file("d", Some(1), Some(2), Some(200)),
+        ];
+        let names = |app: &FileTreeApp| -> Vec<String> {
+            displayed_right_panel_files(app)
+                .iter()
+                .map(|f| f.path.file_stem().unwrap().to_string_lossy().into())
+                .collect()
+        };
+
+        let _ = update(
+            &mut app,
+            Message::SortRightPanelBy(SortColumn::TrackNumber),
+        );
+        assert_eq!(names(&app), ["a", "c", "d", "b"]);
+        let _ =
+            update(&mut app, Message::SortRightPanelBy(SortColumn::DiscNumber));
+        assert_eq!(app.playlist().sort_column, SortColumn::DiscNumber);
+        assert_eq!(names(&app), ["c", "b", "d", "a"]);
+        let _ =
+            update(&mut app, Message::SortRightPanelBy(SortColumn::FileSize));
+        let _ =
+            update(&mut app, Message::SortRightPanelBy(SortColumn::FileSize));
+        assert_eq!(app.playlist().sort_order, SortOrder::Desc);
+        assert_eq!(names(&app), ["a", "d", "b", "c"]);
+    }
+
+    /// Shift-clicking headers builds a sort stack: album, then disc, then
+    /// track. A further key cycles ascending, descending, and removed, and a
+    /// plain click on another column starts over with that column alone.
+    #[test]
+    fn test_sort_right_panel_by_several_keys() {
+        use iced::keyboard::Modifiers;
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let file = |name: &str, album: &str, disc, track| RightPanelFile {
+            path: PathBuf::from(format!("/d/{name}.mp3")),
+            album: Some(album.to_string()),
+            disc_num: Some(disc),
+            track_num: Some(track),
+            ..Default::default()
+        };
+        app.playlist_mut().files = vec![
+            file("a", "Y", 2, 1),
+            file("b", "X", 1, 2),
+            file("c", "Y", 1, 2),
+            file("d", "Y", 1, 1),
+            file("e", "X", 1, 1),
+        ];
+        let names = |app: &FileTreeApp| -> Vec<String> {
+            displayed_right_panel_files(app)
+                .iter()
+                .map(|f| f.path.file_stem().unwrap().to_string_lossy().into())
+                .collect()
+        };
+        let sort = |app: &mut FileTreeApp, column, modifiers| {
+            let _ = update(app, Message::KeyboardModifiersChanged(modifiers));
+            let _ = update(app, Message::SortRightPanelBy(column));
+        };
+
+        sort(&mut app, SortColumn::Album, Modifiers::empty());
+        sort(&mut app, SortColumn::DiscNumber, Modifiers::SHIFT);
+        sort(&mut app, SortColumn::TrackNumber, Modifiers::SHIFT);
+        assert_eq!(app.playlist().sort_column, SortColumn::Album);
+        assert_eq!(names(&app), ["e", "b", "d", "c", "a"]);
+
+        // A further key turns descending, then is removed
+        sort(&mut app, SortColumn::TrackNumber, Modifiers::SHIFT);
+        assert_eq!(names(&app), ["b", "e", "c", "d", "a"]);
+        sort(&mut app, SortColumn::TrackNumber, Modifiers::SHIFT);
+        assert_eq!(
+            app.playlist().then_by,
+            [SortKey { column: SortColumn::DiscNumber, order: SortOrder::Asc }]
+        );
+
+        // Clicking the main column reverses it and keeps the further keys
+        sort(&mut app, SortColumn::Album, Modifiers::empty());
+        assert_eq!(app.playlist().sort_order, SortOrder::Desc);
+        assert_eq!(app.playlist().then_by.len(), 1);
+        let _ = update(&mut app, Message::UndoRightPanel);
+        assert_eq!(app.playlist().sort_order, SortOrder::Asc);
+
+        sort(&mut app, SortColumn::TrackNumber, Modifiers::empty());
+        assert!(app.playlist().then_by.is_empty());
+        assert_eq!(names(&app), ["a", "d", "e", "b", "c"]);
+
+        // A manually ordered playlist has no sort to extend
+        let _ = update(&mut app, Message::SortRightPanelManually);
+        sort(&mut app, SortColumn::Album, Modifiers::SHIFT);
+        assert_eq!(app.playlist().sort_column, SortColumn::Album);
+        assert!(app.playlist().then_by.is_empty());
+    }
+
+    /// The right-panel filter narrows the rows by any metadata field, every
+    /// term having to match; selection and bulk actions only reach the
+    /// rows it shows.
+    #[test]
+    fn test_right_panel_filter_narrows_rows_and_bulk_actions() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let file = |name: &str, creator: &str, year| RightPanelFile {
+            path: PathBuf::from(format!("/music/{name}.mp3")),
+            creator: Some(creator.to_string()),
+            year: Some(year),
+            ..Default::default()
+        };
+        app.playlist_mut().files = vec![
+            file("a", "The Beatles", 1969),
+            file("b", "Nina Simone", 1965),
+            file("c", "The Beatles", 1965),
+            file("d", "Miles Davis", 1959),
+        ];
+        let shown = |app: &FileTreeApp| -> Vec<PathBuf> {
+            app.filtered_right_panel_files()
+                .into_iter()
+                .map(|f| f.path)
+                .collect()
+        };
+
+        let _ = update(
+            &mut app,
+            Message::SelectRightPanelFile("/music/d.mp3".into()),
+        );
+        let _ = update(
+            &mut app,
+            Message::RightPanelFilterChanged("beatles".into()),
+        );
+        assert_eq!(
+            shown(&app),
+            [PathBuf::from("/music/a.mp3"), PathBuf::from("/music/c.mp3")]
+        );
+        // A row the filter hides is no longer selected
+        assert!(app.right_panel_selection.is_empty());
+
+        let _ = update(
+            &mut app,
+            Message::RightPanelFilterChanged("BEATLES 1965".into()),
+        );
+        assert_eq!(shown(&app), [PathBuf::from("/music/c.mp3")]);
+        let _ =
+            update(&mut app, Message::RightPanelFilterChanged("1965".into()));
+        let _ = update(&mut app, Message::SelectAllRightPanelFiles);
+        assert_eq!(
+            selected_paths(&app),
+            [PathBuf::from("/music/b.mp3"), PathBuf::from("/music/c.mp3")]
+        );
+        let _ = update(&mut app, Message::RemoveSelectedFromRightPanel);
+        assert!(shown(&app).is_empty());
+
+        let _ = update(&mut app, Message::RightPanelFilterCleared);
+        assert_eq!(
+            shown(&app),
+            [PathBuf::from("/music/a.mp3"), PathBuf::from("/music/d.mp3")]
+        );
+        // Exports still see the whole playlist while a filter is set
+        let _ =
+            update(&mut app, Message::RightPanelFilterChanged("davis".into()));
+        assert_eq!(displayed_right_panel_files(&app).len(), 2);
+    }
+
+    /// The filter matches the formatted text of the number columns, as the
+    /// table shows it.
+    #[test]
+    fn test_right_panel_filter_matches_formatted_columns() {
+        let file = RightPanelFile {
+            path: PathBuf::from("/music/a.flac"),
+            duration_ms: Some(61_000),
+            bitrate_kbps: Some(320),
+            sample_rate_hz: Some(44_100),
+            channels: Some(2),
+            bit_depth: Some(24),
+            file_size: Some(5 * 1024 * 1024),
+            ..Default::default()
+        };
+        for filter in ["1:01", "320 kbps", "44.1 khz", "24 bit", "5.0 MB", "2"]
+        {
+            assert!(file.matches_filter(&filter_terms(filter)), "{filter}");
+        }
+        assert!(!file.matches_filter(&filter_terms("48.0 kHz")));
+        assert!(!file.matches_filter(&filter_terms("16 bit")));
+        assert!(file.matches_filter(&filter_terms("  ")));
+    }
+
+    /// Keep Only Selected and moving rows up or down leave the rows the
+    /// filter hides alone.
+    #[test]
+    fn test_right_panel_filter_keeps_hidden_rows_in_place() {
+        let mut app = FileTreeApp::new(
+            vec![],
+            &["mp3"],
+            PathBuf::from("/tmp/test.json"),
+            None,
+        );
+        let file = |name: &str, genre: &str| RightPanelFile {
+            path: PathBuf::from(format!("/music/{name}.mp3")),
+            genre: Some(genre.to_string()),
+            ..Default::default()
+        };
+        app.playlist_mut().files = vec![
+            file("a", "Jazz"),
+            file("b", "Rock"),
+            file("c", "Jazz"),
+            file("d", "Rock"),
+            file("e", "Jazz"),
+        ];
+        let _ = update(&mut app, Message::SortRightPanelManually);
+        let order = |app: &FileTreeApp| -> Vec<String> {
+            app.playlist()
+                .files
+                .iter()
+                .map(|f| f.path.file_stem().unwrap().to_string_lossy().into())
+                .collect()
+        };
+        let _ =
+            update(&mut app, Message::RightPanelFilterChanged("jazz".into()));
+
+        // Down passes the hidden "b" and lands after the next shown row
+        let _ = update(
+            &mut app,
+            Message::MoveRightPanelFile("/music/a.mp3".into(), RowMove::Down),
+        );
+        assert_eq!(order(&app), ["b", "c", "a", "d", "e"]);
+        let _ = update(
+            &mut app,
+            Message::MoveRightPanelFile("/music/e.mp3".into(), RowMove::Up),
+        );
+        assert_eq!(order(&app), ["b", "c", "e", "a", "d"]);
+
+        // The selected block swaps with shown rows only
+        let _ = update(
+            &mut app,
+            Message::SelectRightPanelFile("/music/a.mp3".into()),
+        );
+        let _ =
+            update(&mut app, Message::MoveSelectedRightPanelFiles(RowMove::Up));
+        assert_eq!(order(&app), ["b", "c", "a", "e", "d"]);
+
+        let _ = update(&mut app, Message::KeepOnlySelectedInRightPanel);
+        assert_eq!(order(&app), ["b", "a", "d"]);
+    }
 }
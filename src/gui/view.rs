@@ -1,7 +1,8 @@
 //! View composition for the Playlist UI.
 //!
 //! Assembles the full application layout: a horizontal split with a left
-//! panel (file/tag browser) and a right panel (playlist). Defines style
+//! panel (file/tag browser) and a right panel (playlist), above a status bar
+//! shown while there are notifications. Defines style
 //! structs (`MenuStyle`, `TreeBrowserStyle`, `ItemListStyle`) that control
 //! text sizes, spacing, and colours throughout the UI.
 //!
@@ -13,6 +14,7 @@
 
 use crate::gui::left_panel::create_left_panel;
 use crate::gui::right_panel::create_right_panel;
+use crate::gui::status_bar::create_status_bar;
 use crate::gui::{FileTreeApp, Message};
 use iced::{
     Element, Length,
@@ -43,6 +45,10 @@ pub(crate) struct ItemListStyle {
     pub right_panel_width: u16,
     pub light_row_shade: [f32; 3],
     pub dark_row_shade: [f32; 3],
+    /// Background of the playlist row a dragged row will be dropped on.
+    pub drop_target_shade: [f32; 3],
+    /// Background of selected playlist rows.
+    pub selected_row_shade: [f32; 3],
 }
 
 /// Composes the entire application UI, including the left and right panels,
@@ -80,6 +86,8 @@ pub fn view(app: &FileTreeApp) -> Element<'_, Message> {
         right_panel_width: if app.left_panel_expanded { 3 } else { 20 },
         light_row_shade: [0.13, 0.13, 0.13],
         dark_row_shade: [0.16, 0.16, 0.16],
+        drop_target_shade: [0.0, 0.3, 0.3],
+        selected_row_shade: [0.2, 0.2, 0.45],
     };
 
     let left_content = create_left_panel(
@@ -110,7 +118,14 @@ pub fn view(app: &FileTreeApp) -> Element<'_, Message> {
         .width(Length::Fill)
         .height(Length::Fill);
 
-    container::<Message, iced::Theme, iced::Renderer>(split_row)
+    let mut content = iced::widget::Column::new().push(split_row);
+    if let Some(status_bar) =
+        create_status_bar(app, menu_style, item_list_style.row_text_size)
+    {
+        content = content.push(status_bar);
+    }
+
+    container::<Message, iced::Theme, iced::Renderer>(content)
         .width(Length::Fill)
         .height(Length::Fill)
         .style(|_theme| iced::widget::container::Style {
@@ -493,10 +508,7 @@ mod iced_tests {
 
             // Assert that state is unchanged
             assert_eq!(app.selected_extensions, prev_state.selected_extensions);
-            assert_eq!(
-                app.right_panel_shuffled,
-                prev_state.right_panel_shuffled
-            );
+            assert_eq!(app.playlist().shuffled, prev_state.playlist().shuffled);
         }
 
         #[test]
@@ -628,7 +640,7 @@ mod iced_tests {
                 FileTreeApp::new(vec![], &["txt"], PathBuf::from("/tmp"), None);
             let msg = Message::AddToRightPanel(file_path.clone());
             let _ = update(&mut app, msg);
-            assert!(app.right_panel_files.iter().any(|f| f.path == file_path));
+            assert!(app.playlist().files.iter().any(|f| f.path == file_path));
         }
 
         #[test]
@@ -660,8 +672,8 @@ mod iced_tests {
 
             let msg = Message::AddDirectoryToRightPanel(dir_path.clone());
             let _ = update(&mut app, msg);
-            assert!(app.right_panel_files.iter().any(|f| f.path == file1));
-            assert!(app.right_panel_files.iter().any(|f| f.path == file2));
+            assert!(app.playlist().files.iter().any(|f| f.path == file1));
+            assert!(app.playlist().files.iter().any(|f| f.path == file2));
         }
 
         #[test]
@@ -669,17 +681,18 @@ mod iced_tests {
             let file_path = PathBuf::from("/file.txt");
             let mut app =
                 FileTreeApp::new(vec![], &["txt"], PathBuf::from("/tmp"), None);
-            app.right_panel_files.push(RightPanelFile {
+            app.playlist_mut().files.push(RightPanelFile {
                 path: file_path.clone(),
                 creator: None,
                 album: None,
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             });
             let msg = Message::RemoveFromRightPanel(file_path.clone());
             let _ = update(&mut app, msg);
-            assert!(!app.right_panel_files.iter().any(|f| f.path == file_path));
+            assert!(!app.playlist().files.iter().any(|f| f.path == file_path));
         }
 
         #[test]
@@ -694,6 +707,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let right_panel_file2 = RightPanelFile {
                 path: PathBuf::from("/dir/file2.txt"),
@@ -702,6 +716,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let right_panel_file3 = RightPanelFile {
                 path: PathBuf::from("/other/file3.txt"),
@@ -710,8 +725,9 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
-            app.right_panel_files = vec![
+            app.playlist_mut().files = vec![
                 right_panel_file1.clone(),
                 right_panel_file2.clone(),
                 right_panel_file3.clone(),
@@ -719,17 +735,20 @@ mod iced_tests {
             let msg = Message::RemoveDirectoryFromRightPanel(dir_path.clone());
             let _ = update(&mut app, msg);
             assert!(
-                !app.right_panel_files
+                !app.playlist()
+                    .files
                     .iter()
                     .any(|f| f.path == right_panel_file1.path)
             );
             assert!(
-                !app.right_panel_files
+                !app.playlist()
+                    .files
                     .iter()
                     .any(|f| f.path == right_panel_file2.path)
             );
             assert!(
-                app.right_panel_files
+                app.playlist()
+                    .files
                     .iter()
                     .any(|f| f.path == right_panel_file3.path)
             );
@@ -748,6 +767,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let right_panel_file_b = RightPanelFile {
                 path: file_b.clone(),
@@ -756,22 +776,26 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
-            app.right_panel_files =
+            app.playlist_mut().files =
                 vec![right_panel_file_b.clone(), right_panel_file_a.clone()];
 
-            let msg = Message::SortRightPanelByDirectory;
+            let msg = Message::SortRightPanelBy(SortColumn::Directory);
             let _ = update(&mut app, msg);
-            assert_eq!(app.right_panel_sort_column, SortColumn::Directory);
-            assert_eq!(app.right_panel_sort_order, SortOrder::Desc);
+            assert_eq!(app.playlist().sort_column, SortColumn::Directory);
+            assert_eq!(app.playlist().sort_order, SortOrder::Desc);
 
-            let _ = update(&mut app, Message::SortRightPanelByDirectory);
-            assert_eq!(app.right_panel_sort_order, SortOrder::Asc);
+            let _ = update(
+                &mut app,
+                Message::SortRightPanelBy(SortColumn::Directory),
+            );
+            assert_eq!(app.playlist().sort_order, SortOrder::Asc);
 
-            let msg = Message::SortRightPanelByFile;
+            let msg = Message::SortRightPanelBy(SortColumn::File);
             let _ = update(&mut app, msg);
-            assert_eq!(app.right_panel_sort_column, SortColumn::File);
-            assert_eq!(app.right_panel_sort_order, SortOrder::Asc);
+            assert_eq!(app.playlist().sort_column, SortColumn::File);
+            assert_eq!(app.playlist().sort_order, SortOrder::Asc);
         }
 
         #[test]
@@ -787,6 +811,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let right_panel_file2 = RightPanelFile {
                 path: file2.clone(),
@@ -795,12 +820,13 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
-            app.right_panel_files =
+            app.playlist_mut().files =
                 vec![right_panel_file1.clone(), right_panel_file2.clone()];
             let msg = Message::ShuffleRightPanel;
             let _ = update(&mut app, msg);
-            assert!(app.right_panel_shuffled);
+            assert!(app.playlist().shuffled);
         }
 
         #[test]
@@ -815,13 +841,15 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
-            app.right_panel_files.push(right_panel_file.clone());
+            app.playlist_mut().files.push(right_panel_file.clone());
             let msg = Message::AddToRightPanel(file_path.clone());
             let _ = update(&mut app, msg);
             // Should not add duplicate
             assert_eq!(
-                app.right_panel_files
+                app.playlist()
+                    .files
                     .iter()
                     .filter(|p| **p == right_panel_file)
                     .count(),
@@ -838,7 +866,7 @@ mod iced_tests {
             let msg = Message::RemoveFromRightPanel(file_path.clone());
             let _ = update(&mut app, msg);
             // Should not panic and list remains empty
-            assert!(app.right_panel_files.is_empty());
+            assert!(app.playlist().files.is_empty());
         }
 
         #[test]
@@ -853,12 +881,13 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
-            app.right_panel_files = vec![right_panel_file];
+            app.playlist_mut().files = vec![right_panel_file];
             let msg = Message::RemoveDirectoryFromRightPanel(dir_path.clone());
             let _ = update(&mut app, msg);
             // Should not remove unrelated files
-            assert_eq!(app.right_panel_files.len(), 1);
+            assert_eq!(app.playlist().files.len(), 1);
         }
 
         #[test]
@@ -866,8 +895,11 @@ mod iced_tests {
             let mut app =
                 FileTreeApp::new(vec![], &["txt"], PathBuf::from("/tmp"), None);
             // Empty list
-            let _ = update(&mut app, Message::SortRightPanelByDirectory);
-            assert!(app.right_panel_files.is_empty());
+            let _ = update(
+                &mut app,
+                Message::SortRightPanelBy(SortColumn::Directory),
+            );
+            assert!(app.playlist().files.is_empty());
 
             // Single item
             let right_panel_file = RightPanelFile {
@@ -877,11 +909,13 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
-            app.right_panel_files.push(right_panel_file.clone());
-            let _ = update(&mut app, Message::SortRightPanelByFile);
-            assert_eq!(app.right_panel_files.len(), 1);
-            assert_eq!(app.right_panel_files[0], right_panel_file);
+            app.playlist_mut().files.push(right_panel_file.clone());
+            let _ =
+                update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+            assert_eq!(app.playlist().files.len(), 1);
+            assert_eq!(app.playlist().files[0], right_panel_file);
         }
 
         #[test]
@@ -890,7 +924,7 @@ mod iced_tests {
                 FileTreeApp::new(vec![], &["txt"], PathBuf::from("/tmp"), None);
             // Empty list
             let _ = update(&mut app, Message::ShuffleRightPanel);
-            assert!(app.right_panel_shuffled);
+            assert!(app.playlist().shuffled);
 
             // Single item
             let right_panel_file = RightPanelFile {
@@ -900,12 +934,13 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
-            app.right_panel_files.push(right_panel_file.clone());
+            app.playlist_mut().files.push(right_panel_file.clone());
             let _ = update(&mut app, Message::ShuffleRightPanel);
-            assert!(app.right_panel_shuffled);
-            assert_eq!(app.right_panel_files.len(), 1);
-            assert_eq!(app.right_panel_files[0], right_panel_file);
+            assert!(app.playlist().shuffled);
+            assert_eq!(app.playlist().files.len(), 1);
+            assert_eq!(app.playlist().files[0], right_panel_file);
         }
 
         #[test]
@@ -917,6 +952,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let right_panel_file2 = RightPanelFile {
                 path: PathBuf::from("/dir_a/file2.txt"),
@@ -925,23 +961,28 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let mut app =
                 FileTreeApp::new(vec![], &["txt"], PathBuf::from("/tmp"), None);
-            app.right_panel_files =
+            app.playlist_mut().files =
                 vec![right_panel_file1.clone(), right_panel_file2.clone()];
 
             // Sort
-            let _ = update(&mut app, Message::SortRightPanelByDirectory);
-            assert!(!app.right_panel_shuffled);
+            let _ = update(
+                &mut app,
+                Message::SortRightPanelBy(SortColumn::Directory),
+            );
+            assert!(!app.playlist().shuffled);
 
             // Shuffle
             let _ = update(&mut app, Message::ShuffleRightPanel);
-            assert!(app.right_panel_shuffled);
+            assert!(app.playlist().shuffled);
 
             // Sort again
-            let _ = update(&mut app, Message::SortRightPanelByFile);
-            assert!(!app.right_panel_shuffled);
+            let _ =
+                update(&mut app, Message::SortRightPanelBy(SortColumn::File));
+            assert!(!app.playlist().shuffled);
         }
 
         #[test]
@@ -1130,7 +1171,7 @@ mod iced_tests {
         fn test_clear_right_panel() {
             let mut app =
                 FileTreeApp::new(vec![], &["txt"], PathBuf::from("/tmp"), None);
-            app.right_panel_files = vec![
+            app.playlist_mut().files = vec![
                 RightPanelFile {
                     path: PathBuf::from("/a/one.txt"),
                     creator: None,
@@ -1138,6 +1179,7 @@ mod iced_tests {
                     title: None,
                     genre: None,
                     duration_ms: None,
+                    ..Default::default()
                 },
                 RightPanelFile {
                     path: PathBuf::from("/b/two.txt"),
@@ -1146,12 +1188,13 @@ mod iced_tests {
                     title: None,
                     genre: None,
                     duration_ms: None,
+                    ..Default::default()
                 },
             ];
             let _ = update(&mut app, Message::ClearRightPanel);
             assert!(
-                app.right_panel_files.is_empty(),
-                "right_panel_files should be empty after ClearRightPanel"
+                app.playlist().files.is_empty(),
+                "playlist files should be empty after ClearRightPanel"
             );
         }
 
@@ -1159,10 +1202,10 @@ mod iced_tests {
         fn test_clear_right_panel_when_already_empty() {
             let mut app =
                 FileTreeApp::new(vec![], &["txt"], PathBuf::from("/tmp"), None);
-            assert!(app.right_panel_files.is_empty());
+            assert!(app.playlist().files.is_empty());
             let _ = update(&mut app, Message::ClearRightPanel);
             assert!(
-                app.right_panel_files.is_empty(),
+                app.playlist().files.is_empty(),
                 "ClearRightPanel on an empty playlist should be a no-op"
             );
         }
@@ -1171,7 +1214,7 @@ mod iced_tests {
         fn test_clear_right_panel_resets_shuffle_flag() {
             let mut app =
                 FileTreeApp::new(vec![], &["txt"], PathBuf::from("/tmp"), None);
-            app.right_panel_files = vec![
+            app.playlist_mut().files = vec![
                 RightPanelFile {
                     path: PathBuf::from("/a/one.txt"),
                     creator: None,
@@ -1179,6 +1222,7 @@ mod iced_tests {
                     title: None,
                     genre: None,
                     duration_ms: None,
+                    ..Default::default()
                 },
                 RightPanelFile {
                     path: PathBuf::from("/b/two.txt"),
@@ -1187,21 +1231,22 @@ mod iced_tests {
                     title: None,
                     genre: None,
                     duration_ms: None,
+                    ..Default::default()
                 },
             ];
             let _ = update(&mut app, Message::ShuffleRightPanel);
             assert!(
-                app.right_panel_shuffled,
-                "right_panel_shuffled should be true after shuffle"
+                app.playlist().shuffled,
+                "playlist shuffled flag should be true after shuffle"
             );
             let _ = update(&mut app, Message::ClearRightPanel);
             assert!(
-                app.right_panel_files.is_empty(),
-                "right_panel_files should be empty after ClearRightPanel"
+                app.playlist().files.is_empty(),
+                "playlist files should be empty after ClearRightPanel"
             );
             assert!(
-                !app.right_panel_shuffled,
-                "right_panel_shuffled should be reset to false after ClearRightPanel"
+                !app.playlist().shuffled,
+                "playlist shuffled flag should be reset to false after ClearRightPanel"
             );
         }
 
@@ -1253,13 +1298,14 @@ mod iced_tests {
                 FileTreeApp::new(vec![], &["mp3"], PathBuf::from("/tmp"), None);
             // Insert in reverse-alpha order to expose missing secondary sort key
             for name in &["c.mp3", "b.mp3", "a.mp3"] {
-                app.right_panel_files.push(RightPanelFile {
+                app.playlist_mut().files.push(RightPanelFile {
                     path: PathBuf::from("/music").join(name),
                     creator: None,
                     album: None,
                     title: None,
                     genre: None,
                     duration_ms: None,
+                    ..Default::default()
                 });
             }
             let sorted = app.sorted_right_panel_files();
@@ -1378,7 +1424,7 @@ mod iced_tests {
             );
             let _ = update(&mut app, Message::ShuffleRightPanel);
             assert!(
-                app.right_panel_shuffled,
+                app.playlist().shuffled,
                 "should be shuffled after ShuffleRightPanel"
             );
 
@@ -1389,8 +1435,8 @@ mod iced_tests {
                 Message::AddDirectoryToRightPanel(dir_path.clone()),
             );
             assert!(
-                !app.right_panel_shuffled,
-                "adding a directory should reset right_panel_shuffled to false"
+                !app.playlist().shuffled,
+                "adding a directory should reset the playlist shuffled flag to false"
             );
         }
 
@@ -1398,17 +1444,18 @@ mod iced_tests {
         fn test_add_single_file_resets_shuffle_flag() {
             let mut app =
                 FileTreeApp::new(vec![], &["mp3"], PathBuf::from("/tmp"), None);
-            app.right_panel_files.push(RightPanelFile {
+            app.playlist_mut().files.push(RightPanelFile {
                 path: PathBuf::from("/music/a.mp3"),
                 creator: None,
                 album: None,
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             });
             let _ = update(&mut app, Message::ShuffleRightPanel);
             assert!(
-                app.right_panel_shuffled,
+                app.playlist().shuffled,
                 "should be shuffled after ShuffleRightPanel"
             );
 
@@ -1417,8 +1464,8 @@ mod iced_tests {
                 Message::AddToRightPanel(PathBuf::from("/music/b.mp3")),
             );
             assert!(
-                !app.right_panel_shuffled,
-                "adding a single file should reset right_panel_shuffled to false"
+                !app.playlist().shuffled,
+                "adding a single file should reset the playlist shuffled flag to false"
             );
         }
 
@@ -1427,17 +1474,18 @@ mod iced_tests {
             use crate::gui::TagTreeNode;
             let mut app =
                 FileTreeApp::new(vec![], &["mp3"], PathBuf::from("/tmp"), None);
-            app.right_panel_files.push(RightPanelFile {
+            app.playlist_mut().files.push(RightPanelFile {
                 path: PathBuf::from("/music/a.mp3"),
                 creator: None,
                 album: None,
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             });
             let _ = update(&mut app, Message::ShuffleRightPanel);
             assert!(
-                app.right_panel_shuffled,
+                app.playlist().shuffled,
                 "should be shuffled after ShuffleRightPanel"
             );
 
@@ -1454,8 +1502,8 @@ mod iced_tests {
                 Message::AddTagNodeToRightPanel(vec!["Rock".to_string()]),
             );
             assert!(
-                !app.right_panel_shuffled,
-                "adding a tag node should reset right_panel_shuffled to false"
+                !app.playlist().shuffled,
+                "adding a tag node should reset the playlist shuffled flag to false"
             );
         }
     }
@@ -1716,7 +1764,7 @@ mod iced_tests {
             let msg_add = Message::AddToRightPanel(file_path.clone());
             let _ = update(&mut app, msg_add);
             assert!(
-                app.right_panel_files.iter().any(|f| f.path == file_path),
+                app.playlist().files.iter().any(|f| f.path == file_path),
                 "File should be in right panel after adding"
             );
 
@@ -1724,7 +1772,7 @@ mod iced_tests {
             let msg_remove = Message::RemoveFromRightPanel(file_path.clone());
             let _ = update(&mut app, msg_remove);
             assert!(
-                !app.right_panel_files.iter().any(|f| f.path == file_path),
+                !app.playlist().files.iter().any(|f| f.path == file_path),
                 "File should not be in right panel after removing"
             );
         }
@@ -1739,6 +1787,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let right_panel_file2 = RightPanelFile {
                 path: dir_path.join("file2.txt"),
@@ -1747,6 +1796,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let right_panel_file3 = RightPanelFile {
                 path: std::path::PathBuf::from("/other/file3.txt"),
@@ -1755,6 +1805,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let file_extensions = &["txt"];
             let temp_file = tempfile::NamedTempFile::new().unwrap();
@@ -1762,7 +1813,7 @@ mod iced_tests {
             let mut app =
                 FileTreeApp::new(vec![], file_extensions, persist_path, None);
 
-            app.right_panel_files = vec![
+            app.playlist_mut().files = vec![
                 right_panel_file1.clone(),
                 right_panel_file2.clone(),
                 right_panel_file3.clone(),
@@ -1774,19 +1825,22 @@ mod iced_tests {
             let _ = update(&mut app, msg_remove_dir);
 
             assert!(
-                !app.right_panel_files
+                !app.playlist()
+                    .files
                     .iter()
                     .any(|f| f.path == right_panel_file1.path),
                 "file1 should be removed from right panel"
             );
             assert!(
-                !app.right_panel_files
+                !app.playlist()
+                    .files
                     .iter()
                     .any(|f| f.path == right_panel_file2.path),
                 "file2 should be removed from right panel"
             );
             assert!(
-                app.right_panel_files
+                app.playlist()
+                    .files
                     .iter()
                     .any(|f| f.path == right_panel_file3.path),
                 "file3 should be removed from right panel"
@@ -1802,6 +1856,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let right_panel_file2 = RightPanelFile {
                 path: std::path::PathBuf::from("/dir_a/file2.txt"),
@@ -1810,6 +1865,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let file_extensions = &["txt"];
             let temp_file = tempfile::NamedTempFile::new().unwrap();
@@ -1817,26 +1873,26 @@ mod iced_tests {
             let mut app =
                 FileTreeApp::new(vec![], file_extensions, persist_path, None);
 
-            app.right_panel_files =
+            app.playlist_mut().files =
                 vec![right_panel_file1.clone(), right_panel_file2.clone()];
 
             // Shuffle right panel
             let msg_shuffle = Message::ShuffleRightPanel;
             let _ = update(&mut app, msg_shuffle);
             assert!(
-                app.right_panel_shuffled,
+                app.playlist().shuffled,
                 "Right panel should be marked as shuffled"
             );
 
             // Sort right panel by directory
-            let msg_sort = Message::SortRightPanelByDirectory;
+            let msg_sort = Message::SortRightPanelBy(SortColumn::Directory);
             let _ = update(&mut app, msg_sort);
             assert!(
-                !app.right_panel_shuffled,
+                !app.playlist().shuffled,
                 "Right panel should not be marked as shuffled after sorting"
             );
             assert_eq!(
-                app.right_panel_sort_column,
+                app.playlist().sort_column,
                 SortColumn::Directory,
                 "Sort column should be Directory"
             );
@@ -1875,11 +1931,11 @@ mod iced_tests {
             let _ = update(&mut app, msg);
 
             assert!(
-                app.right_panel_files.iter().any(|f| f.path == file1),
+                app.playlist().files.iter().any(|f| f.path == file1),
                 "file1 should be removed from right panel"
             );
             assert!(
-                app.right_panel_files.iter().any(|f| f.path == file2),
+                app.playlist().files.iter().any(|f| f.path == file2),
                 "file2 should be removed from right panel"
             );
         }
@@ -1958,7 +2014,7 @@ mod iced_tests {
 
             // Should not panic and list remains empty
             assert!(
-                app.right_panel_files.is_empty(),
+                app.playlist().files.is_empty(),
                 "Right panel should remain empty"
             );
         }
@@ -1973,6 +2029,7 @@ mod iced_tests {
                 title: None,
                 genre: None,
                 duration_ms: None,
+                ..Default::default()
             };
             let file_extensions = &["txt"];
             let temp_file = tempfile::NamedTempFile::new().unwrap();
@@ -1980,19 +2037,20 @@ mod iced_tests {
             let mut app =
                 FileTreeApp::new(vec![], file_extensions, persist_path, None);
 
-            app.right_panel_files = vec![right_panel_file.clone()];
+            app.playlist_mut().files = vec![right_panel_file.clone()];
 
             let msg = Message::RemoveDirectoryFromRightPanel(dir_path.clone());
             let _ = update(&mut app, msg);
 
             // Should not remove unrelated files
             assert_eq!(
-                app.right_panel_files.len(),
+                app.playlist().files.len(),
                 1,
                 "Unrelated file should remain"
             );
             assert!(
-                app.right_panel_files
+                app.playlist()
+                    .files
                     .iter()
                     .any(|f| f.path == right_panel_file.path),
                 "Unrelated file should remain"
@@ -2149,19 +2207,19 @@ mod iced_tests {
             // Add file
             let _ =
                 update(&mut app, Message::AddToRightPanel(file_path.clone()));
-            assert!(app.right_panel_files.iter().any(|f| f.path == file_path));
+            assert!(app.playlist().files.iter().any(|f| f.path == file_path));
 
             // Remove file
             let _ = update(
                 &mut app,
                 Message::RemoveFromRightPanel(file_path.clone()),
             );
-            assert!(!app.right_panel_files.iter().any(|f| f.path == file_path));
+            assert!(!app.playlist().files.iter().any(|f| f.path == file_path));
 
             // Add again
             let _ =
                 update(&mut app, Message::AddToRightPanel(file_path.clone()));
-            assert!(app.right_panel_files.iter().any(|f| f.path == file_path));
+            assert!(app.playlist().files.iter().any(|f| f.path == file_path));
         }
 
         #[test]
@@ -2260,8 +2318,8 @@ mod iced_tests {
             // Add both files
             let _ = update(&mut app, Message::AddToRightPanel(file1.clone()));
             let _ = update(&mut app, Message::AddToRightPanel(file2.clone()));
-            assert!(app.right_panel_files.iter().any(|f| f.path == file1));
-            assert!(app.right_panel_files.iter().any(|f| f.path == file2));
+            assert!(app.playlist().files.iter().any(|f| f.path == file1));
+            assert!(app.playlist().files.iter().any(|f| f.path == file2));
 
             // Toggle extensions off
             let _ =
@@ -2274,8 +2332,8 @@ mod iced_tests {
             // Remove one file
             let _ =
                 update(&mut app, Message::RemoveFromRightPanel(file1.clone()));
-            assert!(!app.right_panel_files.iter().any(|f| f.path == file1));
-            assert!(app.right_panel_files.iter().any(|f| f.path == file2));
+            assert!(!app.playlist().files.iter().any(|f| f.path == file1));
+            assert!(app.playlist().files.iter().any(|f| f.path == file2));
 
             // Toggle extensions on again
             let _ =
@@ -2403,7 +2461,7 @@ mod iced_tests {
                 FileTreeApp::new(vec![], &["txt"], persist_path, None);
 
             // Populate the playlist
-            app.right_panel_files = vec![
+            app.playlist_mut().files = vec![
                 RightPanelFile {
                     path: PathBuf::from("/a/track1.txt"),
                     creator: None,
@@ -2411,6 +2469,7 @@ mod iced_tests {
                     title: None,
                     genre: None,
                     duration_ms: None,
+                    ..Default::default()
                 },
                 RightPanelFile {
                     path: PathBuf::from("/b/track2.txt"),
@@ -2419,6 +2478,7 @@ mod iced_tests {
                     title: None,
                     genre: None,
                     duration_ms: None,
+                    ..Default::default()
                 },
                 RightPanelFile {
                     path: PathBuf::from("/c/track3.txt"),
@@ -2427,10 +2487,11 @@ mod iced_tests {
                     title: None,
                     genre: None,
                     duration_ms: None,
+                    ..Default::default()
                 },
             ];
             assert_eq!(
-                app.right_panel_files.len(),
+                app.playlist().files.len(),
                 3,
                 "playlist should have 3 entries before clearing"
             );
@@ -2444,7 +2505,7 @@ mod iced_tests {
             let _ = update(&mut app, Message::ClearRightPanel);
 
             assert!(
-                app.right_panel_files.is_empty(),
+                app.playlist().files.is_empty(),
                 "playlist should be empty after ClearRightPanel"
             );
 
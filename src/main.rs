@@ -10,11 +10,12 @@
 mod db;
 mod fs;
 mod gui;
+mod player;
 mod utils;
 
 use crate::db::sled_store::SledStore;
 use crate::fs::media_metadata::{build_creator_tag_tree, build_genre_tag_tree};
-use gui::{FileTreeApp, update, view};
+use gui::{FileTreeApp, read_restored_files_task, subscription, update, view};
 use std::path::PathBuf;
 
 // Currently, Sled database is not incrementally updated when tags from media
@@ -37,37 +38,47 @@ fn main() -> iced::Result {
     let sled_store = SledStore::new(get_sled_db_path().to_str().unwrap())
         .expect("Failed to open sled db");
 
-    iced::application("File Tree Viewer", update, view).run_with(move || {
-        let mut app = FileTreeApp::load(
-            AUDIO_EXPORT_EXTENSIONS,
-            None,
-            Some(sled_store.clone()),
-        );
+    iced::application("File Tree Viewer", update, view)
+        .subscription(subscription)
+        .run_with(move || {
+            let mut app = FileTreeApp::load(
+                AUDIO_EXPORT_EXTENSIONS,
+                None,
+                Some(sled_store.clone()),
+            );
 
-        // Ensure genre tag tree is present in sled
-        if sled_store.load_genre_tag_tree().is_none() {
-            let tree =
-                build_genre_tag_tree(&app.top_dirs, &app.selected_extensions);
-            if let Err(e) = sled_store.save_genre_tag_tree(&tree) {
-                log::warn!("Failed to save genre tag tree: {e}");
+            // Ensure genre tag tree is present in sled
+            if sled_store.load_genre_tag_tree().is_none() {
+                let tree = build_genre_tag_tree(
+                    &app.top_dirs,
+                    &app.selected_extensions,
+                );
+                if let Err(e) = sled_store.save_genre_tag_tree(&tree) {
+                    app.notifications
+                        .warning(format!("Failed to save genre tag tree: {e}"));
+                }
             }
-        }
 
-        // Ensure creator tree is present in sled
-        if sled_store.load_creator_tag_tree().is_none() {
-            let tree =
-                build_creator_tag_tree(&app.top_dirs, &app.selected_extensions);
-            if let Err(e) = sled_store.save_creator_tag_tree(&tree) {
-                log::warn!("Failed to save creator tag tree: {e}");
+            // Ensure creator tree is present in sled
+            if sled_store.load_creator_tag_tree().is_none() {
+                let tree = build_creator_tag_tree(
+                    &app.top_dirs,
+                    &app.selected_extensions,
+                );
+                if let Err(e) = sled_store.save_creator_tag_tree(&tree) {
+                    app.notifications.warning(format!(
+                        "Failed to save creator tag tree: {e}"
+                    ));
+                }
             }
-        }
 
-        // load the genre tree into app.tag_tree_roots if you want to start in
-        // genre tag tree mode
-        if let Some(tree) = sled_store.load_genre_tag_tree() {
-            app.tag_tree_roots = tree;
-        }
+            // load the genre tree into app.tag_tree_roots if you want to start in
+            // genre tag tree mode
+            if let Some(tree) = sled_store.load_genre_tag_tree() {
+                app.tag_tree_roots = tree;
+            }
 
-        (app, iced::Task::none())
-    })
+            let task = read_restored_files_task(&app);
+            (app, task)
+        })
 }
@@ -0,0 +1,340 @@
+//! External player profiles and launching.
+//!
+//! A `PlayerProfile` names an executable, an argument template, and the
+//! playlist format the player prefers. In the template, `{playlist}` is
+//! replaced by the path of a playlist written for the launch, and a `{files}`
+//! argument is repeated once per track path. A template with neither
+//! placeholder gets the playlist path appended. Arguments are separated by
+//! whitespace; double quotes keep an argument with spaces together.
+//!
+//! Each launch writes its playlist to a new, uniquely named file in the
+//! system temp directory, so a second play never overwrites the playlist a
+//! player is still reading. The executable is looked up on `PATH` first, so a
+//! missing player is reported by name instead of as a bare spawn error.
+//!
+//! Public API:
+//!     PlayerProfile     — executable, argument template, playlist format
+//!     default_profiles  — the profiles offered before any are saved
+//!     find_executable   — resolve an executable name against `PATH`
+//!     launch_player     — write the playlist and start the player
+
+use crate::fs::playlist_export::{
+    ExportOptions, PlaylistFormat, PlaylistInfo, export_playlist,
+};
+use crate::gui::RightPanelFile;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How to start one external player.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    bincode::Encode,
+    bincode::Decode,
+)]
+pub(crate) struct PlayerProfile {
+    pub name: String,
+    /// Executable name looked up on `PATH`, or a path to the executable.
+    pub executable: String,
+    /// Argument template with `{playlist}` and `{files}` placeholders.
+    pub args: String,
+    /// Format of the playlist written for `{playlist}`.
+    pub format: PlaylistFormat,
+}
+
+impl Default for PlayerProfile {
+    fn default() -> Self {
+        PlayerProfile {
+            name: String::new(),
+            executable: String::new(),
+            args: "{playlist}".to_string(),
+            format: PlaylistFormat::M3u,
+        }
+    }
+}
+
+impl PlayerProfile {
+    fn new(
+        name: &str,
+        executable: &str,
+        args: &str,
+        format: PlaylistFormat,
+    ) -> Self {
+        PlayerProfile {
+            name: name.to_string(),
+            executable: executable.to_string(),
+            args: args.to_string(),
+            format,
+        }
+    }
+
+    /// Whether a launch needs a playlist file, i.e. the template uses
+    /// `{playlist}` or lists no `{files}`.
+    pub(crate) fn uses_playlist(&self) -> bool {
+        self.args.contains("{playlist}") || !self.args.contains("{files}")
+    }
+
+    /// Expands the argument template for a launch with the given playlist
+    /// file and track paths.
+    pub(crate) fn command_args(
+        &self,
+        playlist: Option<&Path>,
+        files: &[PathBuf],
+    ) -> Vec<OsString> {
+        let expand = |word: &str, placeholder: &str, path: &Path| {
+            if word == placeholder {
+                path.as_os_str().to_os_string()
+            } else {
+                word.replace(placeholder, &path.to_string_lossy()).into()
+            }
+        };
+
+        let mut args = Vec::new();
+        for word in split_args(&self.args) {
+            if word.contains("{files}") {
+                args.extend(
+                    files.iter().map(|file| expand(&word, "{files}", file)),
+                );
+            } else if let Some(playlist) = playlist
+                && word.contains("{playlist}")
+            {
+                args.push(expand(&word, "{playlist}", playlist));
+            } else {
+                args.push(word.into());
+            }
+        }
+        if let Some(playlist) = playlist
+            && !self.args.contains("{playlist}")
+            && !self.args.contains("{files}")
+        {
+            args.push(playlist.as_os_str().to_os_string());
+        }
+        args
+    }
+}
+
+/// The profiles offered until the user saves their own.
+pub(crate) fn default_profiles() -> Vec<PlayerProfile> {
+    vec![
+        PlayerProfile::new("VLC", "vlc", "{playlist}", PlaylistFormat::Xspf),
+        PlayerProfile::new(
+            "mpv",
+            "mpv",
+            "--playlist={playlist}",
+            PlaylistFormat::M3u,
+        ),
+        PlayerProfile::new(
+            "Audacious",
+            "audacious",
+            "{playlist}",
+            PlaylistFormat::M3u,
+        ),
+        PlayerProfile::new(
+            "Strawberry",
+            "strawberry",
+            "--load {playlist}",
+            PlaylistFormat::Xspf,
+        ),
+    ]
+}
+
+/// Splits an argument template on whitespace, keeping double-quoted runs
+/// (without their quotes) inside one argument.
+fn split_args(template: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+    for c in template.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            },
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            },
+            c => {
+                current.push(c);
+                in_word = true;
+            },
+        }
+    }
+    if in_word {
+        args.push(current);
+    }
+    args
+}
+
+/// Resolves `executable` to a file: a path (anything with a separator) is
+/// checked as given, a bare name is searched for in the `PATH` directories.
+/// On Windows the usual executable extensions are tried as well.
+pub(crate) fn find_executable(executable: &str) -> Option<PathBuf> {
+    let path = Path::new(executable);
+    if executable.is_empty() {
+        return None;
+    }
+    if path.components().count() > 1 || path.is_absolute() {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let candidates = |dir: &Path| {
+        let mut names = vec![dir.join(executable)];
+        if cfg!(windows) && path.extension().is_none() {
+            for ext in ["exe", "cmd", "bat", "com"] {
+                names.push(dir.join(executable).with_extension(ext));
+            }
+        }
+        names
+    };
+    let search_path = std::env::var_os("PATH")?;
+    std::env::split_paths(&search_path)
+        .flat_map(|dir| candidates(&dir))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Starts the player of `profile` on `files`. When the template needs one,
+/// the playlist (with `info`) is first written to a new temp file in the
+/// profile's format, whose path is returned. Fails with `NotFound` naming the
+/// profile when its executable cannot be found.
+pub(crate) fn launch_player(
+    profile: &PlayerProfile,
+    files: &[RightPanelFile],
+    info: &PlaylistInfo,
+) -> std::io::Result<Option<PathBuf>> {
+    let executable = find_executable(&profile.executable).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "\"{}\" was not found; install {} or change its player profile",
+                profile.executable, profile.name
+            ),
+        )
+    })?;
+
+    let playlist = if profile.uses_playlist() {
+        let path = tempfile::Builder::new()
+            .prefix("playlist_ui_")
+            .suffix(&format!(".{}", profile.format.extension()))
+            .tempfile()?
+            .into_temp_path()
+            .keep()
+            .map_err(|e| e.error)?;
+        export_playlist(
+            profile.format,
+            files,
+            &path,
+            &ExportOptions::default(),
+            info,
+            &mut |_| {},
+        )?;
+        Some(path)
+    } else {
+        None
+    };
+
+    let paths: Vec<PathBuf> = files.iter().map(|f| f.path.clone()).collect();
+    Command::new(executable)
+        .args(profile.command_args(playlist.as_deref(), &paths))
+        .spawn()?;
+    Ok(playlist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_args_expand_placeholders() {
+        let files =
+            vec![PathBuf::from("/music/a b.mp3"), PathBuf::from("/b.ogg")];
+        let playlist = Path::new("/tmp/playlist_ui_1.m3u8");
+        let args = |template: &str| {
+            let profile = PlayerProfile {
+                args: template.to_string(),
+                ..Default::default()
+            };
+            profile
+                .command_args(
+                    profile.uses_playlist().then_some(playlist),
+                    &files,
+                )
+                .into_iter()
+                .map(|a| a.into_string().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            args("--playlist={playlist}"),
+            ["--playlist=/tmp/playlist_ui_1.m3u8"]
+        );
+        assert_eq!(
+            args("--enqueue {files}"),
+            ["--enqueue", "/music/a b.mp3", "/b.ogg"]
+        );
+        assert_eq!(
+            args(r#"--title "My Mix" -f"#),
+            ["--title", "My Mix", "-f", "/tmp/playlist_ui_1.m3u8"]
+        );
+        assert_eq!(args(""), ["/tmp/playlist_ui_1.m3u8"]);
+    }
+
+    #[test]
+    fn test_find_executable_reports_missing() {
+        assert!(find_executable("").is_none());
+        assert!(find_executable("playlist-ui-no-such-player").is_none());
+        assert!(find_executable("/nonexistent/dir/vlc").is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("player");
+        std::fs::write(&exe, b"").unwrap();
+        assert_eq!(find_executable(exe.to_str().unwrap()), Some(exe));
+    }
+
+    #[test]
+    fn test_launch_player_writes_unique_playlists() {
+        let files = vec![RightPanelFile {
+            path: "/music/a.mp3".into(),
+            ..Default::default()
+        }];
+        let missing = PlayerProfile {
+            name: "Nothing".to_string(),
+            executable: "playlist-ui-no-such-player".to_string(),
+            ..Default::default()
+        };
+        let err = launch_player(&missing, &files, &PlaylistInfo::default())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("playlist-ui-no-such-player"));
+
+        // `true` accepts and ignores any arguments
+        if find_executable("true").is_none() {
+            return;
+        }
+        let profile = PlayerProfile {
+            name: "True".to_string(),
+            executable: "true".to_string(),
+            ..Default::default()
+        };
+        let first = launch_player(&profile, &files, &PlaylistInfo::default())
+            .unwrap()
+            .unwrap();
+        let second = launch_player(&profile, &files, &PlaylistInfo::default())
+            .unwrap()
+            .unwrap();
+        assert_ne!(first, second);
+        assert_eq!(first.extension().unwrap(), "m3u8");
+        assert!(
+            std::fs::read_to_string(&first).unwrap().contains("/music/a.mp3")
+        );
+        std::fs::remove_file(first).unwrap();
+        std::fs::remove_file(second).unwrap();
+    }
+}
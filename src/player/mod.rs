@@ -0,0 +1,14 @@
+//! Playback module for the Playlist UI.
+//!
+//! Hands the right-panel playlist to something that can play it. External
+//! players (VLC, mpv, Audacious, Strawberry, or any user-defined command)
+//! are described by `PlayerProfile`s and launched with a temporary playlist
+//! file or the track paths on their command line. An MPD server is sent the
+//! tracks over its network protocol instead.
+//!
+//! Sub-modules:
+//!     external — player profiles and launching external players
+//!     mpd      — MPD protocol client: queue and stored playlists
+
+pub mod external;
+pub mod mpd;
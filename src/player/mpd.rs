@@ -0,0 +1,544 @@
+//! Sending playlists to an MPD (Music Player Daemon) server.
+//!
+//! Speaks the line-based MPD protocol over TCP: the server greets with
+//! `OK MPD <version>`, every command is answered by `OK` or by an
+//! `ACK [code@index] {command} message` line. Arguments are double-quoted
+//! with `"` and `\` escaped. Track batches are sent as one command list, so
+//! replacing the queue clears and refills it in a single exchange.
+//!
+//! MPD only knows tracks by their URI relative to its `music_directory`. The
+//! settings name the local directory that corresponds to that root (e.g. an
+//! NFS mount of the server's music), and each track path below it becomes a
+//! `/`-separated URI. Tracks outside the directory cannot be sent and are
+//! reported back instead.
+//!
+//! Public API:
+//!     DEFAULT_MPD_PORT — the port MPD listens on by default
+//!     MpdSettings      — server address, password, and local music directory
+//!     MpdAction        — replace or append the queue, or save a playlist
+//!     MpdSummary       — how many tracks were sent and which were skipped
+//!     MpdClient        — a connection speaking the MPD protocol
+//!     mpd_uri          — map a local path to an MPD URI
+//!     send_to_mpd      — connect and perform an action with the given tracks
+
+use crate::gui::RightPanelFile;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// The port MPD listens on unless configured otherwise.
+pub(crate) const DEFAULT_MPD_PORT: u16 = 6600;
+
+/// How long connecting, and waiting for any one response, may take.
+const MPD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// MPD's `ACK_ERROR_NO_EXIST`, e.g. removing a playlist that does not exist.
+const ACK_ERROR_NO_EXIST: u32 = 50;
+
+/// Where the MPD server is and how local paths map onto its library.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    bincode::Encode,
+    bincode::Decode,
+)]
+pub(crate) struct MpdSettings {
+    pub host: String,
+    pub port: u16,
+    /// Sent with the `password` command when not empty.
+    pub password: String,
+    /// Local directory corresponding to the server's `music_directory`.
+    pub music_directory: String,
+}
+
+impl Default for MpdSettings {
+    fn default() -> Self {
+        MpdSettings {
+            host: "localhost".to_string(),
+            port: DEFAULT_MPD_PORT,
+            password: String::new(),
+            music_directory: String::new(),
+        }
+    }
+}
+
+/// What to do with the tracks on the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MpdAction {
+    /// Clear the queue and fill it with the tracks.
+    ReplaceQueue,
+    /// Add the tracks to the end of the queue.
+    AppendToQueue,
+    /// Store the tracks as the named playlist, replacing one of that name.
+    SavePlaylist(String),
+}
+
+/// The outcome of a successful `send_to_mpd`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MpdSummary {
+    /// Number of tracks the server accepted.
+    pub sent: usize,
+    /// Tracks outside the music directory, which were not sent.
+    pub skipped: Vec<PathBuf>,
+}
+
+/// An open connection to an MPD server.
+pub(crate) struct MpdClient {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    /// Protocol version from the server's greeting.
+    pub version: String,
+}
+
+/// Quotes a command argument, escaping backslashes and double quotes.
+fn quote_arg(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Builds a command line from a command name and its arguments.
+fn command_line(command: &str, args: &[&str]) -> String {
+    let mut line = command.to_string();
+    for arg in args {
+        line.push(' ');
+        line.push_str(&quote_arg(arg));
+    }
+    line
+}
+
+/// Turns an `ACK [code@index] {command} message` line into an error.
+/// Missing objects become `NotFound`, everything else `Other`.
+fn ack_error(line: &str) -> std::io::Error {
+    let code = line
+        .strip_prefix("ACK [")
+        .and_then(|rest| rest.split('@').next())
+        .and_then(|code| code.parse::<u32>().ok());
+    let message = line.split_once("} ").map_or(line, |(_, m)| m);
+    let kind = if code == Some(ACK_ERROR_NO_EXIST) {
+        std::io::ErrorKind::NotFound
+    } else {
+        std::io::ErrorKind::Other
+    };
+    std::io::Error::new(kind, format!("MPD error: {message}"))
+}
+
+impl MpdClient {
+    /// Connects to `host:port` and reads the server's greeting.
+    pub(crate) fn connect(host: &str, port: u16) -> std::io::Result<Self> {
+        let mut last_error = None;
+        let mut stream = None;
+        for addr in (host, port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, MPD_TIMEOUT) {
+                Ok(s) => {
+                    stream = Some(s);
+                    break;
+                },
+                Err(e) => last_error = Some(e),
+            }
+        }
+        let stream = match (stream, last_error) {
+            (Some(stream), _) => stream,
+            (None, Some(e)) => return Err(e),
+            (None, None) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no address found for {host}"),
+                ));
+            },
+        };
+        stream.set_read_timeout(Some(MPD_TIMEOUT))?;
+        stream.set_write_timeout(Some(MPD_TIMEOUT))?;
+
+        let mut client = MpdClient {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            version: String::new(),
+        };
+        let greeting = client.read_line()?;
+        client.version = greeting
+            .strip_prefix("OK MPD ")
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("not an MPD server: {greeting}"),
+                )
+            })?
+            .to_string();
+        Ok(client)
+    }
+
+    fn read_line(&mut self) -> std::io::Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "MPD server closed the connection",
+            ));
+        }
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    /// Reads `key: value` lines up to the terminating `OK`.
+    fn read_response(&mut self) -> std::io::Result<Vec<(String, String)>> {
+        let mut pairs = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if line == "OK" {
+                return Ok(pairs);
+            }
+            if line.starts_with("ACK ") {
+                return Err(ack_error(&line));
+            }
+            if let Some((key, value)) = line.split_once(": ") {
+                pairs.push((key.to_string(), value.to_string()));
+            }
+        }
+    }
+
+    /// Sends one command and returns the `key: value` pairs of its answer.
+    pub(crate) fn command(
+        &mut self,
+        command: &str,
+        args: &[&str],
+    ) -> std::io::Result<Vec<(String, String)>> {
+        writeln!(self.writer, "{}", command_line(command, args))?;
+        self.read_response()
+    }
+
+    /// Sends the given command lines as one command list. The server stops
+    /// at the first failing command and reports it.
+    pub(crate) fn command_list(
+        &mut self,
+        lines: &[String],
+    ) -> std::io::Result<()> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let mut batch = String::from("command_list_begin\n");
+        for line in lines {
+            batch.push_str(line);
+            batch.push('\n');
+        }
+        batch.push_str("command_list_end\n");
+        self.writer.write_all(batch.as_bytes())?;
+        self.read_response().map(|_| ())
+    }
+
+    /// Authenticates with the server.
+    pub(crate) fn password(&mut self, password: &str) -> std::io::Result<()> {
+        self.command("password", &[password]).map(|_| ())
+    }
+
+    /// Clears the queue and adds `uris` in one command list.
+    pub(crate) fn replace_queue(
+        &mut self,
+        uris: &[String],
+    ) -> std::io::Result<()> {
+        let mut lines = vec!["clear".to_string()];
+        lines.extend(uris.iter().map(|uri| command_line("add", &[uri])));
+        self.command_list(&lines)
+    }
+
+    /// Adds `uris` to the end of the queue.
+    pub(crate) fn append_to_queue(
+        &mut self,
+        uris: &[String],
+    ) -> std::io::Result<()> {
+        let lines: Vec<String> =
+            uris.iter().map(|uri| command_line("add", &[uri])).collect();
+        self.command_list(&lines)
+    }
+
+    /// Stores `uris` as the playlist `name`, replacing an existing one.
+    pub(crate) fn save_playlist(
+        &mut self,
+        name: &str,
+        uris: &[String],
+    ) -> std::io::Result<()> {
+        match self.command("rm", &[name]) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            other => {
+                other?;
+            },
+        }
+        let lines: Vec<String> = uris
+            .iter()
+            .map(|uri| command_line("playlistadd", &[name, uri]))
+            .collect();
+        self.command_list(&lines)
+    }
+
+    /// Ends the session politely; the server closes the connection.
+    pub(crate) fn close(mut self) -> std::io::Result<()> {
+        writeln!(self.writer, "close")
+    }
+}
+
+/// Maps `path` to its URI relative to `music_directory`, or `None` when the
+/// path is not inside it.
+pub(crate) fn mpd_uri(music_directory: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(music_directory).ok()?;
+    let mut parts = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::CurDir => {},
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Connects to the server in `settings` and performs `action` with every
+/// track of `files` inside the music directory. Fails without connecting
+/// when no music directory is set or no track is inside it.
+pub(crate) fn send_to_mpd(
+    settings: &MpdSettings,
+    action: &MpdAction,
+    files: &[RightPanelFile],
+) -> std::io::Result<MpdSummary> {
+    let music_directory = Path::new(settings.music_directory.trim());
+    if music_directory.as_os_str().is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "no MPD music directory is set",
+        ));
+    }
+    let mut uris = Vec::new();
+    let mut skipped = Vec::new();
+    for file in files {
+        match mpd_uri(music_directory, &file.path) {
+            Some(uri) => uris.push(uri),
+            None => skipped.push(file.path.clone()),
+        }
+    }
+    if uris.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "no track is inside the MPD music directory \"{}\"",
+                music_directory.display()
+            ),
+        ));
+    }
+
+    let mut client = MpdClient::connect(settings.host.trim(), settings.port)?;
+    if !settings.password.is_empty() {
+        client.password(&settings.password)?;
+    }
+    match action {
+        MpdAction::ReplaceQueue => client.replace_queue(&uris)?,
+        MpdAction::AppendToQueue => client.append_to_queue(&uris)?,
+        MpdAction::SavePlaylist(name) => client.save_playlist(name, &uris)?,
+    }
+    client.close()?;
+    Ok(MpdSummary { sent: uris.len(), skipped })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// Starts a fake MPD server on a loopback port that accepts one client,
+    /// answers like MPD, and returns every line it received once the client
+    /// disconnects. Only `secret` is accepted as password, and only the
+    /// stored playlist `Existing` can be removed.
+    fn fake_mpd_server() -> (u16, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            writer.write_all(b"OK MPD 0.23.5\n").unwrap();
+
+            let mut received = Vec::new();
+            let mut in_list = false;
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                let command = line.trim_end().to_string();
+                line.clear();
+                received.push(command.clone());
+                let reply = match command.as_str() {
+                    "close" => break,
+                    "command_list_begin" => {
+                        in_list = true;
+                        continue;
+                    },
+                    "command_list_end" => {
+                        in_list = false;
+                        "OK\n".to_string()
+                    },
+                    _ if in_list => continue,
+                    "password \"secret\"" => "OK\n".to_string(),
+                    c if c.starts_with("password ") => {
+                        "ACK [3@0] {password} incorrect password\n".to_string()
+                    },
+                    "rm \"Existing\"" => "OK\n".to_string(),
+                    c if c.starts_with("rm ") => {
+                        "ACK [50@0] {rm} No such playlist\n".to_string()
+                    },
+                    _ => "ACK [5@0] {} unknown command\n".to_string(),
+                };
+                writer.write_all(reply.as_bytes()).unwrap();
+            }
+            received
+        });
+        (port, handle)
+    }
+
+    fn settings(port: u16) -> MpdSettings {
+        MpdSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            password: String::new(),
+            music_directory: "/music".to_string(),
+        }
+    }
+
+    fn files(paths: &[&str]) -> Vec<RightPanelFile> {
+        paths
+            .iter()
+            .map(|p| RightPanelFile { path: p.into(), ..Default::default() })
+            .collect()
+    }
+
+    #[test]
+    fn test_mpd_uri_is_relative_to_music_directory() {
+        let root = Path::new("/music");
+        assert_eq!(
+            mpd_uri(root, Path::new("/music/Rock/a b.mp3")),
+            Some("Rock/a b.mp3".to_string())
+        );
+        assert_eq!(mpd_uri(root, Path::new("/other/a.mp3")), None);
+        assert_eq!(mpd_uri(root, Path::new("/musical/a.mp3")), None);
+        assert_eq!(mpd_uri(root, Path::new("/music")), None);
+        assert_eq!(mpd_uri(root, Path::new("/music/../etc/passwd")), None);
+    }
+
+    #[test]
+    fn test_quote_arg_escapes_quotes_and_backslashes() {
+        assert_eq!(quote_arg("a b"), "\"a b\"");
+        assert_eq!(quote_arg(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+    }
+
+    #[test]
+    fn test_replace_queue_sends_one_command_list() {
+        let (port, server) = fake_mpd_server();
+        let summary = send_to_mpd(
+            &settings(port),
+            &MpdAction::ReplaceQueue,
+            &files(&[
+                "/music/a.mp3",
+                "/elsewhere/b.mp3",
+                "/music/Rock/\"c\".mp3",
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(summary.sent, 2);
+        assert_eq!(summary.skipped, [PathBuf::from("/elsewhere/b.mp3")]);
+        assert_eq!(
+            server.join().unwrap(),
+            [
+                "command_list_begin",
+                "clear",
+                "add \"a.mp3\"",
+                "add \"Rock/\\\"c\\\".mp3\"",
+                "command_list_end",
+                "close",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_append_authenticates_first() {
+        let (port, server) = fake_mpd_server();
+        let settings =
+            MpdSettings { password: "secret".to_string(), ..settings(port) };
+        send_to_mpd(
+            &settings,
+            &MpdAction::AppendToQueue,
+            &files(&["/music/a.mp3"]),
+        )
+        .unwrap();
+        assert_eq!(
+            server.join().unwrap(),
+            [
+                "password \"secret\"",
+                "command_list_begin",
+                "add \"a.mp3\"",
+                "command_list_end",
+                "close",
+            ]
+        );
+
+        let (port, server) = fake_mpd_server();
+        let settings =
+            MpdSettings { port, password: "wrong".to_string(), ..settings };
+        let err = send_to_mpd(
+            &settings,
+            &MpdAction::AppendToQueue,
+            &files(&["/music/a.mp3"]),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "MPD error: incorrect password");
+        assert_eq!(server.join().unwrap(), ["password \"wrong\""]);
+    }
+
+    #[test]
+    fn test_save_playlist_replaces_stored_playlist() {
+        // Removing a playlist that does not exist yet is not an error
+        for name in ["Existing", "New Mix"] {
+            let (port, server) = fake_mpd_server();
+            send_to_mpd(
+                &settings(port),
+                &MpdAction::SavePlaylist(name.to_string()),
+                &files(&["/music/a.mp3", "/music/b.mp3"]),
+            )
+            .unwrap();
+            let rm = format!("rm \"{name}\"");
+            let mut expected = vec![rm.as_str(), "command_list_begin"];
+            let adds = [
+                format!("playlistadd \"{name}\" \"a.mp3\""),
+                format!("playlistadd \"{name}\" \"b.mp3\""),
+            ];
+            expected.extend(adds.iter().map(String::as_str));
+            expected.extend(["command_list_end", "close"]);
+            assert_eq!(server.join().unwrap(), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_send_without_tracks_in_music_directory_fails() {
+        let err = send_to_mpd(
+            &settings(1),
+            &MpdAction::ReplaceQueue,
+            &files(&["/elsewhere/a.mp3"]),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        let unset =
+            MpdSettings { music_directory: String::new(), ..settings(1) };
+        let err =
+            send_to_mpd(&unset, &MpdAction::ReplaceQueue, &files(&["a.mp3"]))
+                .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -4,6 +4,10 @@
 //!     file_field_matches — case-insensitive substring check on optional
 //!                          string fields
 //!     format_duration     — convert milliseconds to `M:SS` or `H:MM:SS`
+//!     format_file_size    — convert a byte count to `KB`, `MB` or `GB`
+//!     format_totals       — describe an item count and total duration
+//!     format_filtered_totals — describe the shown part of a list of items
+//!     run_blocking        — run blocking work on its own thread, as a future
 
 /// Checks whether an optional string field contains the given query
 /// (case-insensitive). Returns `false` when the field is `None`.
@@ -34,9 +38,78 @@ pub(crate) fn format_duration(duration_ms: Option<u64>) -> String {
     }
 }
 
+/// Formats an optional file size in bytes with one decimal in the largest
+///     binary unit below it, e.g. `4.2 MB`; sizes under 1 KB are given in
+///     bytes. Returns an empty string if the input is `None`.
+pub(crate) fn format_file_size(bytes: Option<u64>) -> String {
+    let Some(bytes) = bytes else {
+        return String::new();
+    };
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next in ["KB", "MB", "GB"] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    if unit == "B" { format!("{bytes} B") } else { format!("{size:.1} {unit}") }
+}
+
+/// Describes a list of items and their summed duration, e.g.
+///     `3 Items, Time: 12:34`, as shown under the playlist and in printed
+///     track listings.
+pub(crate) fn format_totals(
+    item_count: usize,
+    total_duration_ms: u64,
+) -> String {
+    format!(
+        "{} Item{}, Time: {}",
+        item_count,
+        if item_count == 1 { "" } else { "s" },
+        format_duration(Some(total_duration_ms)),
+    )
+}
+
+/// Describes the shown part of a list of items next to the whole list, e.g.
+///     `3 of 120 Items, Time: 12:34 of 8:01:15`, as shown under a filtered
+///     playlist.
+pub(crate) fn format_filtered_totals(
+    shown_count: usize,
+    shown_duration_ms: u64,
+    item_count: usize,
+    total_duration_ms: u64,
+) -> String {
+    format!(
+        "{} of {} Item{}, Time: {} of {}",
+        shown_count,
+        item_count,
+        if item_count == 1 { "" } else { "s" },
+        format_duration(Some(shown_duration_ms)),
+        format_duration(Some(total_duration_ms)),
+    )
+}
+
+/// Runs the blocking `work` (file or network I/O) on its own thread and
+///     resolves with its result, so `Task::perform` never ties up an iced
+///     executor worker with it.
+pub(crate) async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> T {
+    let (sender, receiver) = iced::futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+    receiver.await.expect("blocking work panicked")
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{file_field_matches, format_duration};
+    use super::{
+        file_field_matches, format_duration, format_file_size,
+        format_filtered_totals, format_totals, run_blocking,
+    };
 
     // ── file_field_matches tests ──────────────────────────────────────
 
@@ -139,4 +212,47 @@ mod tests {
         // 1 hour, 16 minutes, 33 seconds = 4593 seconds = 4_593_000 ms
         assert_eq!(format_duration(Some(4_593_000)), "1:16:33");
     }
+
+    // ── format_file_size tests ────────────────────────────────────────
+
+    #[test]
+    fn test_format_file_size_units() {
+        assert_eq!(format_file_size(None), "");
+        assert_eq!(format_file_size(Some(0)), "0 B");
+        assert_eq!(format_file_size(Some(1023)), "1023 B");
+        assert_eq!(format_file_size(Some(1536)), "1.5 KB");
+        assert_eq!(format_file_size(Some(4_404_019)), "4.2 MB");
+        assert_eq!(format_file_size(Some(3 * 1024 * 1024 * 1024)), "3.0 GB");
+    }
+
+    // ── format_totals tests ───────────────────────────────────────────
+
+    #[test]
+    fn test_format_totals_pluralizes_items() {
+        assert_eq!(format_totals(0, 0), "0 Items, Time: 0:00");
+        assert_eq!(format_totals(1, 61_000), "1 Item, Time: 1:01");
+        assert_eq!(format_totals(12, 3_723_000), "12 Items, Time: 1:02:03");
+    }
+    #[test]
+    fn test_format_filtered_totals() {
+        assert_eq!(
+            format_filtered_totals(2, 61_000, 120, 3_723_000),
+            "2 of 120 Items, Time: 1:01 of 1:02:03"
+        );
+        assert_eq!(
+            format_filtered_totals(0, 0, 1, 5_000),
+            "0 of 1 Item, Time: 0:00 of 0:05"
+        );
+    }
+
+    // ── run_blocking tests ────────────────────────────────────────────
+
+    #[test]
+    fn test_run_blocking_runs_on_another_thread() {
+        let caller = std::thread::current().id();
+        let worker = iced::futures::executor::block_on(run_blocking(|| {
+            std::thread::current().id()
+        }));
+        assert_ne!(worker, caller);
+    }
 }